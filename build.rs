@@ -27,4 +27,8 @@ fn main() {
     println!("cargo:rustc-link-lib=xkbcommon");
 
     println!("cargo:rerun-if-changed=cpp/");
+
+    // Install directories, used to find system configuration and themes
+    println!("cargo:rerun-if-env-changed=TLOCKR_SYSCONFDIR");
+    println!("cargo:rerun-if-env-changed=TLOCKR_DATADIR");
 }
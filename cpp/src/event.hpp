@@ -6,13 +6,14 @@
 #ifndef EVENT_HPP
 #define EVENT_HPP
 
+#include <cstddef>
 #include <cstdint>
 
 #ifdef __cplusplus
 extern "C" {
 #endif
 
-enum class EventType : uint64_t {
+enum class EventType : uint16_t {
     Wayland = 1,
     Renderer = 2,
 
@@ -26,15 +27,307 @@ enum class EventType : uint64_t {
 
     AuthSubmit = 9,
     AuthStateUpdate = 10,
+
+    ThemeReload = 11,
+
+    AuthPrompt = 12,
+
+    PointerEnter = 13,
+    PointerLeave = 14,
+    PointerAxis = 15,
+
+    TouchFrame = 16,
+    TouchCancel = 17,
+
+    SeatName = 18,
+    SeatRemoved = 19,
+
+    TextInputUpdate = 20,
+    TextInputPreedit = 21,
+    TextInputCommit = 22,
+
+    PowerStatus = 23,
+    NetworkStatus = 24,
+
+    MediaStatus = 25,
+    MediaAction = 26,
+
+    Notification = 27,
 };
 
-typedef uint64_t EventParam;
+/// Marks the start of every message header, "TLKR" in native byte order
+constexpr uint32_t EVENT_MESSAGE_MAGIC = 0x524b4c54;
+
+/// Version of the event protocol, must match `PROTOCOL_VERSION` in
+/// `src/event/message.rs`
+constexpr uint16_t EVENT_PROTOCOL_VERSION = 2;
 
-typedef struct {
+/// Maximum size of a message, including its header
+///
+/// Pipe writes of up to `PIPE_BUF` bytes are atomic, so a message is never
+/// split or interleaved with a message written by another thread.
+constexpr size_t EVENT_MAX_MESSAGE_SIZE = 4096;
+
+/// Header sent before every message payload
+///
+/// `length` is the size of the payload in bytes.
+struct EventHeader {
+    uint32_t magic;
+    uint16_t version;
     EventType event_type;
-    EventParam param_1;
-    EventParam param_2;
-} Event;
+    uint32_t length;
+};
+
+static_assert(sizeof(EventHeader) == 12, "EventHeader must be 12 bytes");
+
+constexpr size_t EVENT_MAX_PAYLOAD_SIZE =
+    EVENT_MAX_MESSAGE_SIZE - sizeof(EventHeader);
+
+/// A complete message, as read from an event pipe
+struct EventMessage {
+    EventHeader header;
+    uint8_t payload[EVENT_MAX_PAYLOAD_SIZE];
+};
+
+/*
+    Payloads for each `EventType`, these must match the schema of `Event` in
+    `src/event/event.rs`. Payloads are packed, as fields are sent without
+    padding.
+
+    Input payloads start with `seat`, the registry name of the `wl_seat` the
+    input came from.
+
+    `AuthSubmit` and `AuthPrompt` payloads are UTF-8 text filling the whole
+    payload. `ThemeReload` has an empty payload. `PointerLeave`,
+    `TouchCancel` and `SeatRemoved` payloads are a `SeatPayload`.
+
+    `TouchFrame` payloads are a `SeatPayload` followed by an array of
+    `TouchPointPayload`, one for each touch point that changed. `SeatName`
+    payloads are a `SeatPayload` followed by the UTF-8 seat name.
+
+    `TextInputPreedit` and `TextInputCommit` payloads are followed by UTF-8
+    text filling the rest of the payload. `NetworkStatus` payloads are
+    followed by the UTF-8 interface name.
+
+    `MediaStatus` payloads are followed by the player, title, artist, album
+    and art URL, each as a `uint32_t` byte length followed by UTF-8 text.
+    `Notification` payloads are followed by the application name, summary
+    and body in the same way.
+*/
+
+#pragma pack(push, 1)
+
+struct RendererPayload {
+    uint64_t buffer;
+};
+
+struct SeatPayload {
+    uint32_t seat;
+};
+
+struct KeyboardKeymapPayload {
+    uint32_t seat;
+    int32_t fd;
+    uint32_t size;
+};
+
+struct KeyboardKeyPayload {
+    uint32_t seat;
+    uint32_t key;
+    uint32_t state;
+};
+
+struct KeyboardModifiersPayload {
+    uint32_t seat;
+    uint32_t depressed;
+    uint32_t latched;
+    uint32_t locked;
+    uint32_t group;
+};
+
+struct KeyboardRepeatInfoPayload {
+    uint32_t seat;
+    int32_t rate;
+    int32_t delay;
+};
+
+struct PointerMotionPayload {
+    uint32_t seat;
+    double x;
+    double y;
+};
+
+struct PointerButtonPayload {
+    uint32_t seat;
+    uint32_t button;
+    uint32_t state;
+};
+
+struct AuthStateUpdatePayload {
+    uint32_t state;
+};
+
+struct PointerEnterPayload {
+    uint32_t seat;
+    double x;
+    double y;
+};
+
+/// `PointerAxisPayload` source, when the compositor did not send one
+constexpr uint32_t AXIS_SOURCE_UNKNOWN = UINT32_MAX;
+
+/// `PointerAxisPayload` stop flags
+constexpr uint32_t AXIS_STOP_VERTICAL = 1 << 0;
+constexpr uint32_t AXIS_STOP_HORIZONTAL = 1 << 1;
+
+/// Scrolling over one pointer frame
+///
+/// `horizontal` and `vertical` are in surface pixels, the `_120` values are
+/// in fractions of a wheel step, where 120 is one step.
+struct PointerAxisPayload {
+    uint32_t seat;
+    double horizontal;
+    double vertical;
+    int32_t horizontal_120;
+    int32_t vertical_120;
+    uint32_t source;
+    uint32_t stop;
+};
+
+/// How a touch point changed during a touch frame
+enum class TouchPointState : uint32_t {
+    Down = 1,
+    Motion = 2,
+    Up = 3,
+};
+
+/// A touch point sent in a `TouchFrame` event
+///
+/// The position of an `Up` point is not sent, the last position of the point
+/// is used instead.
+struct TouchPointPayload {
+    double x;
+    double y;
+    int32_t id;
+    TouchPointState state;
+};
+
+/// The text field focused in the renderer, sent from the renderer
+///
+/// `hint` and `purpose` are `zwp_text_input_v3` content types, and the
+/// cursor rectangle is in surface coordinates. The other fields are ignored
+/// when `enabled` is 0.
+struct TextInputUpdatePayload {
+    uint32_t enabled;
+    uint32_t hint;
+    uint32_t purpose;
+    int32_t x;
+    int32_t y;
+    int32_t width;
+    int32_t height;
+};
+
+/// Composing text from an input method
+///
+/// The cursor positions are byte offsets into the text, the cursor is hidden
+/// when both are -1.
+struct TextInputPreeditPayload {
+    uint32_t seat;
+    int32_t cursor_begin;
+    int32_t cursor_end;
+};
+
+/// Text from an input method, inserted at the cursor
+///
+/// `delete_before` and `delete_after` are the number of bytes around the
+/// cursor to delete before inserting the text.
+struct TextInputCommitPayload {
+    uint32_t seat;
+    uint32_t delete_before;
+    uint32_t delete_after;
+};
+
+/// Status of the batteries and power adapters
+///
+/// `level` is a percentage, the battery fields are meaningless when
+/// `present` is 0.
+struct PowerStatusPayload {
+    uint32_t present;
+    uint32_t level;
+    uint32_t charging;
+    uint32_t plugged_in;
+};
+
+/// Status of the network connection
+struct NetworkStatusPayload {
+    uint32_t connected;
+    uint32_t wireless;
+};
+
+/// Playback status of a media player
+enum class MediaPlaybackStatus : uint32_t {
+    Stopped = 0,
+    Paused = 1,
+    Playing = 2,
+};
+
+/// `MediaStatusPayload` capability flags
+constexpr uint32_t MEDIA_CAN_PLAY = 1 << 0;
+constexpr uint32_t MEDIA_CAN_PAUSE = 1 << 1;
+constexpr uint32_t MEDIA_CAN_GO_NEXT = 1 << 2;
+constexpr uint32_t MEDIA_CAN_GO_PREVIOUS = 1 << 3;
+
+/// Status of the active media player
+///
+/// The other fields are meaningless when `available` is 0.
+struct MediaStatusPayload {
+    uint32_t available;
+    MediaPlaybackStatus status;
+    uint32_t capabilities;
+};
+
+/// A control action for the active media player, sent from the renderer
+enum class MediaAction : uint32_t {
+    PlayPause = 1,
+    Play = 2,
+    Pause = 3,
+    Next = 4,
+    Previous = 5,
+};
+
+struct MediaActionPayload {
+    MediaAction action;
+};
+
+/// A notification received while locked
+///
+/// A notification replacing an earlier one has the same `key`.
+struct NotificationPayload {
+    uint32_t key;
+};
+
+#pragma pack(pop)
+
+static_assert(sizeof(RendererPayload) == 8);
+static_assert(sizeof(SeatPayload) == 4);
+static_assert(sizeof(KeyboardKeymapPayload) == 12);
+static_assert(sizeof(KeyboardKeyPayload) == 12);
+static_assert(sizeof(KeyboardModifiersPayload) == 20);
+static_assert(sizeof(KeyboardRepeatInfoPayload) == 12);
+static_assert(sizeof(PointerMotionPayload) == 20);
+static_assert(sizeof(PointerButtonPayload) == 12);
+static_assert(sizeof(AuthStateUpdatePayload) == 4);
+static_assert(sizeof(PointerEnterPayload) == 20);
+static_assert(sizeof(PointerAxisPayload) == 36);
+static_assert(sizeof(TouchPointPayload) == 24);
+static_assert(sizeof(TextInputUpdatePayload) == 28);
+static_assert(sizeof(TextInputPreeditPayload) == 12);
+static_assert(sizeof(TextInputCommitPayload) == 12);
+static_assert(sizeof(PowerStatusPayload) == 16);
+static_assert(sizeof(NetworkStatusPayload) == 8);
+static_assert(sizeof(MediaStatusPayload) == 12);
+static_assert(sizeof(MediaActionPayload) == 4);
+static_assert(sizeof(NotificationPayload) == 4);
 
 #ifdef __cplusplus
 }
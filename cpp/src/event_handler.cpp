@@ -7,15 +7,36 @@
 #include "keyboard_repeat.hpp"
 #include "logging.hpp"
 #include "pointer.hpp"
+#include "preview.hpp"
 #include "render.hpp"
+#include "text_input.hpp"
+#include "touch.hpp"
+#include <cstddef>
+#include <cstring>
 #include <errno.h>
 #include <iostream>
 #include <unistd.h>
+#include <vector>
 
 static const char *FILENAME = "tlockr_qt/event_handler.cpp";
 
-int readEvent(int fd, Event *event) {
-    ssize_t res = read(fd, event, sizeof(Event));
+/// Discard everything waiting on the event pipe
+///
+/// This is used after an invalid header, as the position of the next message
+/// is unknown. Messages are written atomically, so the pipe is empty at a
+/// message boundary.
+static void discardPending(int fd) {
+    uint8_t buffer[EVENT_MAX_MESSAGE_SIZE];
+    while (read(fd, buffer, sizeof(buffer)) > 0) {
+    }
+}
+
+/// Read a single message from the event pipe
+///
+/// Returns -1 if no message was waiting, or if the message was truncated or
+/// has an invalid header.
+int readEvent(int fd, EventMessage *message) {
+    ssize_t res = read(fd, &message->header, sizeof(EventHeader));
     if (res == -1) {
         if (errno == EAGAIN || errno == EWOULDBLOCK) {
             return -1;
@@ -25,83 +46,533 @@ int readEvent(int fd, Event *event) {
                 format_log("Failed to read event: ", strerror(errno)).c_str());
             return -1;
         }
-    } else if (res != sizeof(Event)) {
-        error_log(FILENAME, format_log("Partial read: expected ", sizeof(Event),
-                                       " bytes, got ", res)
+    } else if (res != sizeof(EventHeader)) {
+        error_log(FILENAME,
+                  format_log("Truncated event header: expected ",
+                             sizeof(EventHeader), " bytes, got ", res)
+                      .c_str());
+        return -1;
+    }
+
+    const EventHeader &header = message->header;
+    if (header.magic != EVENT_MESSAGE_MAGIC) {
+        error_log(FILENAME, format_log("Invalid event message magic: ",
+                                       header.magic)
                                 .c_str());
+        discardPending(fd);
+        return -1;
+    }
+
+    if (header.version != EVENT_PROTOCOL_VERSION) {
+        error_log(FILENAME,
+                  format_log("Unsupported event protocol version ",
+                             header.version, ", expected ",
+                             EVENT_PROTOCOL_VERSION)
+                      .c_str());
+        discardPending(fd);
+        return -1;
+    }
+
+    if (header.length > EVENT_MAX_PAYLOAD_SIZE) {
+        error_log(FILENAME,
+                  format_log("Event payload of ", header.length,
+                             " bytes exceeds the maximum of ",
+                             EVENT_MAX_PAYLOAD_SIZE, " bytes")
+                      .c_str());
+        discardPending(fd);
+        return -1;
+    }
+
+    if (header.length == 0) {
+        return 0;
+    }
+
+    res = read(fd, message->payload, header.length);
+    if (res != static_cast<ssize_t>(header.length)) {
+        error_log(FILENAME,
+                  format_log("Truncated event payload: expected ",
+                             header.length, " bytes, got ", res)
+                      .c_str());
         return -1;
     }
 
     return 0;
 }
 
-EventHandler::EventHandler(QmlRenderer *renderer,
-                           KeyboardRepeatEngine *keyboardRepeatEngine)
-    : m_renderer(renderer), m_keyboardRepeatEngine(keyboardRepeatEngine) {
-    m_keyboardHandler = new KeyboardHandler(renderer);
-    m_pointerHandler = new PointerHandler(renderer, m_keyboardHandler);
+/// Copy the payload of `message` into `payload`
+///
+/// Returns false, and logs an error, if the payload is not the size expected
+/// for its event type.
+template <typename T>
+static bool decodePayload(const EventMessage &message, T *payload) {
+    if (message.header.length != sizeof(T)) {
+        error_log(FILENAME,
+                  format_log("Rejected event type ",
+                             static_cast<uint16_t>(message.header.event_type),
+                             ": expected ", sizeof(T), " byte payload, got ",
+                             message.header.length)
+                      .c_str());
+        return false;
+    }
+
+    std::memcpy(payload, message.payload, sizeof(T));
+    return true;
+}
+
+/// Close the keymap file descriptor of a rejected `KeyboardKeymap` message
+///
+/// The descriptor is duplicated for the renderer, so it must be closed even
+/// if the rest of the payload is invalid.
+static void closeRejectedKeymap(const EventMessage &message) {
+    const size_t offset = offsetof(KeyboardKeymapPayload, fd);
+    if (message.header.length < offset + sizeof(int32_t)) {
+        return;
+    }
+
+    int32_t fd;
+    std::memcpy(&fd, message.payload + offset, sizeof(fd));
+    if (fd >= 0) {
+        close(fd);
+    }
+}
+
+/// Returns the payload of `message` from `offset` as UTF-8 text
+static QString decodeText(const EventMessage &message, size_t offset = 0) {
+    return QString::fromUtf8(
+        reinterpret_cast<const char *>(message.payload) + offset,
+        message.header.length - offset);
+}
+
+/// Copy the fixed size start of a variable length payload into `payload`
+///
+/// Returns false, and logs an error, if the payload is too short.
+template <typename T>
+static bool decodePayloadPrefix(const EventMessage &message, T *payload) {
+    if (message.header.length < sizeof(T)) {
+        error_log(FILENAME,
+                  format_log("Rejected event type ",
+                             static_cast<uint16_t>(message.header.event_type),
+                             ": expected at least ", sizeof(T),
+                             " byte payload, got ", message.header.length)
+                      .c_str());
+        return false;
+    }
+
+    std::memcpy(payload, message.payload, sizeof(T));
+    return true;
+}
+
+/// Returns the payload of `message` from `offset` as raw bytes
+static QByteArray decodeBytes(const EventMessage &message, size_t offset) {
+    return QByteArray(reinterpret_cast<const char *>(message.payload) + offset,
+                      message.header.length - offset);
 }
 
+/// Read a UTF-8 string, preceded by its length in bytes as a `uint32_t`,
+/// from the payload of `message` at `offset`
+///
+/// `offset` is moved past the string. Returns false, and logs an error, if
+/// the payload is too short.
+static bool decodeTextField(const EventMessage &message, size_t &offset,
+                            QString *text) {
+    uint32_t length;
+    if (message.header.length < offset + sizeof(length)) {
+        error_log(FILENAME, "Rejected event: truncated text length");
+        return false;
+    }
+    std::memcpy(&length, message.payload + offset, sizeof(length));
+    offset += sizeof(length);
+
+    if (message.header.length - offset < length) {
+        error_log(FILENAME, "Rejected event: truncated text");
+        return false;
+    }
+    *text = QString::fromUtf8(
+        reinterpret_cast<const char *>(message.payload) + offset, length);
+    offset += length;
+    return true;
+}
+
+SeatHandlers::SeatHandlers(QmlRenderer *renderer, uint32_t seat)
+    : name(QString("seat%1").arg(seat)) {
+    keyboardRepeatEngine = new KeyboardRepeatEngine(renderer);
+    keyboardHandler = new KeyboardHandler(renderer, keyboardRepeatEngine);
+    pointerHandler = new PointerHandler(renderer, keyboardHandler);
+    touchHandler = new TouchHandler(renderer, keyboardHandler, seat);
+}
+
+SeatHandlers::~SeatHandlers() {
+    delete touchHandler;
+    delete pointerHandler;
+    delete keyboardHandler;
+    delete keyboardRepeatEngine;
+}
+
+EventHandler::EventHandler(QmlRenderer *renderer)
+    : m_renderer(renderer),
+      m_textInputHandler(std::make_unique<TextInputHandler>(renderer)) {}
+
 EventHandler::~EventHandler() = default;
 
-int EventHandler::processEvent(EventType event_type, EventParam param_1,
-                               EventParam param_2) {
+/// Returns the handlers for `seat`, creating them on its first event
+///
+/// Until the compositor names the seat, it is named after its id.
+SeatHandlers *EventHandler::seatHandlers(uint32_t seat) {
+    auto it = m_seats.find(seat);
+    if (it == m_seats.end()) {
+        debug_log(FILENAME,
+                  format_log("Created handlers for seat ", seat).c_str());
+        it = m_seats.emplace(seat, std::make_unique<SeatHandlers>(m_renderer,
+                                                                  seat))
+                 .first;
+    }
+
+    return it->second.get();
+}
+
+/// Mark the seat of `handlers` as the seat the user is interacting with
+void EventHandler::setActiveSeat(SeatHandlers *handlers) {
+    m_activeSeat = handlers;
+    m_renderer->interface->setActiveSeat(handlers->name);
+    publishKeyboardState(handlers);
+}
+
+/// Update the keyboard properties of the interface from the keyboard of
+/// `handlers`
+///
+/// Only the active seat's keyboard is shown. Until a seat is active, the
+/// last keyboard to change is shown.
+void EventHandler::publishKeyboardState(SeatHandlers *handlers) {
+    if (m_activeSeat && m_activeSeat != handlers) {
+        return;
+    }
+
+    const KeyboardHandler *keyboard = handlers->keyboardHandler;
+    m_renderer->interface->setKeyboardState(
+        keyboard->layoutName(), keyboard->layoutNames(), keyboard->capsLock(),
+        keyboard->numLock());
+}
+
+/// Switch the active seat's keyboard to its next layout
+void EventHandler::cycleKeyboardLayout() {
+    SeatHandlers *handlers = m_activeSeat;
+    if (!handlers && !m_seats.empty()) {
+        handlers = m_seats.begin()->second.get();
+    }
+
+    if (!handlers) {
+        warn_log(FILENAME, "No keyboard available to switch layout");
+        return;
+    }
+
+    handlers->keyboardHandler->cycleLayout();
+    publishKeyboardState(handlers);
+}
+
+int EventHandler::processEvent(const EventMessage &message) {
+    const EventType event_type = message.header.event_type;
+
     switch (event_type) {
         case EventType::KeyboardKeymap: {
-            m_keyboardHandler->handleKeymapEvent(param_1, param_2);
+            KeyboardKeymapPayload payload;
+            if (!decodePayload(message, &payload)) {
+                closeRejectedKeymap(message);
+                return -1;
+            }
+            if (payload.size == 0) {
+                error_log(FILENAME, "Rejected empty keymap");
+                closeRejectedKeymap(message);
+                return -1;
+            }
+            SeatHandlers *handlers = seatHandlers(payload.seat);
+            handlers->keyboardHandler->handleKeymapEvent(payload.fd,
+                                                         payload.size);
+            publishKeyboardState(handlers);
             break;
         }
         case EventType::KeyboardModifiers: {
-            // Modifiers bit packed:
-            // param_1: 31 [mods_depressed] [mods_latched] 0
-            // param_2: 31 [  mods_locked ] [    group   ] 0
-            m_keyboardHandler->handleModifiersEvent(
-                param_1 >> 32, param_1 & 0xFFFF, param_2 >> 32,
-                param_2 & 0xFFFF);
+            KeyboardModifiersPayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+            SeatHandlers *handlers = seatHandlers(payload.seat);
+            handlers->keyboardHandler->handleModifiersEvent(
+                payload.depressed, payload.latched, payload.locked,
+                payload.group);
+            publishKeyboardState(handlers);
             break;
         }
         case EventType::KeyboardKey: {
-            m_keyboardHandler->handleKeyEvent(param_1,
-                                              static_cast<KeyState>(param_2));
+            KeyboardKeyPayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+            SeatHandlers *handlers = seatHandlers(payload.seat);
+            const KeyState state = static_cast<KeyState>(payload.state);
+            if (state == KeyState::Pressed) {
+                setActiveSeat(handlers);
+            }
+            handlers->keyboardHandler->handleKeyEvent(payload.key, state);
+            publishKeyboardState(handlers);
+            m_textInputHandler->scheduleUpdate();
             break;
         }
         case EventType::KeyboardRepeatInfo: {
-            m_keyboardRepeatEngine->setRepeatInfo(param_1, param_2);
+            KeyboardRepeatInfoPayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+            seatHandlers(payload.seat)->keyboardRepeatEngine->setRepeatInfo(
+                payload.rate, payload.delay);
             break;
         }
         case EventType::PointerMotion: {
-            double surface_x = *reinterpret_cast<const double *>(&param_1);
-            double surface_y = *reinterpret_cast<const double *>(&param_2);
-            m_pointerHandler->handleMotionEvent(surface_x, surface_y);
+            PointerMotionPayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+            seatHandlers(payload.seat)->pointerHandler->handleMotionEvent(
+                payload.x, payload.y);
             break;
         }
         case EventType::PointerButton: {
-            m_pointerHandler->handleButtonEvent(
-                param_1, static_cast<ButtonState>(param_2));
+            PointerButtonPayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+            SeatHandlers *handlers = seatHandlers(payload.seat);
+            const ButtonState state = static_cast<ButtonState>(payload.state);
+            if (state == ButtonState::Pressed) {
+                setActiveSeat(handlers);
+            }
+            handlers->pointerHandler->handleButtonEvent(payload.button, state);
+            m_textInputHandler->scheduleUpdate();
+            break;
+        }
+        case EventType::PointerEnter: {
+            PointerEnterPayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+            seatHandlers(payload.seat)->pointerHandler->handleEnterEvent(
+                payload.x, payload.y);
+            break;
+        }
+        case EventType::PointerLeave: {
+            SeatPayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+            seatHandlers(payload.seat)->pointerHandler->handleLeaveEvent();
+            break;
+        }
+        case EventType::PointerAxis: {
+            PointerAxisPayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+            SeatHandlers *handlers = seatHandlers(payload.seat);
+            setActiveSeat(handlers);
+            handlers->pointerHandler->handleAxisEvent(payload);
+            break;
+        }
+        case EventType::TouchFrame: {
+            SeatPayload seat;
+            if (!decodePayloadPrefix(message, &seat)) {
+                return -1;
+            }
+
+            const size_t length = message.header.length - sizeof(SeatPayload);
+            if (length % sizeof(TouchPointPayload) != 0) {
+                error_log(FILENAME,
+                          format_log("Rejected touch frame: ", length,
+                                     " bytes is not a whole number of points")
+                              .c_str());
+                return -1;
+            }
+
+            std::vector<TouchPointPayload> points(
+                length / sizeof(TouchPointPayload));
+            std::memcpy(points.data(), message.payload + sizeof(SeatPayload),
+                        length);
+
+            SeatHandlers *handlers = seatHandlers(seat.seat);
+            for (const TouchPointPayload &point : points) {
+                if (point.state == TouchPointState::Down) {
+                    setActiveSeat(handlers);
+                    break;
+                }
+            }
+            handlers->touchHandler->handleFrameEvent(points);
+            break;
+        }
+        case EventType::TouchCancel: {
+            SeatPayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+            seatHandlers(payload.seat)->touchHandler->handleCancelEvent();
+            break;
+        }
+        case EventType::SeatName: {
+            SeatPayload payload;
+            if (!decodePayloadPrefix(message, &payload)) {
+                return -1;
+            }
+            SeatHandlers *handlers = seatHandlers(payload.seat);
+            handlers->name = decodeText(message, sizeof(SeatPayload));
+            if (m_activeSeat == handlers) {
+                m_renderer->interface->setActiveSeat(handlers->name);
+            }
+            break;
+        }
+        case EventType::SeatRemoved: {
+            SeatPayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+
+            auto it = m_seats.find(payload.seat);
+            if (it != m_seats.end()) {
+                // Release anything still held down by the removed seat
+                it->second->keyboardRepeatEngine->reset();
+                it->second->pointerHandler->handleLeaveEvent();
+                it->second->touchHandler->handleCancelEvent();
+                if (m_activeSeat == it->second.get()) {
+                    m_activeSeat = nullptr;
+                }
+                m_seats.erase(it);
+            }
+            break;
+        }
+        case EventType::TextInputPreedit: {
+            TextInputPreeditPayload payload;
+            if (!decodePayloadPrefix(message, &payload)) {
+                return -1;
+            }
+            m_textInputHandler->handlePreeditEvent(
+                decodeBytes(message, sizeof(payload)), payload.cursor_begin,
+                payload.cursor_end);
+            break;
+        }
+        case EventType::TextInputCommit: {
+            TextInputCommitPayload payload;
+            if (!decodePayloadPrefix(message, &payload)) {
+                return -1;
+            }
+            setActiveSeat(seatHandlers(payload.seat));
+            m_textInputHandler->handleCommitEvent(
+                decodeBytes(message, sizeof(payload)), payload.delete_before,
+                payload.delete_after);
+            break;
+        }
+        case EventType::PowerStatus: {
+            PowerStatusPayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+            m_renderer->interface->battery()->update(
+                payload.present != 0, static_cast<int>(payload.level),
+                payload.charging != 0, payload.plugged_in != 0);
+            break;
+        }
+        case EventType::NetworkStatus: {
+            NetworkStatusPayload payload;
+            if (!decodePayloadPrefix(message, &payload)) {
+                return -1;
+            }
+            m_renderer->interface->network()->update(
+                payload.connected != 0, payload.wireless != 0,
+                decodeText(message, sizeof(payload)));
+            break;
+        }
+        case EventType::MediaStatus: {
+            MediaStatusPayload payload;
+            if (!decodePayloadPrefix(message, &payload)) {
+                return -1;
+            }
+            if (payload.status > MediaPlaybackStatus::Playing) {
+                error_log(FILENAME,
+                          format_log("Rejected unknown playback status ",
+                                     static_cast<uint32_t>(payload.status))
+                              .c_str());
+                return -1;
+            }
+
+            size_t offset = sizeof(payload);
+            QString player, title, artist, album, artUrl;
+            for (QString *text : {&player, &title, &artist, &album, &artUrl}) {
+                if (!decodeTextField(message, offset, text)) {
+                    return -1;
+                }
+            }
+
+            m_renderer->interface->media()->update(
+                payload.available != 0, payload.status, payload.capabilities,
+                player, title, artist, album, artUrl);
+            break;
+        }
+        case EventType::Notification: {
+            NotificationPayload payload;
+            if (!decodePayloadPrefix(message, &payload)) {
+                return -1;
+            }
+
+            size_t offset = sizeof(payload);
+            QString appName, summary, body;
+            for (QString *text : {&appName, &summary, &body}) {
+                if (!decodeTextField(message, offset, text)) {
+                    return -1;
+                }
+            }
+
+            m_renderer->interface->notifications()->update(
+                payload.key, appName, summary, body);
             break;
         }
         case EventType::AuthStateUpdate: {
+            AuthStateUpdatePayload payload;
+            if (!decodePayload(message, &payload)) {
+                return -1;
+            }
+            if (payload.state > Interface::AuthState::Success) {
+                error_log(FILENAME, format_log("Rejected unknown auth state ",
+                                               payload.state)
+                                        .c_str());
+                return -1;
+            }
             emit m_renderer->interface->authStateChange(
-                static_cast<Interface::AuthState>(param_1));
+                static_cast<Interface::AuthState>(payload.state));
             break;
         }
-        default: {
+        case EventType::AuthPrompt: {
+            emit m_renderer->interface->authPrompt(decodeText(message));
+            break;
+        }
+        case EventType::ThemeReload: {
+            reload_component(m_renderer);
             break;
         }
+        default: {
+            warn_log(FILENAME, format_log("Rejected unexpected event type ",
+                                          static_cast<uint16_t>(event_type))
+                                   .c_str());
+            return -1;
+        }
     }
 
     debug_log(FILENAME,
-              format_log("Event Type: ", static_cast<uint64_t>(event_type),
-                         "; Param 1: ", param_1, "; Param 2: ", param_2)
+              format_log("Event Type: ", static_cast<uint16_t>(event_type),
+                         "; Length: ", message.header.length)
                   .c_str());
     return 0;
 }
 
 void EventHandler::handleReceivedEvent() {
-    Event ev = {};
-    int result = readEvent(m_renderer->appState->rendererReadFd, &ev);
+    EventMessage message;
+    int result = readEvent(m_renderer->output->rendererReadFd, &message);
 
     if (result == 0) {
-        processEvent(ev.event_type, ev.param_1, ev.param_2);
+        processEvent(message);
     }
 }
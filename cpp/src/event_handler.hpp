@@ -7,28 +7,51 @@
 #define EVENT_HANDLER_HPP
 
 #include "event.hpp"
+#include <QString>
+#include <map>
 #include <memory>
 
 struct QmlRenderer;
 class KeyboardHandler;
 class PointerHandler;
+class TouchHandler;
+class TextInputHandler;
 class KeyboardRepeatEngine;
 
+/// Input handlers for a single seat
+///
+/// Each seat has its own keymap, modifiers, key repeat, buttons and touch
+/// points, so input from one seat never affects another.
+struct SeatHandlers {
+    QString name;
+    KeyboardRepeatEngine *keyboardRepeatEngine;
+    KeyboardHandler *keyboardHandler;
+    PointerHandler *pointerHandler;
+    TouchHandler *touchHandler;
+
+    SeatHandlers(QmlRenderer *renderer, uint32_t seat);
+    ~SeatHandlers();
+};
+
 class EventHandler {
 private:
     QmlRenderer *m_renderer;
-    KeyboardHandler *m_keyboardHandler;
-    PointerHandler *m_pointerHandler;
-    KeyboardRepeatEngine *m_keyboardRepeatEngine;
+    std::map<uint32_t, std::unique_ptr<SeatHandlers>> m_seats;
+    std::unique_ptr<TextInputHandler> m_textInputHandler;
+    SeatHandlers *m_activeSeat = nullptr;
+
+    SeatHandlers *seatHandlers(uint32_t seat);
+    void setActiveSeat(SeatHandlers *handlers);
+    void publishKeyboardState(SeatHandlers *handlers);
 
 public:
-    explicit EventHandler(QmlRenderer *renderer,
-                          KeyboardRepeatEngine *keyboardRepeatEngine);
+    explicit EventHandler(QmlRenderer *renderer);
     ~EventHandler();
 
-    int processEvent(EventType event_type, EventParam param_1,
-                     EventParam param_2);
+    int processEvent(const EventMessage &message);
     void handleReceivedEvent();
+
+    void cycleKeyboardLayout();
 };
 
 #endif
@@ -0,0 +1,340 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+        image_provider.cpp:
+                Serves theme assets, the user's avatar, screenshots, the
+                wallpaper and generated images to themes.
+*/
+
+#include "image_provider.hpp"
+#include "logging.hpp"
+#include "render.hpp"
+#include "sandbox.hpp"
+
+#include <QColor>
+#include <QDir>
+#include <QFileInfo>
+#include <QFont>
+#include <QImageReader>
+#include <QMutexLocker>
+#include <QPainter>
+#include <QRegularExpression>
+#include <QStringList>
+#include <QUrl>
+#include <climits>
+
+static const char *FILENAME = "tlockr_qt/image_provider.cpp";
+
+/// Name the provider is registered with, as in `image://tlockr/`
+static const char *PROVIDER_NAME = "tlockr";
+
+/// Maximum size of the decoded images kept in the cache, in KiB
+static const int CACHE_SIZE_KIB = 64 * 1024;
+
+/// Size of generated avatars, if the theme does not request one
+static const int DEFAULT_AVATAR_SIZE = 256;
+
+/// Returns the size to scale an image of `size` to, for `requestedSize`
+///
+/// As with `sourceSize` in QML, the image is scaled to fit within the
+/// requested size, keeping its aspect ratio. A dimension of 0 is not
+/// constrained.
+static QSize targetSize(const QSize &size, const QSize &requestedSize) {
+    int width = requestedSize.width();
+    int height = requestedSize.height();
+    if (size.isEmpty() || (width <= 0 && height <= 0)) {
+        return size;
+    }
+
+    return size.scaled(width > 0 ? width : INT_MAX,
+                       height > 0 ? height : INT_MAX, Qt::KeepAspectRatio);
+}
+
+/// Returns the size to generate an image at, for `requestedSize`
+///
+/// A missing dimension is taken from the other, so generated images are
+/// square unless the theme asks otherwise.
+static QSize generatedSize(const QSize &requestedSize, int fallback) {
+    int width = requestedSize.width();
+    int height = requestedSize.height();
+    if (width <= 0 && height <= 0) {
+        return QSize(fallback, fallback);
+    }
+
+    return QSize(width > 0 ? width : height, height > 0 ? height : width);
+}
+
+/// Returns the key an image is cached with
+static QString cacheKey(const QString &id, const QSize &requestedSize) {
+    return QStringLiteral("%1@%2x%3")
+        .arg(id)
+        .arg(requestedSize.width())
+        .arg(requestedSize.height());
+}
+
+const Screenshot *findScreenshot(const ApplicationState *appState,
+                                 const QString &name) {
+    const Background &background = appState->background;
+    for (size_t i = 0; i < background.count; i++) {
+        const Screenshot &screenshot = background.screenshots[i];
+        if (QString::fromUtf8(screenshot.name) == name) {
+            return &screenshot;
+        }
+    }
+
+    return nullptr;
+}
+
+ImageProvider::ImageProvider(const ApplicationState *appState,
+                             const OutputState *output)
+    : QQuickImageProvider(QQuickImageProvider::Image), m_appState(appState),
+      m_output(output), m_roots(sandboxRoots(output->sandbox)),
+      m_cache(CACHE_SIZE_KIB) {}
+
+/// Read the image file at `path`, decoding it at the size it is shown at
+///
+/// If the theme does not request a size, images larger than the output are
+/// decoded at the output's size, so large wallpapers do not use more memory
+/// than needed.
+QImage ImageProvider::readFile(const QString &path,
+                               const QSize &requestedSize) const {
+    QImageReader reader(path);
+    reader.setAutoTransform(true);
+
+    QSize size = reader.size();
+    QSize target = targetSize(size, requestedSize);
+    if (requestedSize.width() <= 0 && requestedSize.height() <= 0) {
+        QSize output(m_output->width, m_output->height);
+        if (!output.isEmpty() && !size.isEmpty() &&
+            (size.width() > output.width() ||
+             size.height() > output.height())) {
+            target = size.scaled(output, Qt::KeepAspectRatio);
+        }
+    }
+
+    if (target.isValid() && target != size) {
+        reader.setScaledSize(target);
+    }
+
+    QImage image = reader.read();
+    if (image.isNull()) {
+        warn_log(FILENAME,
+                 format_log("Failed to load image '",
+                            path.toUtf8().constData(), "': ",
+                            reader.errorString().toUtf8().constData())
+                     .c_str());
+    }
+
+    return image;
+}
+
+/// Returns the file `path` inside the theme directory, or a null image
+///
+/// If the theme does not have the file, the directories of the themes it
+/// extends are searched in order. Paths leaving a theme directory are
+/// refused, even if the theme is allowed to load other files.
+QImage ImageProvider::themeAsset(const QString &path,
+                                 const QSize &requestedSize) const {
+    if (m_roots.isEmpty()) {
+        warn_log(FILENAME, "Theme assets are unavailable without a theme");
+        return QImage();
+    }
+
+    QString relative = QUrl::fromPercentEncoding(path.toUtf8());
+    QStringList files;
+    for (const QString &root : m_roots) {
+        QString file = canonicalPath(QDir(root).filePath(relative));
+        if (QDir::isAbsolutePath(relative) || !isInside(file, root)) {
+            warn_log(FILENAME,
+                     format_log("Blocked theme asset '",
+                                relative.toUtf8().constData(),
+                                "' outside the theme directory")
+                         .c_str());
+            return QImage();
+        }
+
+        files.append(file);
+    }
+
+    for (const QString &file : files) {
+        if (QFileInfo::exists(file)) {
+            return readFile(file, requestedSize);
+        }
+    }
+
+    // Report the missing file from the most derived theme
+    return readFile(files.first(), requestedSize);
+}
+
+/// Returns the user's avatar, or a null image if there is none
+QImage ImageProvider::avatar(const QSize &requestedSize) const {
+    const char *path = m_appState->session.avatarPath;
+    if (!path) {
+        warn_log(FILENAME, "No avatar found");
+        return QImage();
+    }
+
+    return readFile(QString::fromUtf8(path), requestedSize);
+}
+
+/// Returns an avatar showing the user's initials, for users without one
+///
+/// The background colour is chosen from the username, so it stays the same
+/// between locks.
+QImage ImageProvider::initials(const QSize &requestedSize) const {
+    const SessionInfo &session = m_appState->session;
+    QString userName = QString::fromUtf8(session.userName);
+    QString name =
+        session.realName ? QString::fromUtf8(session.realName) : userName;
+
+    QStringList words = name.split(' ', Qt::SkipEmptyParts);
+    QString text;
+    if (!words.isEmpty()) {
+        text += words.first().front();
+        if (words.size() > 1) {
+            text += words.last().front();
+        }
+    }
+
+    QSize size = generatedSize(requestedSize, DEFAULT_AVATAR_SIZE);
+    QImage image(size, QImage::Format_ARGB32_Premultiplied);
+    int hue = static_cast<int>(qHash(userName) % 360);
+    image.fill(QColor::fromHsl(hue, 96, 96));
+
+    QPainter painter(&image);
+    painter.setRenderHint(QPainter::TextAntialiasing);
+    QFont font = painter.font();
+    font.setPixelSize(qMax(1, qMin(size.width(), size.height()) * 2 / 5));
+    painter.setFont(font);
+    painter.setPen(Qt::white);
+    painter.drawText(image.rect(), Qt::AlignCenter, text.toUpper());
+
+    return image;
+}
+
+/// Returns an image of a single colour, or a null image if `name` is not a
+/// colour
+///
+/// Hex colours are written without `#`, which would start the URL's
+/// fragment.
+QImage ImageProvider::colour(const QString &name,
+                             const QSize &requestedSize) const {
+    static const QRegularExpression hex(
+        QStringLiteral("^([0-9a-fA-F]{3}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})$"));
+
+    QColor colour(hex.match(name).hasMatch() ? QStringLiteral("#") + name
+                                             : name);
+    if (!colour.isValid()) {
+        warn_log(FILENAME, format_log("Invalid colour '",
+                                      name.toUtf8().constData(), "'")
+                               .c_str());
+        return QImage();
+    }
+
+    QImage image(generatedSize(requestedSize, 1),
+                 QImage::Format_ARGB32_Premultiplied);
+    image.fill(colour);
+    return image;
+}
+
+/// Returns the screenshot of the output `name`, or a null image
+///
+/// The image refers to the pixels held by the application state, which
+/// outlives the renderer, so it is not copied.
+QImage ImageProvider::screenshot(const QString &name) const {
+    const Screenshot *screenshot = findScreenshot(m_appState, name);
+    if (!screenshot) {
+        warn_log(FILENAME,
+                 format_log("No screenshot of output '",
+                            name.toUtf8().constData(), "'")
+                     .c_str());
+        return QImage();
+    }
+
+    PixelFormat format = static_cast<PixelFormat>(screenshot->format);
+    return QImage(screenshot->data, screenshot->width, screenshot->height,
+                  screenshot->stride,
+                  hasAlphaChannel(format) ? QImage::Format_ARGB32_Premultiplied
+                                          : QImage::Format_RGB32);
+}
+
+/// Returns the configured wallpaper, or a null image if there is none
+QImage ImageProvider::wallpaper(const QSize &requestedSize) const {
+    const char *path = m_appState->background.wallpaper;
+    if (!path) {
+        warn_log(FILENAME, "No wallpaper configured");
+        return QImage();
+    }
+
+    return readFile(QString::fromUtf8(path), requestedSize);
+}
+
+/// Returns the image `id`, at the size requested by the theme
+QImage ImageProvider::loadImage(const QString &id,
+                                const QSize &requestedSize) const {
+    QImage image;
+
+    if (id.startsWith(QStringLiteral("theme/"))) {
+        image = themeAsset(id.mid(QStringLiteral("theme/").size()),
+                           requestedSize);
+    } else if (id == QStringLiteral("avatar")) {
+        image = avatar(requestedSize);
+    } else if (id == QStringLiteral("initials")) {
+        image = initials(requestedSize);
+    } else if (id.startsWith(QStringLiteral("color/"))) {
+        image = colour(id.mid(QStringLiteral("color/").size()), requestedSize);
+    } else if (id.startsWith(QStringLiteral("screenshot/"))) {
+        image = screenshot(id.mid(QStringLiteral("screenshot/").size()));
+    } else if (id == QStringLiteral("wallpaper")) {
+        image = wallpaper(requestedSize);
+    } else {
+        warn_log(FILENAME,
+                 format_log("Unknown image '", id.toUtf8().constData(), "'")
+                     .c_str());
+    }
+
+    QSize target = targetSize(image.size(), requestedSize);
+    if (image.size() != target) {
+        image = image.scaled(target, Qt::IgnoreAspectRatio,
+                             Qt::SmoothTransformation);
+    }
+
+    return image;
+}
+
+QImage ImageProvider::requestImage(const QString &id, QSize *size,
+                                   const QSize &requestedSize) {
+    QString key = cacheKey(id, requestedSize);
+
+    QImage image;
+    {
+        QMutexLocker lock(&m_cacheMutex);
+        if (QImage *cached = m_cache.object(key)) {
+            image = *cached;
+        }
+    }
+
+    if (image.isNull()) {
+        image = loadImage(id, requestedSize);
+
+        // Failed images are not cached, so they are retried
+        if (!image.isNull()) {
+            QMutexLocker lock(&m_cacheMutex);
+            m_cache.insert(key, new QImage(image),
+                           qMax<qsizetype>(1, image.sizeInBytes() / 1024));
+        }
+    }
+
+    if (size) {
+        *size = image.size();
+    }
+
+    return image;
+}
+
+/// Register the image provider with the QML engine, which takes ownership
+void setup_image_provider(QmlRenderer *renderer) {
+    renderer->engine->addImageProvider(
+        PROVIDER_NAME, new ImageProvider(renderer->appState, renderer->output));
+}
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#pragma once
+
+#ifndef IMAGE_PROVIDER_HPP
+#define IMAGE_PROVIDER_HPP
+
+#include <QCache>
+#include <QImage>
+#include <QMutex>
+#include <QQuickImageProvider>
+#include <QSize>
+#include <QString>
+#include <QStringList>
+
+struct ApplicationState;
+struct OutputState;
+struct QmlRenderer;
+struct Screenshot;
+
+/// Returns the screenshot of the output `name`, or null if there is none
+const Screenshot *findScreenshot(const ApplicationState *appState,
+                                 const QString &name);
+
+/// Serves images from tlockr to themes, under `image://tlockr/`
+///
+/// - `theme/<path>`: a file inside the theme directory, or the directory of a
+///   theme it extends.
+/// - `avatar`: the user's avatar.
+/// - `initials`: an avatar generated from the user's initials.
+/// - `color/<colour>`: a single colour, e.g. `color/336699` or `color/red`.
+/// - `screenshot/<output>`: screenshot of an output, taken before locking.
+/// - `wallpaper`: the configured wallpaper.
+///
+/// Files are decoded at the size requested by the theme, or at most the
+/// size of the output, and decoded images are cached.
+class ImageProvider : public QQuickImageProvider {
+public:
+    ImageProvider(const ApplicationState *appState, const OutputState *output);
+
+    QImage requestImage(const QString &id, QSize *size,
+                        const QSize &requestedSize) override;
+
+private:
+    QImage loadImage(const QString &id, const QSize &requestedSize) const;
+    QImage readFile(const QString &path, const QSize &requestedSize) const;
+
+    QImage themeAsset(const QString &path, const QSize &requestedSize) const;
+    QImage avatar(const QSize &requestedSize) const;
+    QImage initials(const QSize &requestedSize) const;
+    QImage colour(const QString &name, const QSize &requestedSize) const;
+    QImage screenshot(const QString &name) const;
+    QImage wallpaper(const QSize &requestedSize) const;
+
+    const ApplicationState *m_appState;
+    const OutputState *m_output;
+    QStringList m_roots;
+
+    // Images can be requested from QML's loader threads
+    QMutex m_cacheMutex;
+    QCache<QString, QImage> m_cache;
+};
+
+void setup_image_provider(QmlRenderer *renderer);
+
+#endif
@@ -3,44 +3,285 @@
 
 #include "interface.hpp"
 #include "event.hpp"
-#include "ffi.hpp"
+#include "event_handler.hpp"
+#include "image_provider.hpp"
 #include "logging.hpp"
 #include "render.hpp"
+#include <QColor>
+#include <QJSValue>
+#include <QStringList>
+#include <QUrl>
 
 static const char *FILENAME = "tlockr_qt/interface.cpp";
 
 Interface::Interface(QmlRenderer *renderer, QObject *parent)
-    : m_renderer(renderer), QObject(parent) {}
+    : m_renderer(renderer), QObject(parent),
+      m_battery(new BatteryStatus(this)), m_network(new NetworkStatus(this)),
+      m_media(new MediaPlayer(renderer, this)),
+      m_notifications(new NotificationModel(this)) {}
 
 Interface::~Interface() = default;
 
 Q_INVOKABLE void Interface::sendAuthSubmit(const QString &msg) {
     QByteArray bm = msg.toUtf8();
-    ForeignBuffer *fbu =
-        build_ffi_buffer(static_cast<void *>(bm.data()), bm.length());
-    writeEvent(m_renderer->appState->authWriteFd, EventType::AuthSubmit,
-               reinterpret_cast<EventParam>(fbu), 0);
+    int res = writeEvent(m_renderer->appState->authWriteFd,
+                         EventType::AuthSubmit, bm.constData(), bm.size());
+    bm.fill('\0');
+
+    if (res != 0) {
+        error_log(FILENAME, "Failed to send AuthSubmit event to authenticator");
+        return;
+    }
+
     debug_log(FILENAME, "Sent AuthSubmit event to authenticator");
 }
 
+Q_INVOKABLE void Interface::cycleKeyboardLayout() {
+    if (m_renderer->eventHandler) {
+        m_renderer->eventHandler->cycleKeyboardLayout();
+    }
+}
+
+/// Find the QML file and line that called into the interface
+///
+/// The JavaScript engine records the call stack when an `Error` is
+/// constructed, as lines of `function@url:line`. The first frame with a URL
+/// is the QML caller, since the evaluated expression itself has none.
+///
+/// If no caller can be found, the main QML file is used, with line 0.
+void Interface::callerLocation(QByteArray &file, int &line) const {
+    file = QByteArray(m_renderer->output->qmlPath);
+    line = 0;
+
+    if (!m_renderer->engine) {
+        return;
+    }
+
+    QJSValue stack = m_renderer->engine->evaluate("new Error().stack");
+    const QStringList frames = stack.toString().split('\n');
+
+    for (const QString &frame : frames) {
+        qsizetype at = frame.indexOf('@');
+        qsizetype colon = frame.lastIndexOf(':');
+        if (at < 0 || colon <= at + 1) {
+            continue;
+        }
+
+        QUrl url(frame.mid(at + 1, colon - at - 1));
+        if (url.isEmpty()) {
+            continue;
+        }
+
+        file = url.isLocalFile() ? url.toLocalFile().toUtf8()
+                                 : url.toString().toUtf8();
+        line = frame.mid(colon + 1).toInt();
+        return;
+    }
+}
+
+void Interface::logMessage(LogLevel level, const QString &msg) {
+    QByteArray file;
+    int line;
+    callerLocation(file, line);
+
+    qml_log(level, file.constData(), line, msg.toUtf8().constData());
+}
+
 Q_INVOKABLE void Interface::debug(const QString &msg) {
-    debug_log(m_renderer->appState->qmlPath, msg.toStdString().c_str());
+    logMessage(LogLevel::Debug, msg);
 }
 
 Q_INVOKABLE void Interface::info(const QString &msg) {
-    info_log(m_renderer->appState->qmlPath, msg.toStdString().c_str());
+    logMessage(LogLevel::Info, msg);
 }
 
 Q_INVOKABLE void Interface::warn(const QString &msg) {
-    warn_log(m_renderer->appState->qmlPath, msg.toStdString().c_str());
+    logMessage(LogLevel::Warn, msg);
 }
 
 Q_INVOKABLE void Interface::error(const QString &msg) {
-    error_log(m_renderer->appState->qmlPath, msg.toStdString().c_str());
+    logMessage(LogLevel::Error, msg);
+}
+
+int Interface::outputWidth() const { return m_renderer->output->width; }
+
+int Interface::outputHeight() const { return m_renderer->output->height; }
+
+QString Interface::outputName() const {
+    return QString::fromUtf8(m_renderer->output->details.name);
+}
+
+QString Interface::outputDescription() const {
+    return QString::fromUtf8(m_renderer->output->details.description);
+}
+
+/// Returns the role of the output from the user's configuration, or an empty
+/// string if it has none
+QString Interface::outputRole() const {
+    return QString::fromUtf8(m_renderer->output->details.role);
+}
+
+bool Interface::primary() const {
+    return m_renderer->output->details.primary;
+}
+
+QString Interface::userName() const {
+    return QString::fromUtf8(m_renderer->appState->session.userName);
+}
+
+/// Returns the user's full name, or their username if it is not set
+QString Interface::realName() const {
+    const SessionInfo &session = m_renderer->appState->session;
+    return QString::fromUtf8(session.realName ? session.realName
+                                              : session.userName);
+}
+
+/// Returns the user's avatar image, or an empty URL if there is none
+QUrl Interface::avatar() const {
+    return m_renderer->appState->session.avatarPath
+               ? QUrl(QStringLiteral("image://tlockr/avatar"))
+               : QUrl();
+}
+
+QString Interface::hostName() const {
+    return QString::fromUtf8(m_renderer->appState->session.hostName);
 }
 
-int Interface::outputWidth() const { return m_renderer->appState->outputWidth; }
+QDateTime Interface::lockTime() const {
+    return QDateTime::fromSecsSinceEpoch(
+        m_renderer->appState->session.lockTime);
+}
+
+/// Returns the resolved theme settings, by name
+QVariantMap Interface::settings() const {
+    const ThemeSettings &settings = m_renderer->output->settings;
+    QVariantMap map;
+
+    for (size_t i = 0; i < settings.count; i++) {
+        const ThemeSettingEntry &entry = settings.entries[i];
+        QString text = QString::fromUtf8(entry.text);
+        QVariant value;
+
+        switch (entry.kind) {
+            case ThemeSettingKind::String:
+                value = text;
+                break;
+            case ThemeSettingKind::Bool:
+                value = entry.boolean;
+                break;
+            case ThemeSettingKind::Int:
+                value = static_cast<qint64>(entry.integer);
+                break;
+            case ThemeSettingKind::Float:
+                value = entry.number;
+                break;
+            case ThemeSettingKind::Color:
+                value = QColor(text);
+                break;
+            case ThemeSettingKind::Path:
+                value = QUrl::fromLocalFile(text);
+                break;
+            default:
+                warn_log(FILENAME,
+                         format_log("Ignoring setting of unknown type ",
+                                    entry.name)
+                             .c_str());
+                continue;
+        }
+
+        map.insert(QString::fromUtf8(entry.name), value);
+    }
+
+    return map;
+}
+
+/// Returns the preferred colour scheme, `dark`, `light` or `default` if there
+/// is no preference
+QString Interface::colorScheme() const {
+    switch (m_renderer->appState->colorScheme) {
+        case ColorScheme::Dark:
+            return QStringLiteral("dark");
+        case ColorScheme::Light:
+            return QStringLiteral("light");
+        default:
+            return QStringLiteral("default");
+    }
+}
+
+/// Returns the screenshot of the output the theme is shown on, or an empty
+/// URL if there is none
+QUrl Interface::screenshot() const {
+    const char *name = m_renderer->output->details.name;
+    if (!name) {
+        return QUrl();
+    }
+
+    QString output = QString::fromUtf8(name);
+    if (!findScreenshot(m_renderer->appState, output)) {
+        return QUrl();
+    }
+
+    return QUrl(QStringLiteral("image://tlockr/screenshot/") + output);
+}
+
+/// Returns the configured wallpaper, or an empty URL if there is none
+QUrl Interface::wallpaper() const {
+    return m_renderer->appState->background.wallpaper
+               ? QUrl(QStringLiteral("image://tlockr/wallpaper"))
+               : QUrl();
+}
+
+QString Interface::activeSeat() const { return m_activeSeat; }
+
+/// Set the name of the seat the user last interacted with
+void Interface::setActiveSeat(const QString &seat) {
+    if (m_activeSeat == seat) {
+        return;
+    }
+
+    m_activeSeat = seat;
+    emit activeSeatChanged();
+}
+
+QString Interface::keyboardLayout() const { return m_keyboardLayout; }
+
+QStringList Interface::keyboardLayouts() const { return m_keyboardLayouts; }
+
+bool Interface::capsLock() const { return m_capsLock; }
+
+bool Interface::numLock() const { return m_numLock; }
+
+/// Set the state of the active keyboard, emitting signals for any changes
+void Interface::setKeyboardState(const QString &layout,
+                                 const QStringList &layouts, bool capsLock,
+                                 bool numLock) {
+    if (m_keyboardLayouts != layouts) {
+        m_keyboardLayouts = layouts;
+        emit keyboardLayoutsChanged();
+    }
+
+    if (m_keyboardLayout != layout) {
+        m_keyboardLayout = layout;
+        emit keyboardLayoutChanged();
+    }
+
+    if (m_capsLock != capsLock) {
+        m_capsLock = capsLock;
+        emit capsLockChanged();
+    }
+
+    if (m_numLock != numLock) {
+        m_numLock = numLock;
+        emit numLockChanged();
+    }
+}
+
+BatteryStatus *Interface::battery() const { return m_battery; }
+
+NetworkStatus *Interface::network() const { return m_network; }
+
+MediaPlayer *Interface::media() const { return m_media; }
 
-int Interface::outputHeight() const {
-    return m_renderer->appState->outputHeight;
+NotificationModel *Interface::notifications() const {
+    return m_notifications;
 }
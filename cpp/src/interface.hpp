@@ -6,24 +6,70 @@
 #ifndef INTERFACE_HPP
 #define INTERFACE_HPP
 
+#include "media.hpp"
+#include "notifications.hpp"
+#include "status.hpp"
+#include <QByteArray>
+#include <QDateTime>
 #include <QObject>
 #include <QString>
+#include <QStringList>
+#include <QUrl>
+#include <QVariantMap>
 
 struct QmlRenderer;
+enum class LogLevel : int;
 
 class Interface : public QObject {
     Q_OBJECT
     Q_PROPERTY(int Width READ outputWidth CONSTANT)
     Q_PROPERTY(int Height READ outputHeight CONSTANT)
+    Q_PROPERTY(QString OutputName READ outputName CONSTANT)
+    Q_PROPERTY(QString OutputDescription READ outputDescription CONSTANT)
+    Q_PROPERTY(QString OutputRole READ outputRole CONSTANT)
+    Q_PROPERTY(bool isPrimary READ primary CONSTANT)
+    Q_PROPERTY(QString UserName READ userName CONSTANT)
+    Q_PROPERTY(QString RealName READ realName CONSTANT)
+    Q_PROPERTY(QUrl Avatar READ avatar CONSTANT)
+    Q_PROPERTY(QString HostName READ hostName CONSTANT)
+    Q_PROPERTY(QDateTime LockTime READ lockTime CONSTANT)
+    Q_PROPERTY(QVariantMap settings READ settings CONSTANT)
+    Q_PROPERTY(QString colorScheme READ colorScheme CONSTANT)
+    Q_PROPERTY(QUrl Screenshot READ screenshot CONSTANT)
+    Q_PROPERTY(QUrl Wallpaper READ wallpaper CONSTANT)
+    Q_PROPERTY(QString ActiveSeat READ activeSeat NOTIFY activeSeatChanged)
+    Q_PROPERTY(QString KeyboardLayout READ keyboardLayout NOTIFY
+                   keyboardLayoutChanged)
+    Q_PROPERTY(QStringList KeyboardLayouts READ keyboardLayouts NOTIFY
+                   keyboardLayoutsChanged)
+    Q_PROPERTY(bool CapsLock READ capsLock NOTIFY capsLockChanged)
+    Q_PROPERTY(bool NumLock READ numLock NOTIFY numLockChanged)
+    Q_PROPERTY(BatteryStatus *battery READ battery CONSTANT)
+    Q_PROPERTY(NetworkStatus *network READ network CONSTANT)
+    Q_PROPERTY(MediaPlayer *media READ media CONSTANT)
+    Q_PROPERTY(NotificationModel *notifications READ notifications CONSTANT)
 
 private:
     QmlRenderer *m_renderer;
+    QString m_activeSeat;
+    QString m_keyboardLayout;
+    QStringList m_keyboardLayouts;
+    bool m_capsLock = false;
+    bool m_numLock = false;
+    BatteryStatus *m_battery;
+    NetworkStatus *m_network;
+    MediaPlayer *m_media;
+    NotificationModel *m_notifications;
+
+    void callerLocation(QByteArray &file, int &line) const;
+    void logMessage(LogLevel level, const QString &msg);
 
 public:
     explicit Interface(QmlRenderer *renderer, QObject *parent = nullptr);
     ~Interface();
 
     Q_INVOKABLE void sendAuthSubmit(const QString &msg);
+    Q_INVOKABLE void cycleKeyboardLayout();
 
     Q_INVOKABLE void debug(const QString &msg);
     Q_INVOKABLE void info(const QString &msg);
@@ -32,6 +78,35 @@ public:
 
     int outputWidth() const;
     int outputHeight() const;
+    QString outputName() const;
+    QString outputDescription() const;
+    QString outputRole() const;
+    bool primary() const;
+
+    QString userName() const;
+    QString realName() const;
+    QUrl avatar() const;
+    QString hostName() const;
+    QDateTime lockTime() const;
+    QVariantMap settings() const;
+    QString colorScheme() const;
+    QUrl screenshot() const;
+    QUrl wallpaper() const;
+
+    QString activeSeat() const;
+    void setActiveSeat(const QString &seat);
+
+    QString keyboardLayout() const;
+    QStringList keyboardLayouts() const;
+    bool capsLock() const;
+    bool numLock() const;
+    void setKeyboardState(const QString &layout, const QStringList &layouts,
+                          bool capsLock, bool numLock);
+
+    BatteryStatus *battery() const;
+    NetworkStatus *network() const;
+    MediaPlayer *media() const;
+    NotificationModel *notifications() const;
 
     enum AuthState {
         Pending = 0,
@@ -42,6 +117,12 @@ public:
 
 signals:
     void authStateChange(AuthState state);
+    void authPrompt(const QString &message);
+    void activeSeatChanged();
+    void keyboardLayoutChanged();
+    void keyboardLayoutsChanged();
+    void capsLockChanged();
+    void numLockChanged();
 };
 
 #endif
@@ -13,10 +13,11 @@
 
 static const char *FILENAME = "tlockr_qt/keyboard.cpp";
 
-KeyboardHandler::KeyboardHandler(QmlRenderer *renderer)
-    : m_renderer(renderer), m_xkbContext(nullptr), m_xkbKeymap(nullptr),
-      m_xkbState(nullptr) {
-    renderer->keyboardRepeatEngine->setCallback(
+KeyboardHandler::KeyboardHandler(QmlRenderer *renderer,
+                                 KeyboardRepeatEngine *keyboardRepeatEngine)
+    : m_renderer(renderer), m_keyboardRepeatEngine(keyboardRepeatEngine),
+      m_xkbContext(nullptr), m_xkbKeymap(nullptr), m_xkbState(nullptr) {
+    m_keyboardRepeatEngine->setCallback(
         [this](KeyPressEvent *event) { sendLastPress(event); });
 }
 
@@ -69,12 +70,15 @@ void KeyboardHandler::handleKeymapEvent(int fd, uint32_t size) {
 
     m_xkbKeymap = keymap;
     m_xkbState = xkb_state_new(keymap);
+    m_groupOffset = 0;
 
     if (!m_xkbState) {
         error_log(FILENAME, "Failed to create XKB state");
         return;
     }
 
+    updateMask();
+
     info_log(FILENAME, "Loaded new XKB keymap");
 }
 
@@ -82,13 +86,91 @@ void KeyboardHandler::handleModifiersEvent(uint32_t mods_depressed,
                                            uint32_t mods_latched,
                                            uint32_t mods_locked,
                                            uint32_t group) {
+    m_modsDepressed = mods_depressed;
+    m_modsLatched = mods_latched;
+    m_modsLocked = mods_locked;
+    m_group = group;
+
     if (m_xkbState) {
-        xkb_state_update_mask(m_xkbState, mods_depressed, mods_latched,
-                              mods_locked, 0, 0, group);
+        updateMask();
         debug_log(FILENAME, "Updated keyboard modifiers");
     }
 }
 
+/// Apply the last modifiers from the compositor, with the local layout
+void KeyboardHandler::updateMask() {
+    const xkb_layout_index_t layouts = xkb_keymap_num_layouts(m_xkbKeymap);
+    const uint32_t group =
+        layouts > 0 ? (m_group + m_groupOffset) % layouts : m_group;
+
+    xkb_state_update_mask(m_xkbState, m_modsDepressed, m_modsLatched,
+                          m_modsLocked, 0, 0, group);
+}
+
+/// Returns the name of the active layout, or an empty string without a
+/// keymap
+QString KeyboardHandler::layoutName() const {
+    if (!m_xkbState) {
+        return QString();
+    }
+
+    const xkb_layout_index_t layout =
+        xkb_state_serialize_layout(m_xkbState, XKB_STATE_LAYOUT_EFFECTIVE);
+    const char *name = xkb_keymap_layout_get_name(m_xkbKeymap, layout);
+
+    return name ? QString::fromUtf8(name) : QString();
+}
+
+/// Returns the names of every layout in the keymap, in group order
+QStringList KeyboardHandler::layoutNames() const {
+    QStringList names;
+    if (!m_xkbKeymap) {
+        return names;
+    }
+
+    const xkb_layout_index_t layouts = xkb_keymap_num_layouts(m_xkbKeymap);
+    for (xkb_layout_index_t i = 0; i < layouts; i++) {
+        const char *name = xkb_keymap_layout_get_name(m_xkbKeymap, i);
+        names.append(name ? QString::fromUtf8(name) : QString());
+    }
+
+    return names;
+}
+
+bool KeyboardHandler::capsLock() const {
+    return m_xkbState &&
+           xkb_state_led_name_is_active(m_xkbState, XKB_LED_NAME_CAPS) > 0;
+}
+
+bool KeyboardHandler::numLock() const {
+    return m_xkbState &&
+           xkb_state_led_name_is_active(m_xkbState, XKB_LED_NAME_NUM) > 0;
+}
+
+/// Switch to the next layout in the keymap
+///
+/// The layout is only switched for the lock screen, the compositor is not
+/// told about it. The switch is kept when the compositor sends new
+/// modifiers, until the next keymap.
+void KeyboardHandler::cycleLayout() {
+    if (!m_xkbState) {
+        warn_log(FILENAME, "No XKB state available");
+        return;
+    }
+
+    const xkb_layout_index_t layouts = xkb_keymap_num_layouts(m_xkbKeymap);
+    if (layouts < 2) {
+        return;
+    }
+
+    m_groupOffset = (m_groupOffset + 1) % layouts;
+    updateMask();
+
+    info_log(FILENAME, format_log("Switched keyboard layout to ",
+                                  layoutName().toStdString())
+                           .c_str());
+}
+
 void KeyboardHandler::handleKeyEvent(uint32_t key_code, KeyState state) {
     if (!m_xkbState) {
         warn_log(FILENAME, "No XKB state available");
@@ -124,11 +206,11 @@ void KeyboardHandler::handleKeyEvent(uint32_t key_code, KeyState state) {
         m_lastEvent.modifiers = modifiers;
         m_lastEvent.text = text;
 
-        m_renderer->keyboardRepeatEngine->set(&m_lastEvent);
+        m_keyboardRepeatEngine->set(&m_lastEvent);
     } else if (state == KeyState::Released) {
         sendKeyEvent(QEvent::KeyRelease, key, modifiers, text);
 
-        m_renderer->keyboardRepeatEngine->reset();
+        m_keyboardRepeatEngine->reset();
     }
 }
 
@@ -147,8 +229,10 @@ void KeyboardHandler::sendKeyEvent(QEvent::Type eventType, Qt::Key key,
     QKeyEvent *event = new QKeyEvent(eventType, key, modifiers, text);
     QObject *target = nullptr;
 
-    if (QGuiApplication::focusObject()) {
-        target = QGuiApplication::focusObject();
+    // Every output has its own window, so the focus of this renderer's
+    // window is used rather than the application's
+    if (m_renderer->window && m_renderer->window->focusObject()) {
+        target = m_renderer->window->focusObject();
     } else if (m_renderer->window && m_renderer->window->activeFocusItem()) {
         target = m_renderer->window->activeFocusItem();
     } else if (m_renderer->rootItem) {
@@ -8,6 +8,7 @@
 
 #include <QKeyEvent>
 #include <QString>
+#include <QStringList>
 #include <cstdint>
 #include <xkbcommon/xkbcommon.h>
 
@@ -18,6 +19,7 @@ enum class KeyState : uint32_t {
 };
 
 struct QmlRenderer;
+class KeyboardRepeatEngine;
 
 struct KeyPressEvent {
     Qt::Key key;
@@ -28,14 +30,26 @@ struct KeyPressEvent {
 class KeyboardHandler {
 private:
     QmlRenderer *m_renderer;
+    KeyboardRepeatEngine *m_keyboardRepeatEngine;
     struct xkb_context *m_xkbContext;
     struct xkb_keymap *m_xkbKeymap;
     struct xkb_state *m_xkbState;
 
     KeyPressEvent m_lastEvent;
 
+    // Modifiers last sent by the compositor, and the number of layouts the
+    // layout has been cycled by locally
+    uint32_t m_modsDepressed = 0;
+    uint32_t m_modsLatched = 0;
+    uint32_t m_modsLocked = 0;
+    uint32_t m_group = 0;
+    uint32_t m_groupOffset = 0;
+
+    void updateMask();
+
 public:
-    explicit KeyboardHandler(QmlRenderer *renderer);
+    explicit KeyboardHandler(QmlRenderer *renderer,
+                             KeyboardRepeatEngine *keyboardRepeatEngine);
     ~KeyboardHandler();
 
     void setupXkbContext();
@@ -44,6 +58,12 @@ public:
                               uint32_t mods_locked, uint32_t group);
     void handleKeyEvent(uint32_t key_code, KeyState state);
 
+    QString layoutName() const;
+    QStringList layoutNames() const;
+    bool capsLock() const;
+    bool numLock() const;
+    void cycleLayout();
+
     Qt::Key xkbKeysymToQtKey(xkb_keysym_t keysym);
     Qt::KeyboardModifiers xkbStateToQtModifiers();
 
@@ -3,23 +3,49 @@
 
 #include "logging.hpp"
 
-void qtMessageHandler(QtMsgType type, const QMessageLogContext &,
+void trace_log(const char *component, const char *msg) {
+    renderer_log(LogLevel::Trace, component, msg);
+}
+
+void debug_log(const char *component, const char *msg) {
+    renderer_log(LogLevel::Debug, component, msg);
+}
+
+void info_log(const char *component, const char *msg) {
+    renderer_log(LogLevel::Info, component, msg);
+}
+
+void warn_log(const char *component, const char *msg) {
+    renderer_log(LogLevel::Warn, component, msg);
+}
+
+void error_log(const char *component, const char *msg) {
+    renderer_log(LogLevel::Error, component, msg);
+}
+
+void qtMessageHandler(QtMsgType type, const QMessageLogContext &context,
                       const QString &msg) {
-    QByteArray localMsg = msg.toLocal8Bit();
+    QByteArray localMsg = msg.toUtf8();
+    LogLevel level = LogLevel::Error;
 
     switch (type) {
         case QtDebugMsg:
-            debug_log("tlockr_qt", localMsg.constData());
+            level = LogLevel::Debug;
             break;
         case QtInfoMsg:
-            info_log("tlockr_qt", localMsg.constData());
+            level = LogLevel::Info;
             break;
         case QtWarningMsg:
-            warn_log("tlockr_qt", localMsg.constData());
+            level = LogLevel::Warn;
             break;
         case QtCriticalMsg:
         case QtFatalMsg:
-            error_log("tlockr_qt", localMsg.constData());
+            level = LogLevel::Error;
             break;
     }
-}
\ No newline at end of file
+
+    // The file, function, and category may be NULL, these are handled on the
+    // Rust side
+    qt_log(level, context.category, context.file, context.line,
+           context.function, localMsg.constData());
+}
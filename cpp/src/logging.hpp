@@ -18,18 +18,32 @@ template <typename... Args> std::string format_log(Args &&...args) {
     return ss.str();
 }
 
-void qtMessageHandler(QtMsgType type, const QMessageLogContext &,
+/// Log levels understood by the Rust logging functions
+enum class LogLevel : int {
+    Trace = 0,
+    Debug = 1,
+    Info = 2,
+    Warn = 3,
+    Error = 4,
+};
+
+void qtMessageHandler(QtMsgType type, const QMessageLogContext &context,
                       const QString &msg);
 
+void trace_log(const char *component, const char *msg);
+void debug_log(const char *component, const char *msg);
+void info_log(const char *component, const char *msg);
+void warn_log(const char *component, const char *msg);
+void error_log(const char *component, const char *msg);
+
 #ifdef __cplusplus
 extern "C" {
 #endif
 
-void trace_log(const char *file, const char *msg);
-void debug_log(const char *file, const char *msg);
-void info_log(const char *file, const char *msg);
-void warn_log(const char *file, const char *msg);
-void error_log(const char *file, const char *msg);
+void renderer_log(LogLevel level, const char *component, const char *msg);
+void qt_log(LogLevel level, const char *category, const char *file, int line,
+            const char *function, const char *msg);
+void qml_log(LogLevel level, const char *qml_file, int line, const char *msg);
 
 #ifdef __cplusplus
 }
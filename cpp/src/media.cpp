@@ -0,0 +1,117 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#include "media.hpp"
+#include "logging.hpp"
+#include "render.hpp"
+#include "sandbox.hpp"
+
+static const char *FILENAME = "tlockr_qt/media.cpp";
+
+MediaPlayer::MediaPlayer(QmlRenderer *renderer, QObject *parent)
+    : QObject(parent), m_renderer(renderer) {}
+
+/// Ask Rust to send `action` to the active player
+void MediaPlayer::sendAction(MediaAction action) {
+    if (!m_available) {
+        warn_log(FILENAME, "No media player to control");
+        return;
+    }
+
+    MediaActionPayload payload = {action};
+    if (writeEvent(m_renderer->appState->rendererWriteFd,
+                   EventType::MediaAction, &payload, sizeof(payload)) != 0) {
+        error_log(FILENAME, "Failed to send media action");
+        return;
+    }
+
+    debug_log(FILENAME, format_log("Sent media action ",
+                                   static_cast<uint32_t>(action))
+                            .c_str());
+}
+
+Q_INVOKABLE void MediaPlayer::playPause() {
+    sendAction(MediaAction::PlayPause);
+}
+
+Q_INVOKABLE void MediaPlayer::play() { sendAction(MediaAction::Play); }
+
+Q_INVOKABLE void MediaPlayer::pause() { sendAction(MediaAction::Pause); }
+
+Q_INVOKABLE void MediaPlayer::next() { sendAction(MediaAction::Next); }
+
+Q_INVOKABLE void MediaPlayer::previous() {
+    sendAction(MediaAction::Previous);
+}
+
+bool MediaPlayer::available() const { return m_available; }
+
+QString MediaPlayer::playerName() const { return m_playerName; }
+
+/// Returns the MPRIS name of the playback status, or an empty string if there
+/// is no player
+QString MediaPlayer::playbackStatus() const {
+    if (!m_available) {
+        return QString();
+    }
+
+    switch (m_status) {
+        case MediaPlaybackStatus::Playing:
+            return QStringLiteral("Playing");
+        case MediaPlaybackStatus::Paused:
+            return QStringLiteral("Paused");
+        default:
+            return QStringLiteral("Stopped");
+    }
+}
+
+bool MediaPlayer::playing() const {
+    return m_available && m_status == MediaPlaybackStatus::Playing;
+}
+
+QString MediaPlayer::title() const { return m_title; }
+
+QString MediaPlayer::artist() const { return m_artist; }
+
+QString MediaPlayer::album() const { return m_album; }
+
+QUrl MediaPlayer::artUrl() const { return m_artUrl; }
+
+bool MediaPlayer::canPlay() const { return m_capabilities & MEDIA_CAN_PLAY; }
+
+bool MediaPlayer::canPause() const {
+    return m_capabilities & MEDIA_CAN_PAUSE;
+}
+
+bool MediaPlayer::canGoNext() const {
+    return m_capabilities & MEDIA_CAN_GO_NEXT;
+}
+
+bool MediaPlayer::canGoPrevious() const {
+    return m_capabilities & MEDIA_CAN_GO_PREVIOUS;
+}
+
+/// Set the status of the active player
+///
+/// Rust only sends the status when it changes, so `changed` is always
+/// emitted.
+void MediaPlayer::update(bool available, MediaPlaybackStatus status,
+                         uint32_t capabilities, const QString &playerName,
+                         const QString &title, const QString &artist,
+                         const QString &album, const QString &artUrl) {
+    m_available = available;
+    m_status = status;
+    m_capabilities = available ? capabilities : 0;
+    m_playerName = playerName;
+    m_title = title;
+    m_artist = artist;
+    m_album = album;
+    m_artUrl = QUrl(artUrl);
+
+    // Album art is chosen by the player, so the theme can always load it
+    if (m_renderer->urlInterceptor) {
+        m_renderer->urlInterceptor->allowUrl(m_artUrl);
+    }
+
+    emit changed();
+}
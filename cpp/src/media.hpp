@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#pragma once
+
+#ifndef MEDIA_HPP
+#define MEDIA_HPP
+
+#include "event.hpp"
+#include <QObject>
+#include <QString>
+#include <QUrl>
+
+struct QmlRenderer;
+
+/// The active media player, exposed as `tlockr.media`
+///
+/// Every property changes together, so they share a single signal. Controls
+/// are sent to Rust, which forwards them to the player over D-Bus.
+class MediaPlayer : public QObject {
+    Q_OBJECT
+    Q_PROPERTY(bool available READ available NOTIFY changed)
+    Q_PROPERTY(QString playerName READ playerName NOTIFY changed)
+    Q_PROPERTY(QString playbackStatus READ playbackStatus NOTIFY changed)
+    Q_PROPERTY(bool playing READ playing NOTIFY changed)
+    Q_PROPERTY(QString title READ title NOTIFY changed)
+    Q_PROPERTY(QString artist READ artist NOTIFY changed)
+    Q_PROPERTY(QString album READ album NOTIFY changed)
+    Q_PROPERTY(QUrl artUrl READ artUrl NOTIFY changed)
+    Q_PROPERTY(bool canPlay READ canPlay NOTIFY changed)
+    Q_PROPERTY(bool canPause READ canPause NOTIFY changed)
+    Q_PROPERTY(bool canGoNext READ canGoNext NOTIFY changed)
+    Q_PROPERTY(bool canGoPrevious READ canGoPrevious NOTIFY changed)
+
+private:
+    QmlRenderer *m_renderer;
+    bool m_available = false;
+    MediaPlaybackStatus m_status = MediaPlaybackStatus::Stopped;
+    uint32_t m_capabilities = 0;
+    QString m_playerName;
+    QString m_title;
+    QString m_artist;
+    QString m_album;
+    QUrl m_artUrl;
+
+    void sendAction(MediaAction action);
+
+public:
+    explicit MediaPlayer(QmlRenderer *renderer, QObject *parent = nullptr);
+
+    Q_INVOKABLE void playPause();
+    Q_INVOKABLE void play();
+    Q_INVOKABLE void pause();
+    Q_INVOKABLE void next();
+    Q_INVOKABLE void previous();
+
+    bool available() const;
+    QString playerName() const;
+    QString playbackStatus() const;
+    bool playing() const;
+    QString title() const;
+    QString artist() const;
+    QString album() const;
+    QUrl artUrl() const;
+    bool canPlay() const;
+    bool canPause() const;
+    bool canGoNext() const;
+    bool canGoPrevious() const;
+
+    void update(bool available, MediaPlaybackStatus status,
+                uint32_t capabilities, const QString &playerName,
+                const QString &title, const QString &artist,
+                const QString &album, const QString &artUrl);
+
+signals:
+    void changed();
+};
+
+#endif
@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#include "notifications.hpp"
+#include "logging.hpp"
+
+static const char *FILENAME = "tlockr_qt/notifications.cpp";
+
+/// Maximum number of rows kept, older notifications are dropped
+static constexpr int MAX_NOTIFICATIONS = 100;
+
+NotificationModel::NotificationModel(QObject *parent)
+    : QAbstractListModel(parent) {}
+
+int NotificationModel::rowCount(const QModelIndex &parent) const {
+    return parent.isValid() ? 0 : m_notifications.size();
+}
+
+QVariant NotificationModel::data(const QModelIndex &index, int role) const {
+    if (!index.isValid() || index.row() >= m_notifications.size()) {
+        return QVariant();
+    }
+
+    const Notification &notification = m_notifications[index.row()];
+    switch (role) {
+        case AppNameRole:
+            return notification.appName;
+        case SummaryRole:
+            return notification.summary;
+        case BodyRole:
+            return notification.body;
+        case TimeRole:
+            return notification.time;
+        default:
+            return QVariant();
+    }
+}
+
+QHash<int, QByteArray> NotificationModel::roleNames() const {
+    return {
+        {AppNameRole, "appName"},
+        {SummaryRole, "summary"},
+        {BodyRole, "body"},
+        {TimeRole, "time"},
+    };
+}
+
+int NotificationModel::count() const { return m_keys.size(); }
+
+/// Add a notification, or update the row of the notification it replaces
+void NotificationModel::update(uint32_t key, const QString &appName,
+                               const QString &summary, const QString &body) {
+    Notification notification = {key, appName, summary, body,
+                                 QDateTime::currentDateTime()};
+
+    for (int row = 0; row < m_notifications.size(); row++) {
+        if (m_notifications[row].key == key) {
+            m_notifications[row] = notification;
+            emit dataChanged(index(row), index(row));
+            return;
+        }
+    }
+
+    if (m_notifications.size() >= MAX_NOTIFICATIONS) {
+        beginRemoveRows(QModelIndex(), 0, 0);
+        m_notifications.removeFirst();
+        endRemoveRows();
+    }
+
+    int row = m_notifications.size();
+    beginInsertRows(QModelIndex(), row, row);
+    m_notifications.append(notification);
+    endInsertRows();
+
+    debug_log(FILENAME, format_log("Added notification ", key).c_str());
+
+    if (!m_keys.contains(key)) {
+        m_keys.insert(key);
+        emit countChanged();
+    }
+}
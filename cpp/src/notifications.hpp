@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#pragma once
+
+#ifndef NOTIFICATIONS_HPP
+#define NOTIFICATIONS_HPP
+
+#include <QAbstractListModel>
+#include <QDateTime>
+#include <QHash>
+#include <QList>
+#include <QSet>
+#include <QString>
+
+/// Notifications received while locked, exposed as `tlockr.notifications`
+///
+/// Rows are in the order notifications were received, and a notification
+/// replacing an earlier one updates its row. Text hidden by the privacy level
+/// is empty. `count` includes notifications whose rows were dropped.
+class NotificationModel : public QAbstractListModel {
+    Q_OBJECT
+    Q_PROPERTY(int count READ count NOTIFY countChanged)
+
+public:
+    enum Role {
+        AppNameRole = Qt::UserRole + 1,
+        SummaryRole,
+        BodyRole,
+        TimeRole,
+    };
+
+private:
+    struct Notification {
+        uint32_t key;
+        QString appName;
+        QString summary;
+        QString body;
+        QDateTime time;
+    };
+
+    QList<Notification> m_notifications;
+    QSet<uint32_t> m_keys;
+
+public:
+    explicit NotificationModel(QObject *parent = nullptr);
+
+    int rowCount(const QModelIndex &parent = QModelIndex()) const override;
+    QVariant data(const QModelIndex &index, int role) const override;
+    QHash<int, QByteArray> roleNames() const override;
+
+    int count() const;
+    void update(uint32_t key, const QString &appName, const QString &summary,
+                const QString &body);
+
+signals:
+    void countChanged();
+};
+
+#endif
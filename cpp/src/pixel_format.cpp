@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#include "pixel_format.hpp"
+
+bool isDeepColorFormat(PixelFormat format) {
+    return format == PixelFormat::Argb2101010 ||
+           format == PixelFormat::Xrgb2101010;
+}
+
+bool hasAlphaChannel(PixelFormat format) {
+    return format == PixelFormat::Argb8888 ||
+           format == PixelFormat::Argb2101010;
+}
+
+int bytesPerPixel(PixelFormat format) {
+    switch (format) {
+        case PixelFormat::Argb8888:
+        case PixelFormat::Xrgb8888:
+        case PixelFormat::Argb2101010:
+        case PixelFormat::Xrgb2101010:
+            return 4;
+        default:
+            return 0;
+    }
+}
+
+void convertRow(PixelFormat format, unsigned char *row, int width) {
+    if (!isDeepColorFormat(format)) {
+        // 8-bit formats are read directly as BGRA, which matches the
+        // little-endian layout of ARGB8888 and XRGB8888
+        return;
+    }
+
+    // GL_UNSIGNED_INT_2_10_10_10_REV stores red in the low bits, while
+    // wl_shm 2101010 formats store blue in the low bits, so swap them
+    uint32_t *pixels = reinterpret_cast<uint32_t *>(row);
+    for (int x = 0; x < width; x++) {
+        uint32_t p = pixels[x];
+        uint32_t r = p & 0x3FF;
+        uint32_t b = (p >> 20) & 0x3FF;
+        pixels[x] = (p & 0xC00FFC00) | (r << 20) | b;
+    }
+}
+
+void widenRow(unsigned char *row, int width) {
+    uint32_t *pixels = reinterpret_cast<uint32_t *>(row);
+    for (int x = 0; x < width; x++) {
+        uint32_t p = pixels[x];
+        uint32_t a = p >> 24;
+        uint32_t r = (p >> 16) & 0xFF;
+        uint32_t g = (p >> 8) & 0xFF;
+        uint32_t b = p & 0xFF;
+
+        // Repeat the high bits, so 0xFF becomes 0x3FF
+        r = (r << 2) | (r >> 6);
+        g = (g << 2) | (g >> 6);
+        b = (b << 2) | (b >> 6);
+        pixels[x] = ((a >> 6) << 30) | (r << 20) | (g << 10) | b;
+    }
+}
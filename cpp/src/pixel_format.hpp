@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#pragma once
+
+#ifndef PIXEL_FORMAT_HPP
+#define PIXEL_FORMAT_HPP
+
+#include <cstdint>
+
+/// Pixel formats used for Wayland buffers
+///
+/// Values match the `wl_shm::format` codes selected by the Rust side.
+enum class PixelFormat : uint32_t {
+    Argb8888 = 0,
+    Xrgb8888 = 1,
+    Argb2101010 = 0x30335241,
+    Xrgb2101010 = 0x30335258,
+};
+
+/// Returns true if `format` stores 10 bits per colour channel
+bool isDeepColorFormat(PixelFormat format);
+
+/// Returns true if `format` has a meaningful alpha channel
+bool hasAlphaChannel(PixelFormat format);
+
+/// Returns the number of bytes used to store a single pixel of `format`
+int bytesPerPixel(PixelFormat format);
+
+/// Convert a row of pixels read with `glReadPixels` into `format`, in place
+void convertRow(PixelFormat format, unsigned char *row, int width);
+
+/// Convert a row of 8-bit BGRA pixels into a 10-bit `wl_shm` format, in place
+///
+/// Used when the framebuffer cannot store 10 bits per channel, but the
+/// buffers use a 10-bit format.
+void widenRow(unsigned char *row, int width);
+
+#endif
@@ -8,6 +8,7 @@
 #include <QCoreApplication>
 #include <QGuiApplication>
 #include <QQuickItem>
+#include <QWheelEvent>
 #include <QWindow>
 #include <iostream>
 
@@ -41,6 +42,95 @@ void PointerHandler::handleButtonEvent(uint32_t button, ButtonState state) {
     }
 }
 
+void PointerHandler::handleEnterEvent(double surface_x, double surface_y) {
+    if (!m_renderer->window) {
+        error_log(FILENAME, "No renderer window available");
+        return;
+    }
+
+    m_globalPos = QPointF(surface_x, surface_y);
+
+    QCoreApplication::postEvent(
+        m_renderer->window,
+        new QEnterEvent(m_globalPos, m_globalPos, m_globalPos));
+
+    // Items under the pointer are only hovered after it moves
+    sendMouseEvent(QEvent::MouseMove, m_globalPos, Qt::NoButton, m_buttonState);
+}
+
+void PointerHandler::handleLeaveEvent() {
+    if (!m_renderer->window) {
+        error_log(FILENAME, "No renderer window available");
+        return;
+    }
+
+    // The compositor does not send button releases after the pointer leaves
+    m_buttonState = Qt::NoButton;
+    m_scrolling = false;
+
+    QCoreApplication::postEvent(m_renderer->window, new QEvent(QEvent::Leave));
+}
+
+/// Convert scrolling over one pointer frame into a `QWheelEvent`
+///
+/// Wayland scrolls down and right with positive values, Qt scrolls up and
+/// left with positive values. `angleDelta` is in eighths of a degree, so one
+/// wheel step of 120 is 15 degrees, as in Qt.
+///
+/// Touchpads and other continuous sources also get a `pixelDelta`, and scroll
+/// phases, so flickables can follow them exactly.
+void PointerHandler::handleAxisEvent(const PointerAxisPayload &axis) {
+    if (!m_renderer->window) {
+        error_log(FILENAME, "No renderer window available");
+        return;
+    }
+
+    const AxisSource source = static_cast<AxisSource>(axis.source);
+    const bool continuous =
+        source == AxisSource::Finger || source == AxisSource::Continuous;
+
+    // Without wheel steps, assume 10 pixels per step, as libinput does
+    QPoint angleDelta(
+        axis.horizontal_120 != 0 ? -axis.horizontal_120
+                                 : qRound(-axis.horizontal * 12.0),
+        axis.vertical_120 != 0 ? -axis.vertical_120
+                               : qRound(-axis.vertical * 12.0));
+
+    QPoint pixelDelta;
+    if (continuous) {
+        pixelDelta = QPoint(qRound(-axis.horizontal), qRound(-axis.vertical));
+    }
+
+    bool stopped = axis.stop != 0;
+    Qt::ScrollPhase phase = Qt::NoScrollPhase;
+
+    if (continuous) {
+        if (stopped) {
+            phase = Qt::ScrollEnd;
+            m_scrolling = false;
+        } else if (!m_scrolling) {
+            phase = Qt::ScrollBegin;
+            m_scrolling = true;
+        } else {
+            phase = Qt::ScrollUpdate;
+        }
+    }
+
+    if (angleDelta.isNull() && pixelDelta.isNull() &&
+        phase != Qt::ScrollEnd) {
+        return;
+    }
+
+    Qt::KeyboardModifiers keyboardModifiers =
+        m_keyboardHandler->xkbStateToQtModifiers();
+
+    QWheelEvent *event =
+        new QWheelEvent(m_globalPos, m_globalPos, pixelDelta, angleDelta,
+                        m_buttonState, keyboardModifiers, phase, false);
+
+    QCoreApplication::postEvent(m_renderer->window, event);
+}
+
 void PointerHandler::sendMouseEvent(QEvent::Type eventType, QPointF globalPos,
                                     Qt::MouseButton button,
                                     Qt::MouseButtons buttons) {
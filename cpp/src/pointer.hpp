@@ -6,6 +6,7 @@
 #ifndef POINTER_HPP
 #define POINTER_HPP
 
+#include "event.hpp"
 #include <QMouseEvent>
 #include <cstdint>
 
@@ -14,6 +15,14 @@ enum class ButtonState : uint32_t {
     Pressed = 1,
 };
 
+/// `wl_pointer` axis sources
+enum class AxisSource : uint32_t {
+    Wheel = 0,
+    Finger = 1,
+    Continuous = 2,
+    WheelTilt = 3,
+};
+
 struct QmlRenderer;
 class KeyboardHandler;
 
@@ -23,6 +32,7 @@ private:
     KeyboardHandler *m_keyboardHandler;
     Qt::MouseButtons m_buttonState;
     QPointF m_globalPos;
+    bool m_scrolling = false;
 
 public:
     explicit PointerHandler(QmlRenderer *renderer,
@@ -31,6 +41,9 @@ public:
 
     void handleMotionEvent(double surface_x, double surface_y);
     void handleButtonEvent(uint32_t button, ButtonState state);
+    void handleEnterEvent(double surface_x, double surface_y);
+    void handleLeaveEvent();
+    void handleAxisEvent(const PointerAxisPayload &axis);
 
     void sendMouseEvent(QEvent::Type eventType, QPointF globalPos,
                         Qt::MouseButton button, Qt::MouseButtons buttons);
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+        preview.cpp:
+                Theme reloading and error display for preview mode.
+*/
+
+#include "preview.hpp"
+#include "logging.hpp"
+#include "render.hpp"
+
+static const char *FILENAME = "tlockr_qt/preview.cpp";
+
+/// QML source for the error overlay
+///
+/// This is compiled from memory, so it still works when the theme's own
+/// files are broken.
+static const char *ERROR_OVERLAY_QML = R"(
+import QtQuick
+
+Rectangle {
+    property alias text: label.text
+
+    color: "#E0200000"
+    z: 1000000
+
+    Text {
+        id: label
+        anchors.fill: parent
+        anchors.margins: 24
+        color: "#FFFFFF"
+        font.family: "monospace"
+        font.pixelSize: 16
+        wrapMode: Text.WrapAnywhere
+        textFormat: Text.PlainText
+    }
+}
+)";
+
+void show_error_overlay(QmlRenderer *renderer, const QList<QQmlError> &errors) {
+    QString text = "QML component has errors:\n\n";
+    for (const auto &error : errors) {
+        text += error.toString() + "\n";
+    }
+
+    if (!renderer->errorOverlay) {
+        QQmlComponent overlayComponent(renderer->engine);
+        overlayComponent.setData(ERROR_OVERLAY_QML,
+                                 QUrl("qrc:/tlockr/error_overlay.qml"));
+
+        QObject *overlayObject = overlayComponent.create();
+        QQuickItem *overlay = qobject_cast<QQuickItem *>(overlayObject);
+        if (!overlay) {
+            error_log(FILENAME, "Failed to create error overlay");
+            delete overlayObject;
+            return;
+        }
+
+        overlay->setParentItem(renderer->window->contentItem());
+        overlay->setWidth(renderer->fbSize.width());
+        overlay->setHeight(renderer->fbSize.height());
+
+        renderer->errorOverlay = overlay;
+    }
+
+    renderer->errorOverlay->setProperty("text", text);
+
+    // The overlay needs to be rendered, even without any theme content
+    renderer->running = true;
+}
+
+void hide_error_overlay(QmlRenderer *renderer) {
+    if (!renderer->errorOverlay) {
+        return;
+    }
+
+    renderer->errorOverlay->setParentItem(nullptr);
+    renderer->errorOverlay->deleteLater();
+    renderer->errorOverlay = nullptr;
+}
+
+void reload_component(QmlRenderer *renderer) {
+    if (renderer->rootItem) {
+        renderer->rootItem->setParentItem(nullptr);
+        renderer->rootItem->deleteLater();
+        renderer->rootItem = nullptr;
+    }
+
+    if (renderer->component) {
+        renderer->component->deleteLater();
+    }
+
+    // Make sure changed files are read from disk again
+    renderer->engine->clearComponentCache();
+
+    renderer->component = new QQmlComponent(renderer->engine);
+    setup_component_signals(renderer);
+    load_component(renderer);
+
+    info_log(FILENAME, "Reloaded QML component");
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#pragma once
+
+#ifndef PREVIEW_HPP
+#define PREVIEW_HPP
+
+#include <QList>
+#include <QQmlError>
+
+struct QmlRenderer;
+
+/// Display QML errors in an overlay covering the preview window
+void show_error_overlay(QmlRenderer *renderer, const QList<QQmlError> &errors);
+
+/// Remove the error overlay, if it is being displayed
+void hide_error_overlay(QmlRenderer *renderer);
+
+/// Destroy the current QML content, and load it again from disk
+void reload_component(QmlRenderer *renderer);
+
+#endif
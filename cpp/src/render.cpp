@@ -8,26 +8,82 @@
 
 #include "render.hpp"
 #include "event_handler.hpp"
+#include "image_provider.hpp"
 #include "interface.hpp"
-#include "keyboard_repeat.hpp"
 #include "logging.hpp"
+#include "preview.hpp"
+#include "sandbox.hpp"
+#include <cstring>
+#include <vector>
 
 static const char *FILENAME = "tlockr_qt/render.cpp";
 
+/// The Qt application and the thread it runs on, shared by the renderers on
+/// every output
+///
+/// Qt only allows a single `QGuiApplication` per process, so it is started
+/// with the first renderer and stopped with the last. Renderers which have
+/// been cleaned up are kept in `stopped` until then, as their Qt objects can
+/// still use them while the thread runs.
+struct RenderHost {
+    QGuiApplication *app = nullptr;
+    std::thread thread;
+    std::atomic<bool> shouldStop{false};
+    std::mutex mutex;
+    std::condition_variable condition;
+    bool started = false;
+    int renderers = 0;
+    std::vector<QmlRenderer *> stopped;
+};
+
+static RenderHost host;
+
+/// Returns whether `context` can render to and read from 10-bit framebuffers
+///
+/// The formats are core in desktop OpenGL and GLES 3, but GLES 2 needs
+/// `GL_EXT_texture_type_2_10_10_10_REV`.
+static bool supportsDeepColor(QOpenGLContext *context) {
+    if (!context->isOpenGLES() || context->format().majorVersion() >= 3) {
+        return true;
+    }
+
+    return context->hasExtension(
+        QByteArrayLiteral("GL_EXT_texture_type_2_10_10_10_REV"));
+}
+
 #ifdef __cplusplus
 extern "C" {
 #endif
 
-int writeEvent(int fd, EventType event_type, EventParam param_1,
-               EventParam param_2) {
-    Event ev = {
+/// Write a single message to an event pipe
+///
+/// The header and payload are written together, so the message is written
+/// atomically.
+int writeEvent(int fd, EventType event_type, const void *payload,
+               size_t length) {
+    if (length > EVENT_MAX_PAYLOAD_SIZE) {
+        error_log(FILENAME,
+                  format_log("Event payload of ", length,
+                             " bytes exceeds the maximum of ",
+                             EVENT_MAX_PAYLOAD_SIZE, " bytes")
+                      .c_str());
+        return -1;
+    }
+
+    EventMessage message;
+    message.header = {
+        EVENT_MESSAGE_MAGIC,
+        EVENT_PROTOCOL_VERSION,
         event_type,
-        param_1,
-        param_2,
+        static_cast<uint32_t>(length),
     };
+    if (length > 0) {
+        std::memcpy(message.payload, payload, length);
+    }
 
-    ssize_t res = write(fd, &ev, sizeof(Event));
-    if (res != sizeof(Event)) {
+    ssize_t size = static_cast<ssize_t>(sizeof(EventHeader) + length);
+    ssize_t res = write(fd, &message, size);
+    if (res != size) {
         error_log(
             FILENAME,
             format_log("Failed to write event: ", strerror(errno)).c_str());
@@ -37,12 +93,12 @@ int writeEvent(int fd, EventType event_type, EventParam param_1,
     return 0;
 }
 
-QmlRenderer *initialize_renderer(int width, int height, const char *qmlPath,
+QmlRenderer *initialize_renderer(OutputState *output,
                                  ApplicationState *appState) {
     QmlRenderer *renderer = new QmlRenderer();
-    renderer->fbSize = QSize(width, height);
-    renderer->qmlPath = qmlPath;
+    renderer->fbSize = QSize(output->width, output->height);
     renderer->appState = appState;
+    renderer->output = output;
 
     return renderer;
 }
@@ -66,7 +122,7 @@ void set_initialize(QmlRenderer *renderer) {
 void setup_event_socket(QmlRenderer *renderer) {
     // Set non-blocking I/O on the read file descriptor, required by
     // QSocketNotifier
-    int fd = renderer->appState->rendererReadFd;
+    int fd = renderer->output->rendererReadFd;
     fcntl(fd, F_SETFL, O_NONBLOCK);
 
     QSocketNotifier *notifier = new QSocketNotifier(fd, QSocketNotifier::Read);
@@ -81,19 +137,12 @@ void setup_event_socket(QmlRenderer *renderer) {
 }
 
 void send_frame_rendered_event(QmlRenderer *renderer, void *buf) {
+    RendererPayload payload = {reinterpret_cast<uint64_t>(buf)};
     writeEvent(renderer->appState->rendererWriteFd, EventType::Renderer,
-               reinterpret_cast<EventParam>(buf), 0);
+               &payload, sizeof(payload));
 }
 
 void setup_renderer(QmlRenderer *renderer) {
-    int argc = 0;
-    char **argv = nullptr;
-
-    // Install custom message handler
-    qInstallMessageHandler(qtMessageHandler);
-
-    renderer->app = new QGuiApplication(argc, argv);
-
     renderer->context = new QOpenGLContext();
     renderer->surfaceFormat = new QSurfaceFormat();
 
@@ -135,9 +184,24 @@ void setup_renderer(QmlRenderer *renderer) {
         return;
     }
 
+    PixelFormat format =
+        static_cast<PixelFormat>(renderer->appState->bufferFormat);
+
     renderer->fbFormat = new QOpenGLFramebufferObjectFormat();
     renderer->fbFormat->setAttachment(
         QOpenGLFramebufferObject::CombinedDepthStencil);
+
+    // Render at 10 bits per channel if the buffers can hold it, and the
+    // context can render and read it
+    if (isDeepColorFormat(format)) {
+        if (supportsDeepColor(renderer->context)) {
+            renderer->fbFormat->setInternalTextureFormat(GL_RGB10_A2);
+        } else {
+            warn_log(FILENAME, "10-bit rendering is not supported, rendering "
+                               "at 8 bits per channel");
+        }
+    }
+
     renderer->fb =
         new QOpenGLFramebufferObject(renderer->fbSize, *renderer->fbFormat);
 
@@ -145,7 +209,15 @@ void setup_renderer(QmlRenderer *renderer) {
         renderer->fb->texture(), renderer->fb->size());
     renderer->window->setRenderTarget(renderTarget);
 
+    // Only clear to a transparent background if the compositor will use
+    // the alpha channel
+    if (hasAlphaChannel(format)) {
+        renderer->window->setColor(Qt::transparent);
+    }
+
     renderer->engine = new QQmlEngine();
+    setup_sandbox(renderer);
+    setup_image_provider(renderer);
 
     renderer->interface = new Interface(renderer);
 
@@ -155,12 +227,10 @@ void setup_renderer(QmlRenderer *renderer) {
 
     renderer->component = new QQmlComponent(renderer->engine);
 
-    renderer->keyboardRepeatEngine = new KeyboardRepeatEngine(renderer);
-    renderer->eventHandler =
-        new EventHandler(renderer, renderer->keyboardRepeatEngine);
+    renderer->eventHandler = new EventHandler(renderer);
 }
 
-void setup_renderer_signals(QmlRenderer *renderer) {
+void setup_component_signals(QmlRenderer *renderer) {
     QObject::connect(
         renderer->component, &QQmlComponent::statusChanged, [renderer]() {
             if (renderer->component->status() == QQmlComponent::Ready) {
@@ -183,16 +253,36 @@ void setup_renderer_signals(QmlRenderer *renderer) {
 
                 renderer->rootItem = rootItem;
                 renderer->running = true;
+
+                if (renderer->appState->preview) {
+                    hide_error_overlay(renderer);
+                }
             } else if (renderer->component->status() == QQmlComponent::Error) {
                 error_log(FILENAME, "QML component has errors:");
                 const auto errors = renderer->component->errors();
                 for (const auto &error : errors) {
-                    error_log(renderer->appState->qmlPath,
-                              format_log("\t", error.toString().toStdString())
-                                  .c_str());
+                    QByteArray file = error.url().isLocalFile()
+                                          ? error.url().toLocalFile().toUtf8()
+                                          : error.url().toString().toUtf8();
+                    qml_log(LogLevel::Error, file.constData(), error.line(),
+                            error.description().toUtf8().constData());
+                }
+
+                if (renderer->appState->preview) {
+                    show_error_overlay(renderer, errors);
                 }
             }
         });
+}
+
+void load_component(QmlRenderer *renderer) {
+    info_log(FILENAME, "Loading QML component...");
+    renderer->component->loadUrl(
+        QUrl::fromLocalFile(renderer->output->qmlPath));
+}
+
+void setup_renderer_signals(QmlRenderer *renderer) {
+    setup_component_signals(renderer);
 
     QObject::connect(
         renderer->renderControl, &QQuickRenderControl::renderRequested,
@@ -215,7 +305,9 @@ void setup_renderer_signals(QmlRenderer *renderer) {
             if (renderer->getBufferCallback) {
                 void *buffer = renderer->getBufferCallback(renderer->userData);
                 if (buffer) {
-                    render(*renderer->fb, buffer);
+                    render(*renderer->fb, buffer,
+                           static_cast<PixelFormat>(
+                               renderer->appState->bufferFormat));
                     send_frame_rendered_event(renderer, buffer);
                 }
             }
@@ -229,28 +321,73 @@ void setup_renderer_signals(QmlRenderer *renderer) {
                              Qt::QueuedConnection);
                      });
 
-    QObject::connect(renderer->app, &QGuiApplication::aboutToQuit,
+    QObject::connect(host.app, &QGuiApplication::aboutToQuit,
                      [renderer]() {
                          renderer->running = false;
                          renderer->renderControl->disconnect();
                      });
 }
 
-void qml_renderer_thread(QmlRenderer *renderer) {
+void render_host_thread() {
+    static int argc = 0;
+    char **argv = nullptr;
+
     QGuiApplication::setAttribute(Qt::AA_UseOpenGLES, false);
 
-    setup_renderer(renderer);
-    setup_event_socket(renderer);
-    setup_renderer_signals(renderer);
-    set_initialize(renderer);
+    // Install custom message handler
+    qInstallMessageHandler(qtMessageHandler);
+
+    host.app = new QGuiApplication(argc, argv);
+
+    {
+        std::lock_guard<std::mutex> lock(host.mutex);
+        host.started = true;
+    }
+    host.condition.notify_all();
 
-    renderer->threadRunning = true;
-    while (!renderer->shouldStop && renderer->app) {
-        renderer->app->processEvents(QEventLoop::AllEvents, 16);
+    while (!host.shouldStop) {
+        host.app->processEvents(QEventLoop::AllEvents, 16);
         std::this_thread::sleep_for(std::chrono::milliseconds(1));
     }
+}
+
+/// Start the Qt thread, if this is the first renderer
+void acquire_render_host() {
+    std::unique_lock<std::mutex> lock(host.mutex);
+    if (host.renderers++ > 0) {
+        return;
+    }
+
+    host.shouldStop = false;
+    host.started = false;
+    host.thread = std::thread(render_host_thread);
+    host.condition.wait(lock, [] { return host.started; });
+}
+
+/// Stop the Qt thread if this was the last renderer, then delete every
+/// stopped renderer
+void release_render_host(QmlRenderer *renderer) {
+    std::vector<QmlRenderer *> stopped;
+    {
+        std::lock_guard<std::mutex> lock(host.mutex);
+        host.stopped.push_back(renderer);
+        if (--host.renderers > 0) {
+            return;
+        }
+
+        stopped.swap(host.stopped);
+    }
 
-    renderer->threadRunning = false;
+    host.shouldStop = true;
+    if (host.thread.joinable()) {
+        host.thread.join();
+    }
+
+    info_log(FILENAME, "Renderer thread exited");
+
+    for (QmlRenderer *stoppedRenderer : stopped) {
+        delete stoppedRenderer;
+    }
 }
 
 int start_renderer(QmlRenderer *renderer) {
@@ -259,7 +396,18 @@ int start_renderer(QmlRenderer *renderer) {
         return -1;
     }
 
-    renderer->renderThread = std::thread(qml_renderer_thread, renderer);
+    acquire_render_host();
+
+    // Every renderer is set up on the Qt thread, which owns its objects
+    QMetaObject::invokeMethod(
+        host.app,
+        [renderer]() {
+            setup_renderer(renderer);
+            setup_event_socket(renderer);
+            setup_renderer_signals(renderer);
+            set_initialize(renderer);
+        },
+        Qt::QueuedConnection);
 
     std::unique_lock<std::mutex> lock(renderer->initMutex);
     renderer->initCondition.wait(
@@ -271,12 +419,7 @@ int start_renderer(QmlRenderer *renderer) {
     }
 
     QMetaObject::invokeMethod(
-        renderer->component,
-        [renderer]() {
-            info_log(FILENAME, "Loading QML component...");
-            renderer->component->loadUrl(
-                QUrl::fromLocalFile(renderer->qmlPath));
-        },
+        renderer->component, [renderer]() { load_component(renderer); },
         Qt::QueuedConnection);
 
     return 0;
@@ -300,16 +443,26 @@ void cleanup_renderer(QmlRenderer *renderer) {
     renderer->shouldStop = true;
     renderer->running = false;
 
-    if (renderer->renderThread.joinable()) {
-        renderer->renderThread.join();
+    if (renderer->initialized) {
+        // Stop rendering and reading events on the Qt thread, so nothing
+        // uses the renderer once it is deleted
+        QMetaObject::invokeMethod(
+            host.app,
+            [renderer]() {
+                renderer->renderControl->disconnect();
+                renderer->eventSocketNotifier->setEnabled(false);
+            },
+            Qt::BlockingQueuedConnection);
+
+        release_render_host(renderer);
+        return;
     }
 
     delete renderer;
-
-    info_log("tlockr_qt/render.cpp", "Renderer thread exited");
 }
 
-int render(const QOpenGLFramebufferObject &fbo, void *buffer) {
+int render(const QOpenGLFramebufferObject &fbo, void *buffer,
+           PixelFormat format) {
     int width = fbo.width();
     int height = fbo.height();
 
@@ -324,12 +477,29 @@ int render(const QOpenGLFramebufferObject &fbo, void *buffer) {
     }
 
     unsigned char *outputBuffer = static_cast<unsigned char *>(buffer);
-    int rowSize = width * 4;
+    int rowSize = width * bytesPerPixel(format);
+
+    // The framebuffer is only 10-bit if the context supports it
+    bool deepColor = isDeepColorFormat(format) &&
+                     fbo.format().internalTextureFormat() == GL_RGB10_A2;
+
+    GLenum readFormat = GL_BGRA;
+    GLenum readType = GL_UNSIGNED_BYTE;
+    if (deepColor) {
+        readFormat = GL_RGBA;
+        readType = GL_UNSIGNED_INT_2_10_10_10_REV;
+    }
 
     // Read the framebuffer, starting at the bottom
     for (int y = 0; y < height; y++) {
-        glReadPixels(0, height - 1 - y, width, 1, GL_BGRA, GL_UNSIGNED_BYTE,
-                     outputBuffer + y * rowSize);
+        unsigned char *row = outputBuffer + y * rowSize;
+        glReadPixels(0, height - 1 - y, width, 1, readFormat, readType, row);
+
+        if (deepColor) {
+            convertRow(format, row, width);
+        } else if (isDeepColorFormat(format)) {
+            widenRow(row, width);
+        }
     }
 
     GLenum error = glGetError();
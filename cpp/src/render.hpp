@@ -4,6 +4,7 @@
 #pragma once
 
 #include "event.hpp"
+#include "pixel_format.hpp"
 #ifndef RENDER_HPP
 #define RENDER_HPP
 
@@ -27,6 +28,16 @@
 #include <GLES2/gl2.h>
 #include <GLES2/gl2ext.h>
 
+// 10-bit formats are core in GLES 3, but not declared by the GLES 2 headers.
+// Check `supportsDeepColor` in `render.cpp` before using them.
+#ifndef GL_RGB10_A2
+#define GL_RGB10_A2 0x8059
+#endif
+
+#ifndef GL_UNSIGNED_INT_2_10_10_10_REV
+#define GL_UNSIGNED_INT_2_10_10_10_REV 0x8368
+#endif
+
 #include <atomic>
 #include <condition_variable>
 #include <fcntl.h>
@@ -36,7 +47,7 @@
 
 class EventHandler;
 class Interface;
-class KeyboardRepeatEngine;
+class UrlInterceptor;
 
 #ifdef __cplusplus
 extern "C" {
@@ -44,20 +55,137 @@ extern "C" {
 
 typedef void *(*RsGetBufferCallback)(void *user_data);
 
+/// Information about the user and session, must match `SessionInfo` in
+/// `src/shared/session.rs`
+///
+/// Strings are null if unknown. `lockTime` is in seconds since the Unix
+/// epoch.
+struct SessionInfo {
+    const char *userName;
+    const char *realName;
+    const char *avatarPath;
+    const char *hostName;
+    int64_t lockTime;
+};
+
+/// Type of a theme setting, must match `SettingKind` in
+/// `src/config/theme.rs`
+enum class ThemeSettingKind : uint32_t {
+    String = 0,
+    Bool = 1,
+    Int = 2,
+    Float = 3,
+    Color = 4,
+    Path = 5,
+};
+
+/// A single theme setting, must match `ThemeSettingEntry` in
+/// `src/shared/settings.rs`
+///
+/// `text` holds `String`, `Color` and `Path` values. Only the field matching
+/// `kind` is meaningful.
+struct ThemeSettingEntry {
+    const char *name;
+    ThemeSettingKind kind;
+    const char *text;
+    bool boolean;
+    int64_t integer;
+    double number;
+};
+
+/// The settings of the loaded theme, must match `ThemeSettings` in
+/// `src/shared/settings.rs`
+struct ThemeSettings {
+    const ThemeSettingEntry *entries;
+    size_t count;
+};
+
+/// The theme directories and the permissions declared by the theme, must
+/// match `ThemeSandbox` in `src/shared/sandbox.rs`
+///
+/// `roots` holds a directory for each theme in the chain of `extends`, most
+/// derived first. If `count` is 0, nothing is restricted.
+struct ThemeSandbox {
+    const char *const *roots;
+    size_t count;
+    bool network;
+    bool files;
+};
+
+/// The output the theme is shown on, must match `OutputDetails` in
+/// `src/shared/output.rs`
+///
+/// Strings are null if unknown. `role` is set by the user's output
+/// configuration.
+struct OutputDetails {
+    const char *name;
+    const char *description;
+    const char *role;
+    bool primary;
+};
+
+/// Preferred colour scheme, must match `ColorScheme` in
+/// `src/config/global.rs`
+enum class ColorScheme : uint32_t {
+    Default = 0,
+    Dark = 1,
+    Light = 2,
+};
+
+/// A screenshot of an output, must match `Screenshot` in
+/// `src/shared/background.rs`
+///
+/// `format` is a `wl_shm` format code, either ARGB8888 or XRGB8888.
+struct Screenshot {
+    const char *name;
+    const unsigned char *data;
+    size_t size;
+    int width;
+    int height;
+    int stride;
+    uint32_t format;
+};
+
+/// Images themes can use as their background, must match `Background` in
+/// `src/shared/background.rs`
+///
+/// `wallpaper` is null if no wallpaper is configured.
+struct Background {
+    const Screenshot *screenshots;
+    size_t count;
+    const char *wallpaper;
+};
+
+/// State shared by the renderers on every output, must match
+/// `ApplicationState` in `src/shared/state.rs`
 struct ApplicationState {
-    const char *qmlPath;
     int state;
-    void *renderer;
     int rendererWriteFd;
-    int rendererReadFd;
     int authWriteFd;
     int authReadFd;
-    int outputWidth;
-    int outputHeight;
+    uint32_t bufferFormat;
+    bool preview;
+    SessionInfo session;
+    ColorScheme colorScheme;
+    Background background;
+};
+
+/// State of the renderer for a single output, must match `OutputState` in
+/// `src/shared/output.rs`
+///
+/// Each output can show a different theme, so the theme's settings and
+/// sandbox are kept here.
+struct OutputState {
+    const char *qmlPath;
+    int rendererReadFd;
+    int width;
+    int height;
+    OutputDetails details;
+    ThemeSettings settings;
+    ThemeSandbox sandbox;
 };
 
 struct QmlRenderer {
-    QGuiApplication *app;
     QSize fbSize;
     QOpenGLContext *context;
     QSurfaceFormat *surfaceFormat;
@@ -68,37 +196,39 @@ struct QmlRenderer {
     QOpenGLFramebufferObject *fb;
     QQmlEngine *engine;
     QQmlComponent *component;
+    UrlInterceptor *urlInterceptor = nullptr;
     QSocketNotifier *eventSocketNotifier;
     QQuickItem *rootItem;
+    QQuickItem *errorOverlay = nullptr;
 
-    const char *qmlPath;
     bool running = false;
 
     RsGetBufferCallback getBufferCallback = nullptr;
     void *userData = nullptr;
 
-    std::thread renderThread;
-    std::atomic<bool> threadRunning{false};
     std::atomic<bool> shouldStop{false};
     std::mutex initMutex;
     std::condition_variable initCondition;
     std::atomic<bool> initialized{false};
 
-    EventHandler *eventHandler;
+    EventHandler *eventHandler = nullptr;
     ApplicationState *appState;
+    OutputState *output;
     Interface *interface;
-    KeyboardRepeatEngine *keyboardRepeatEngine;
 };
 
-QmlRenderer *initialize_renderer(int width, int height, const char *qmlPath,
+QmlRenderer *initialize_renderer(OutputState *output,
                                  ApplicationState *appState);
 int start_renderer(QmlRenderer *renderer);
 void set_callbacks(QmlRenderer *renderer, RsGetBufferCallback getBuffer,
                    void *userData);
-int render(const QOpenGLFramebufferObject &fbo, void *buffer);
+int render(const QOpenGLFramebufferObject &fbo, void *buffer,
+           PixelFormat format);
 void cleanup_renderer(QmlRenderer *renderer);
-int writeEvent(int fd, EventType event_type, EventParam param_1,
-               EventParam param_2);
+void setup_component_signals(QmlRenderer *renderer);
+void load_component(QmlRenderer *renderer);
+int writeEvent(int fd, EventType event_type, const void *payload,
+               size_t length);
 
 #ifdef __cplusplus
 }
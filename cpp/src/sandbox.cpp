@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+        sandbox.cpp:
+                Restricts the files and network resources a theme can load.
+*/
+
+#include "sandbox.hpp"
+#include "logging.hpp"
+#include "render.hpp"
+
+#include <QDir>
+#include <QFileInfo>
+#include <QLibraryInfo>
+
+static const char *FILENAME = "tlockr_qt/sandbox.cpp";
+
+QString canonicalPath(const QString &path) {
+    QFileInfo info(path);
+    QString canonical = info.canonicalFilePath();
+    return canonical.isEmpty() ? QDir::cleanPath(info.absoluteFilePath())
+                               : canonical;
+}
+
+bool isInside(const QString &path, const QString &dir) {
+    if (path == dir) {
+        return true;
+    }
+
+    return path.startsWith(dir.endsWith('/') ? dir : dir + '/');
+}
+
+QStringList sandboxRoots(const ThemeSandbox &sandbox) {
+    QStringList roots;
+    for (size_t i = 0; i < sandbox.count; i++) {
+        roots.append(canonicalPath(QString::fromUtf8(sandbox.roots[i])));
+    }
+
+    return roots;
+}
+
+/// Returns whether `path` is a QML import path inside a Qt resource
+static bool isResourcePath(const QString &path) {
+    return path.startsWith(QStringLiteral("qrc:")) ||
+           path.startsWith(QStringLiteral(":"));
+}
+
+UrlInterceptor::UrlInterceptor(const ApplicationState *appState,
+                               const OutputState *output,
+                               const QStringList &importPaths)
+    : m_roots(sandboxRoots(output->sandbox)),
+      m_network(output->sandbox.network), m_files(output->sandbox.files) {
+    for (const QString &path : importPaths) {
+        if (!isResourcePath(path)) {
+            m_importPaths.append(canonicalPath(path));
+        }
+    }
+
+    if (appState->session.avatarPath) {
+        m_allowedFiles.insert(
+            canonicalPath(QString::fromUtf8(appState->session.avatarPath)));
+    }
+
+    // Paths in settings are either inside the theme, or chosen by the user
+    const ThemeSettings &settings = output->settings;
+    for (size_t i = 0; i < settings.count; i++) {
+        const ThemeSettingEntry &entry = settings.entries[i];
+        if (entry.kind == ThemeSettingKind::Path && entry.text) {
+            m_allowedFiles.insert(canonicalPath(QString::fromUtf8(entry.text)));
+        }
+    }
+}
+
+/// Allow the theme to load `url`, such as album art sent by a media player
+void UrlInterceptor::allowUrl(const QUrl &url) {
+    if (url.isEmpty()) {
+        return;
+    }
+
+    if (url.isLocalFile()) {
+        m_allowedFiles.insert(canonicalPath(url.toLocalFile()));
+    } else {
+        m_allowedUrls.insert(url);
+    }
+}
+
+bool UrlInterceptor::isAllowedFile(const QString &path) const {
+    if (m_files) {
+        return true;
+    }
+
+    QString canonical = canonicalPath(path);
+    if (m_allowedFiles.contains(canonical)) {
+        return true;
+    }
+
+    for (const QString &dir : m_roots + m_importPaths) {
+        if (isInside(canonical, dir)) {
+            return true;
+        }
+    }
+
+    return false;
+}
+
+bool UrlInterceptor::isAllowed(const QUrl &url) const {
+    QString scheme = url.scheme();
+
+    // Resources compiled into Qt and tlockr, and image providers
+    if (scheme == QStringLiteral("qrc") || scheme == QStringLiteral("data") ||
+        scheme == QStringLiteral("image")) {
+        return true;
+    }
+
+    if (url.isLocalFile()) {
+        return isAllowedFile(url.toLocalFile());
+    }
+
+    return m_network || m_allowedUrls.contains(url);
+}
+
+QUrl UrlInterceptor::intercept(const QUrl &url, DataType type) {
+    if (url.isEmpty() || isAllowed(url)) {
+        return url;
+    }
+
+    warn_log(FILENAME, format_log("Blocked theme from loading ",
+                                  url.toString().toStdString(), " (type ",
+                                  static_cast<int>(type), ")")
+                           .c_str());
+    return QUrl();
+}
+
+/// Restrict QML imports to Qt's own modules and the theme directories, and
+/// install a `UrlInterceptor` for the theme
+///
+/// Import paths from the environment, such as `QML_IMPORT_PATH`, are
+/// dropped. Nothing is restricted if the sandbox has no roots.
+void setup_sandbox(QmlRenderer *renderer) {
+    const OutputState *output = renderer->output;
+    if (output->sandbox.count == 0) {
+        return;
+    }
+
+    QStringList importPaths;
+    for (const QString &path : renderer->engine->importPathList()) {
+        if (isResourcePath(path)) {
+            importPaths.append(path);
+        }
+    }
+    importPaths.append(QLibraryInfo::path(QLibraryInfo::QmlImportsPath));
+    QStringList roots = sandboxRoots(output->sandbox);
+    importPaths.append(roots);
+
+    renderer->engine->setImportPathList(importPaths);
+
+    renderer->urlInterceptor =
+        new UrlInterceptor(renderer->appState, output, importPaths);
+    renderer->engine->addUrlInterceptor(renderer->urlInterceptor);
+
+    debug_log(FILENAME,
+              format_log("Restricted theme to ",
+                         roots.join(QStringLiteral(", ")).toStdString(),
+                         output->sandbox.network ? ", network" : "",
+                         output->sandbox.files ? ", files" : "")
+                  .c_str());
+}
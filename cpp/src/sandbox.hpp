@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#pragma once
+
+#ifndef SANDBOX_HPP
+#define SANDBOX_HPP
+
+#include <QQmlAbstractUrlInterceptor>
+#include <QSet>
+#include <QString>
+#include <QStringList>
+#include <QUrl>
+
+struct ApplicationState;
+struct OutputState;
+struct QmlRenderer;
+struct ThemeSandbox;
+
+/// Returns the canonical form of `path`, or the cleaned absolute path if it
+/// does not exist
+QString canonicalPath(const QString &path);
+
+/// Returns whether `path` is `dir`, or inside it
+bool isInside(const QString &path, const QString &dir);
+
+/// Returns the canonical theme directories of `sandbox`, most derived first
+QStringList sandboxRoots(const ThemeSandbox &sandbox);
+
+/// Blocks URLs the theme is not allowed to load
+///
+/// Themes can load files in their directory, or the directory of a theme
+/// they extend, the QML modules shipped with
+/// Qt, and files tlockr gives them, such as the user's avatar. Any other
+/// local file, or network resource, needs a permission in `theme.toml`.
+class UrlInterceptor : public QQmlAbstractUrlInterceptor {
+public:
+    UrlInterceptor(const ApplicationState *appState, const OutputState *output,
+                   const QStringList &importPaths);
+
+    void allowUrl(const QUrl &url);
+    QUrl intercept(const QUrl &url, DataType type) override;
+
+private:
+    bool isAllowed(const QUrl &url) const;
+    bool isAllowedFile(const QString &path) const;
+
+    QStringList m_roots;
+    bool m_network;
+    bool m_files;
+    QStringList m_importPaths;
+    QSet<QString> m_allowedFiles;
+    QSet<QUrl> m_allowedUrls;
+};
+
+void setup_sandbox(QmlRenderer *renderer);
+
+#endif
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#include "status.hpp"
+
+BatteryStatus::BatteryStatus(QObject *parent) : QObject(parent) {}
+
+bool BatteryStatus::present() const { return m_present; }
+
+int BatteryStatus::level() const { return m_level; }
+
+bool BatteryStatus::charging() const { return m_charging; }
+
+bool BatteryStatus::pluggedIn() const { return m_pluggedIn; }
+
+/// Set the battery status, emitting signals for any changes
+void BatteryStatus::update(bool present, int level, bool charging,
+                           bool pluggedIn) {
+    if (m_present != present) {
+        m_present = present;
+        emit presentChanged();
+    }
+
+    if (m_level != level) {
+        m_level = level;
+        emit levelChanged();
+    }
+
+    if (m_charging != charging) {
+        m_charging = charging;
+        emit chargingChanged();
+    }
+
+    if (m_pluggedIn != pluggedIn) {
+        m_pluggedIn = pluggedIn;
+        emit pluggedInChanged();
+    }
+}
+
+NetworkStatus::NetworkStatus(QObject *parent) : QObject(parent) {}
+
+bool NetworkStatus::connected() const { return m_connected; }
+
+bool NetworkStatus::wireless() const { return m_wireless; }
+
+QString NetworkStatus::interfaceName() const { return m_interfaceName; }
+
+/// Set the network status, emitting signals for any changes
+void NetworkStatus::update(bool connected, bool wireless,
+                           const QString &interfaceName) {
+    if (m_connected != connected) {
+        m_connected = connected;
+        emit connectedChanged();
+    }
+
+    if (m_wireless != wireless) {
+        m_wireless = wireless;
+        emit wirelessChanged();
+    }
+
+    if (m_interfaceName != interfaceName) {
+        m_interfaceName = interfaceName;
+        emit interfaceNameChanged();
+    }
+}
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#pragma once
+
+#ifndef STATUS_HPP
+#define STATUS_HPP
+
+#include <QObject>
+#include <QString>
+
+/// Battery and power adapter status, exposed as `tlockr.battery`
+///
+/// `level` is a percentage, and is only meaningful when `present` is true.
+class BatteryStatus : public QObject {
+    Q_OBJECT
+    Q_PROPERTY(bool present READ present NOTIFY presentChanged)
+    Q_PROPERTY(int level READ level NOTIFY levelChanged)
+    Q_PROPERTY(bool charging READ charging NOTIFY chargingChanged)
+    Q_PROPERTY(bool pluggedIn READ pluggedIn NOTIFY pluggedInChanged)
+
+private:
+    bool m_present = false;
+    int m_level = 0;
+    bool m_charging = false;
+    bool m_pluggedIn = false;
+
+public:
+    explicit BatteryStatus(QObject *parent = nullptr);
+
+    bool present() const;
+    int level() const;
+    bool charging() const;
+    bool pluggedIn() const;
+    void update(bool present, int level, bool charging, bool pluggedIn);
+
+signals:
+    void presentChanged();
+    void levelChanged();
+    void chargingChanged();
+    void pluggedInChanged();
+};
+
+/// Network connection status, exposed as `tlockr.network`
+///
+/// `interfaceName` is empty when not connected.
+class NetworkStatus : public QObject {
+    Q_OBJECT
+    Q_PROPERTY(bool connected READ connected NOTIFY connectedChanged)
+    Q_PROPERTY(bool wireless READ wireless NOTIFY wirelessChanged)
+    Q_PROPERTY(QString interfaceName READ interfaceName NOTIFY
+                   interfaceNameChanged)
+
+private:
+    bool m_connected = false;
+    bool m_wireless = false;
+    QString m_interfaceName;
+
+public:
+    explicit NetworkStatus(QObject *parent = nullptr);
+
+    bool connected() const;
+    bool wireless() const;
+    QString interfaceName() const;
+    void update(bool connected, bool wireless, const QString &interfaceName);
+
+signals:
+    void connectedChanged();
+    void wirelessChanged();
+    void interfaceNameChanged();
+};
+
+#endif
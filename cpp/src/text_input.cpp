@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#include "text_input.hpp"
+#include "logging.hpp"
+#include "render.hpp"
+#include <QCoreApplication>
+#include <QInputMethodEvent>
+#include <QInputMethodQueryEvent>
+#include <QList>
+#include <QQuickItem>
+#include <QTextCharFormat>
+#include <cstring>
+
+static const char *FILENAME = "tlockr_qt/text_input.cpp";
+
+/// Convert Qt input method hints into a text input content type
+static void contentType(Qt::InputMethodHints hints, uint32_t *hint,
+                        uint32_t *purpose) {
+    uint32_t contentHint = ContentHintNone;
+    TextInputContentPurpose contentPurpose = TextInputContentPurpose::Normal;
+
+    if (hints & Qt::ImhHiddenText) {
+        contentHint |= ContentHintHiddenText;
+        contentPurpose = TextInputContentPurpose::Password;
+    }
+    if (hints & Qt::ImhSensitiveData) {
+        contentHint |= ContentHintSensitiveData;
+    }
+    if (!(hints & (Qt::ImhNoPredictiveText | Qt::ImhSensitiveData))) {
+        contentHint |= ContentHintCompletion | ContentHintSpellcheck;
+    }
+    if (!(hints & Qt::ImhNoAutoUppercase)) {
+        contentHint |= ContentHintAutoCapitalization;
+    }
+    if (hints & Qt::ImhPreferLowercase) {
+        contentHint |= ContentHintLowercase;
+    }
+    if (hints & Qt::ImhPreferUppercase) {
+        contentHint |= ContentHintUppercase;
+    }
+    if (hints & Qt::ImhLatinOnly) {
+        contentHint |= ContentHintLatin;
+    }
+    if (hints & Qt::ImhMultiLine) {
+        contentHint |= ContentHintMultiline;
+    }
+
+    if (hints & Qt::ImhDigitsOnly) {
+        contentPurpose = (hints & Qt::ImhHiddenText)
+                             ? TextInputContentPurpose::Pin
+                             : TextInputContentPurpose::Digits;
+    } else if (hints & Qt::ImhFormattedNumbersOnly) {
+        contentPurpose = TextInputContentPurpose::Number;
+    } else if (hints & Qt::ImhDialableCharactersOnly) {
+        contentPurpose = TextInputContentPurpose::Phone;
+    } else if (hints & Qt::ImhUrlCharactersOnly) {
+        contentPurpose = TextInputContentPurpose::Url;
+    } else if (hints & Qt::ImhEmailCharactersOnly) {
+        contentPurpose = TextInputContentPurpose::Email;
+    } else if ((hints & Qt::ImhDate) && (hints & Qt::ImhTime)) {
+        contentPurpose = TextInputContentPurpose::DateTime;
+    } else if (hints & Qt::ImhDate) {
+        contentPurpose = TextInputContentPurpose::Date;
+    } else if (hints & Qt::ImhTime) {
+        contentPurpose = TextInputContentPurpose::Time;
+    }
+
+    *hint = contentHint;
+    *purpose = static_cast<uint32_t>(contentPurpose);
+}
+
+/// Returns the number of UTF-16 code units in the first `bytes` bytes of
+/// UTF-8 `text`
+static int utf16Length(const QByteArray &text, qsizetype bytes) {
+    return QString::fromUtf8(text.left(bytes)).length();
+}
+
+TextInputHandler::TextInputHandler(QmlRenderer *renderer)
+    : m_renderer(renderer) {
+    if (m_renderer->window) {
+        QObject::connect(m_renderer->window, &QQuickWindow::focusObjectChanged,
+                         [this](QObject *) { scheduleUpdate(); });
+    }
+}
+
+TextInputHandler::~TextInputHandler() = default;
+
+QObject *TextInputHandler::focusObject() {
+    if (!m_renderer->window) {
+        return nullptr;
+    }
+
+    return m_renderer->window->focusObject();
+}
+
+/// Query the focused item once all pending events have been delivered
+///
+/// Input is posted to the window, so the focused item, and its cursor, are
+/// only up to date after the event loop has delivered it.
+void TextInputHandler::scheduleUpdate() {
+    if (m_updatePending || !m_renderer->window) {
+        return;
+    }
+
+    m_updatePending = true;
+    QMetaObject::invokeMethod(
+        m_renderer->window,
+        [this]() {
+            m_updatePending = false;
+            update();
+        },
+        Qt::QueuedConnection);
+}
+
+/// Tell the compositor about the focused text field, if it changed
+void TextInputHandler::update() {
+    TextInputUpdatePayload state = {};
+
+    QObject *focus = focusObject();
+    if (focus) {
+        QInputMethodQueryEvent query(Qt::ImEnabled | Qt::ImHints |
+                                     Qt::ImCursorRectangle);
+        QCoreApplication::sendEvent(focus, &query);
+
+        if (query.value(Qt::ImEnabled).toBool()) {
+            state.enabled = 1;
+            contentType(Qt::InputMethodHints(query.value(Qt::ImHints).toInt()),
+                        &state.hint, &state.purpose);
+
+            // The cursor rectangle is in item coordinates
+            QRectF rect = query.value(Qt::ImCursorRectangle).toRectF();
+            if (QQuickItem *item = qobject_cast<QQuickItem *>(focus)) {
+                rect = item->mapRectToScene(rect);
+            }
+
+            const QRect cursor = rect.toAlignedRect();
+            state.x = cursor.x();
+            state.y = cursor.y();
+            state.width = cursor.width();
+            state.height = cursor.height();
+        }
+    }
+
+    if (std::memcmp(&state, &m_state, sizeof(state)) == 0) {
+        return;
+    }
+
+    m_state = state;
+    if (writeEvent(m_renderer->appState->rendererWriteFd,
+                   EventType::TextInputUpdate, &state, sizeof(state)) != 0) {
+        error_log(FILENAME, "Failed to send text input update");
+        return;
+    }
+
+    debug_log(FILENAME, state.enabled ? "Text input enabled"
+                                      : "Text input disabled");
+}
+
+/// Set the composing text of the focused item
+///
+/// The text is underlined, and the cursor is placed at `cursor_end`. If the
+/// cursor positions differ, the text between them is highlighted.
+void TextInputHandler::handlePreeditEvent(const QByteArray &text,
+                                          int32_t cursor_begin,
+                                          int32_t cursor_end) {
+    QObject *focus = focusObject();
+    if (!focus) {
+        return;
+    }
+
+    const QString preedit = QString::fromUtf8(text);
+    QList<QInputMethodEvent::Attribute> attributes;
+
+    QTextCharFormat underline;
+    underline.setFontUnderline(true);
+    attributes.append(QInputMethodEvent::Attribute(
+        QInputMethodEvent::TextFormat, 0, preedit.length(), underline));
+
+    const bool hidden = cursor_begin == -1 && cursor_end == -1;
+    if (hidden || cursor_begin < 0 || cursor_end < 0 ||
+        cursor_begin > text.size() || cursor_end > text.size()) {
+        attributes.append(QInputMethodEvent::Attribute(
+            QInputMethodEvent::Cursor, preedit.length(), 0));
+    } else {
+        const int begin = utf16Length(text, cursor_begin);
+        const int end = utf16Length(text, cursor_end);
+
+        attributes.append(
+            QInputMethodEvent::Attribute(QInputMethodEvent::Cursor, end, 1));
+
+        if (begin != end) {
+            QTextCharFormat highlight;
+            highlight.setFontUnderline(true);
+            highlight.setFontWeight(QFont::Bold);
+            attributes.append(QInputMethodEvent::Attribute(
+                QInputMethodEvent::TextFormat, qMin(begin, end),
+                qAbs(end - begin), highlight));
+        }
+    }
+
+    QCoreApplication::postEvent(focus,
+                                new QInputMethodEvent(preedit, attributes));
+    scheduleUpdate();
+}
+
+/// Insert text into the focused item, replacing any composing text
+///
+/// The surrounding text is never sent to the compositor, as it may be a
+/// password, but it is read here to convert the deleted byte counts into
+/// characters.
+void TextInputHandler::handleCommitEvent(const QByteArray &text,
+                                         uint32_t delete_before,
+                                         uint32_t delete_after) {
+    QObject *focus = focusObject();
+    if (!focus) {
+        return;
+    }
+
+    int replaceFrom = 0;
+    int replaceLength = 0;
+
+    if (delete_before != 0 || delete_after != 0) {
+        QInputMethodQueryEvent query(Qt::ImSurroundingText |
+                                     Qt::ImCursorPosition);
+        QCoreApplication::sendEvent(focus, &query);
+
+        const QString surrounding =
+            query.value(Qt::ImSurroundingText).toString();
+        const int cursor = query.value(Qt::ImCursorPosition).toInt();
+
+        const QByteArray before = surrounding.left(cursor).toUtf8();
+        const QByteArray after = surrounding.mid(cursor).toUtf8();
+
+        const int beforeLength = QString::fromUtf8(before.right(
+                                     qMin<qsizetype>(delete_before,
+                                                     before.size())))
+                                     .length();
+        const int afterLength =
+            utf16Length(after, qMin<qsizetype>(delete_after, after.size()));
+
+        replaceFrom = -beforeLength;
+        replaceLength = beforeLength + afterLength;
+    }
+
+    QInputMethodEvent *event = new QInputMethodEvent();
+    event->setCommitString(QString::fromUtf8(text), replaceFrom,
+                           replaceLength);
+    QCoreApplication::postEvent(focus, event);
+    scheduleUpdate();
+}
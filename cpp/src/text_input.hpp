@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#pragma once
+
+#ifndef TEXT_INPUT_HPP
+#define TEXT_INPUT_HPP
+
+#include "event.hpp"
+#include <QByteArray>
+#include <Qt>
+#include <cstdint>
+
+/// `zwp_text_input_v3` content hints
+enum TextInputContentHint : uint32_t {
+    ContentHintNone = 0x0,
+    ContentHintCompletion = 0x1,
+    ContentHintSpellcheck = 0x2,
+    ContentHintAutoCapitalization = 0x4,
+    ContentHintLowercase = 0x8,
+    ContentHintUppercase = 0x10,
+    ContentHintTitlecase = 0x20,
+    ContentHintHiddenText = 0x40,
+    ContentHintSensitiveData = 0x80,
+    ContentHintLatin = 0x100,
+    ContentHintMultiline = 0x200,
+};
+
+/// `zwp_text_input_v3` content purposes
+enum class TextInputContentPurpose : uint32_t {
+    Normal = 0,
+    Alpha = 1,
+    Digits = 2,
+    Number = 3,
+    Phone = 4,
+    Url = 5,
+    Email = 6,
+    Name = 7,
+    Password = 8,
+    Pin = 9,
+    Date = 10,
+    Time = 11,
+    DateTime = 12,
+    Terminal = 13,
+};
+
+struct QmlRenderer;
+class QObject;
+
+/// Connects input methods to the text field focused in the renderer
+///
+/// Input method text is delivered to the focused item as `QInputMethodEvent`s.
+/// The focused item is queried whenever it may have changed, and the
+/// compositor is told when a text field is focused, so it can enable the
+/// input method.
+class TextInputHandler {
+private:
+    QmlRenderer *m_renderer;
+    TextInputUpdatePayload m_state = {};
+    bool m_updatePending = false;
+
+    QObject *focusObject();
+    void update();
+
+public:
+    explicit TextInputHandler(QmlRenderer *renderer);
+    ~TextInputHandler();
+
+    void scheduleUpdate();
+    void handlePreeditEvent(const QByteArray &text, int32_t cursor_begin,
+                            int32_t cursor_end);
+    void handleCommitEvent(const QByteArray &text, uint32_t delete_before,
+                           uint32_t delete_after);
+};
+
+#endif
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#include "touch.hpp"
+#include "keyboard.hpp"
+#include "logging.hpp"
+#include "render.hpp"
+#include <QCoreApplication>
+#include <QEventPoint>
+#include <QList>
+#include <QTouchEvent>
+
+static const char *FILENAME = "tlockr_qt/touch.cpp";
+
+/// Maximum number of touch points reported for the touch device
+static const int MAX_TOUCH_POINTS = 10;
+
+TouchHandler::TouchHandler(QmlRenderer *renderer,
+                           KeyboardHandler *keyboardHandler, uint32_t seat)
+    : m_renderer(renderer), m_keyboardHandler(keyboardHandler) {
+    // Each seat has its own touchscreen, identified by the seat id
+    m_device = new QPointingDevice(
+        QString("tlockr touchscreen (seat %1)").arg(seat), seat,
+        QInputDevice::DeviceType::TouchScreen,
+        QPointingDevice::PointerType::Finger,
+        QInputDevice::Capability::Position, MAX_TOUCH_POINTS, 0);
+}
+
+// Touch events already posted to the window may still refer to the device
+TouchHandler::~TouchHandler() { m_device->deleteLater(); }
+
+/// Send the touch points changed over one touch frame to the window
+///
+/// Qt expects every active point in each touch event, so points that did not
+/// change are sent as stationary. The event is a `TouchBegin` if no points
+/// were active before, and a `TouchEnd` if none are active after.
+void TouchHandler::handleFrameEvent(
+    const std::vector<TouchPointPayload> &points) {
+    if (!m_renderer->window) {
+        error_log(FILENAME, "No renderer window available");
+        return;
+    }
+
+    const bool wasActive = !m_points.isEmpty();
+    QMap<int32_t, QEventPoint::State> changed;
+
+    for (const TouchPointPayload &point : points) {
+        switch (point.state) {
+            case TouchPointState::Down:
+                m_points.insert(point.id, QPointF(point.x, point.y));
+                changed.insert(point.id, QEventPoint::State::Pressed);
+                break;
+            case TouchPointState::Motion:
+                if (!m_points.contains(point.id)) {
+                    warn_log(FILENAME,
+                             format_log("Motion for unknown touch point ",
+                                        point.id)
+                                 .c_str());
+                    continue;
+                }
+                m_points.insert(point.id, QPointF(point.x, point.y));
+                if (!changed.contains(point.id)) {
+                    changed.insert(point.id, QEventPoint::State::Updated);
+                }
+                break;
+            case TouchPointState::Up:
+                if (!m_points.contains(point.id)) {
+                    warn_log(FILENAME,
+                             format_log("Release of unknown touch point ",
+                                        point.id)
+                                 .c_str());
+                    continue;
+                }
+                changed.insert(point.id, QEventPoint::State::Released);
+                break;
+            default:
+                warn_log(FILENAME,
+                         format_log("Rejected unknown touch point state ",
+                                    static_cast<uint32_t>(point.state))
+                             .c_str());
+                continue;
+        }
+    }
+
+    if (changed.isEmpty()) {
+        return;
+    }
+
+    QList<QEventPoint> eventPoints;
+    for (auto it = m_points.constBegin(); it != m_points.constEnd(); ++it) {
+        QEventPoint::State state =
+            changed.value(it.key(), QEventPoint::State::Stationary);
+        eventPoints.append(QEventPoint(it.key(), state, it.value(), it.value()));
+    }
+
+    // Released points are only sent in this event
+    for (auto it = changed.constBegin(); it != changed.constEnd(); ++it) {
+        if (it.value() == QEventPoint::State::Released) {
+            m_points.remove(it.key());
+        }
+    }
+
+    QEvent::Type eventType = QEvent::TouchUpdate;
+    if (!wasActive) {
+        eventType = QEvent::TouchBegin;
+    } else if (m_points.isEmpty()) {
+        eventType = QEvent::TouchEnd;
+    }
+
+    QTouchEvent *event =
+        new QTouchEvent(eventType, m_device,
+                        m_keyboardHandler->xkbStateToQtModifiers(), eventPoints);
+
+    QCoreApplication::postEvent(m_renderer->window, event);
+}
+
+/// Cancel every active touch point
+///
+/// The compositor sends this when it takes over the touch sequence, so no
+/// release is sent for the active points.
+void TouchHandler::handleCancelEvent() {
+    if (m_points.isEmpty()) {
+        return;
+    }
+
+    m_points.clear();
+
+    if (!m_renderer->window) {
+        error_log(FILENAME, "No renderer window available");
+        return;
+    }
+
+    QCoreApplication::postEvent(m_renderer->window,
+                                new QTouchEvent(QEvent::TouchCancel, m_device));
+}
@@ -0,0 +1,35 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+#pragma once
+
+#ifndef TOUCH_HPP
+#define TOUCH_HPP
+
+#include "event.hpp"
+#include <QMap>
+#include <QPointF>
+#include <QPointingDevice>
+#include <cstdint>
+#include <vector>
+
+struct QmlRenderer;
+class KeyboardHandler;
+
+class TouchHandler {
+private:
+    QmlRenderer *m_renderer;
+    KeyboardHandler *m_keyboardHandler;
+    QPointingDevice *m_device;
+    QMap<int32_t, QPointF> m_points;
+
+public:
+    explicit TouchHandler(QmlRenderer *renderer,
+                          KeyboardHandler *keyboardHandler, uint32_t seat);
+    ~TouchHandler();
+
+    void handleFrameEvent(const std::vector<TouchPointPayload> &points);
+    void handleCancelEvent();
+};
+
+#endif
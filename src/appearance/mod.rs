@@ -0,0 +1,3 @@
+mod portal;
+
+pub use portal::*;
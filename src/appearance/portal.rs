@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    portal.rs:
+        Reads the system colour scheme preference from the desktop portal
+*/
+
+use crate::config::{AppearanceConfig, ColorScheme};
+
+use std::time::Duration;
+use tracing::{debug, warn};
+use zbus::blocking::connection;
+use zbus::proxy;
+use zbus::zvariant::{OwnedValue, Value};
+
+/// Namespace of the portal's appearance settings
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+
+/// Key of the colour scheme preference
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+
+/// How long to wait for the portal, so a missing or slow portal does not
+/// delay locking
+const PORTAL_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Settings interface of the desktop portal
+#[proxy(
+    interface = "org.freedesktop.portal.Settings",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop",
+    gen_async = false,
+    blocking_name = "PortalSettingsProxy"
+)]
+trait PortalSettings {
+    fn read_one(&self, namespace: &str, key: &str) -> zbus::Result<OwnedValue>;
+
+    /// Deprecated in favour of `read_one`, and returns the value wrapped in
+    /// another variant
+    fn read(&self, namespace: &str, key: &str) -> zbus::Result<OwnedValue>;
+}
+
+impl ColorScheme {
+    /// Convert a value of the portal's `color-scheme` setting
+    ///
+    /// Unknown values are treated as no preference.
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Value(inner) => Self::from_value(inner),
+            Value::U32(1) => Some(ColorScheme::Dark),
+            Value::U32(2) => Some(ColorScheme::Light),
+            Value::U32(_) => Some(ColorScheme::Default),
+            _ => None,
+        }
+    }
+}
+
+/// Read the colour scheme preference from the desktop portal
+///
+/// Returns `None` if there is no session bus or portal, or it does not
+/// support the setting.
+pub fn read_portal_color_scheme() -> Option<ColorScheme> {
+    let connection = connection::Builder::session()
+        .and_then(|builder| builder.method_timeout(PORTAL_TIMEOUT).build())
+        .inspect_err(|e| debug!("Failed to connect to the session bus: {}", e))
+        .ok()?;
+
+    let settings = PortalSettingsProxy::new(&connection)
+        .inspect_err(|e| debug!("Failed to create portal settings proxy: {}", e))
+        .ok()?;
+
+    // Older portals only implement `Read`
+    let value = settings
+        .read_one(APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY)
+        .or_else(|_| settings.read(APPEARANCE_NAMESPACE, COLOR_SCHEME_KEY))
+        .inspect_err(|e| debug!("Failed to read the colour scheme from the portal: {}", e))
+        .ok()?;
+
+    let scheme = ColorScheme::from_value(&value);
+    if scheme.is_none() {
+        warn!("Ignoring colour scheme from the portal with unexpected type");
+    }
+
+    scheme
+}
+
+/// Returns the colour scheme themes are shown with
+///
+/// The configured colour scheme takes precedence over the portal. If neither
+/// is available, there is no preference.
+pub fn resolve_color_scheme(config: &AppearanceConfig) -> ColorScheme {
+    let scheme = config
+        .color_scheme
+        .or_else(read_portal_color_scheme)
+        .unwrap_or_default();
+
+    debug!("Using colour scheme {:?}", scheme);
+    scheme
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn portal_values_are_converted() {
+        assert_eq!(
+            ColorScheme::from_value(&Value::U32(0)),
+            Some(ColorScheme::Default)
+        );
+        assert_eq!(
+            ColorScheme::from_value(&Value::U32(1)),
+            Some(ColorScheme::Dark)
+        );
+        assert_eq!(
+            ColorScheme::from_value(&Value::U32(2)),
+            Some(ColorScheme::Light)
+        );
+        assert_eq!(
+            ColorScheme::from_value(&Value::U32(3)),
+            Some(ColorScheme::Default)
+        );
+    }
+
+    #[test]
+    fn wrapped_values_are_converted() {
+        let value = Value::Value(Box::new(Value::Value(Box::new(Value::U32(1)))));
+        assert_eq!(ColorScheme::from_value(&value), Some(ColorScheme::Dark));
+    }
+
+    #[test]
+    fn unexpected_types_are_rejected() {
+        assert_eq!(ColorScheme::from_value(&Value::I32(1)), None);
+        assert_eq!(ColorScheme::from_value(&Value::from("dark")), None);
+    }
+
+    #[test]
+    fn configured_scheme_is_used_without_the_portal() {
+        let config = AppearanceConfig {
+            color_scheme: Some(ColorScheme::Light),
+        };
+        assert_eq!(resolve_color_scheme(&config), ColorScheme::Light);
+    }
+}
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    authenticator.rs:
+        The `Authenticator` trait, implemented by authentication backends
+*/
+
+/// Backend used to verify a user's credentials
+///
+/// Implementations are run on the authentication thread, so they may block.
+pub trait Authenticator: Send {
+    /// Attempt to authenticate `user` with `password`
+    ///
+    /// Messages that should be shown to the user while authenticating can be
+    /// sent with `prompt`.
+    fn authenticate(
+        &mut self,
+        user: &str,
+        password: &str,
+        prompt: &mut dyn FnMut(&str),
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Returns a short name for this authenticator, for logging
+    fn name(&self) -> &'static str;
+}
@@ -6,50 +6,29 @@
         Contains the event loop for the `AuthenticatorState` struct
 */
 
-use crate::auth::state::AuthenticatorState;
-use crate::event::{Event, EventType};
-use crate::ffi::{ForeignBuffer, set_state};
+use crate::auth::state::{AuthenticatorState, send_prompt};
+use crate::event::Event;
+use crate::ffi::set_state;
 use crate::shared::State;
 
 use nix::poll::{PollFd, PollFlags, poll};
-use pam_rs::{Client, PamError};
-use std::{
-    ffi::{CStr, c_void},
-    os::{fd::AsFd, raw::c_char},
-    sync::atomic::Ordering,
-};
+use std::{os::fd::AsFd, sync::atomic::Ordering};
 use tracing::{debug, error, info, warn};
 
 impl AuthenticatorState {
     /// Read a single event from the authentictor event pipe
     ///
     /// This function may return an error if file descriptors are invalid or
-    /// the event is not the correct size.
-    fn read_event(&self) -> Result<Event, Box<dyn std::error::Error>> {
+    /// the message is truncated. Invalid messages are discarded, and `None`
+    /// is returned.
+    fn read_event(&self) -> Result<Option<Event>, Box<dyn std::error::Error>> {
         let auth_fd = self
             .auth_pipe
             .as_ref()
             .ok_or::<Box<dyn std::error::Error>>("Authenticator pipe not initialized".into())?
             .read_fd();
 
-        let mut event_buffer = [0u8; std::mem::size_of::<Event>()];
-        let bytes_read = nix::unistd::read(auth_fd, &mut event_buffer)?;
-
-        if bytes_read != std::mem::size_of::<Event>() {
-            return Err(format!(
-                "Failed to read authenticator event pipe, expected {} bytes, got {}.",
-                std::mem::size_of::<Event>(),
-                bytes_read
-            )
-            .into());
-        }
-
-        // https://stackoverflow.com/questions/42499049/how-to-transmute-a-u8-buffer-to-struct-in-rust
-        let (head, body, _tail) = unsafe { event_buffer.align_to::<Event>() };
-        assert!(head.is_empty(), "Event data was not aligned");
-        let event = body[0];
-
-        Ok(event)
+        Event::read_from(auth_fd)
     }
 
     /// Wait for an event to be recieved on the pipe
@@ -77,66 +56,52 @@ impl AuthenticatorState {
     }
 
     /// Attempt to authenticate the current user with the specified password
-    fn authenticate(&mut self, password: String) -> Result<(), PamError> {
-        let mut client = Client::with_password("system-auth")?;
-        client
-            .conversation_mut()
-            .set_credentials(self.user.clone(), password);
-        client.authenticate()
+    ///
+    /// Any prompts produced by the authenticator are forwarded to the renderers.
+    fn authenticate(&mut self, password: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let renderers = self
+            .renderers
+            .as_ref()
+            .ok_or::<Box<dyn std::error::Error>>("Renderer pipes not initialized".into())?;
+
+        self.authenticator
+            .authenticate(&self.user, password, &mut |message| {
+                if let Err(e) = send_prompt(renderers, message) {
+                    warn!("Failed to send prompt: {}", e);
+                }
+            })
     }
 
     /// Set the application state to unlocking, to allow the Wayland thread
     /// to release the screen lock, and clear up.
     fn unlock(&mut self) {
+        if !self.unlock_on_success {
+            return;
+        }
+
         set_state(self.app_state.get(), State::Unlocking);
     }
 
     /// Handle a received AuthSubmit event
     ///
-    /// This routine takes a received event and attempts to authenticate
-    /// the current user based on the event-provided password.
-    fn handle_auth_submit(&mut self, event: Event) {
+    /// This routine attempts to authenticate the current user based on the
+    /// event-provided password.
+    fn handle_auth_submit(&mut self, password: &str) {
         debug!("Received AuthSubmit event");
 
         let _ = self.send_state_update(super::state::AuthState::Pending);
 
-        let pfbu = event.param_1.raw() as *mut ForeignBuffer;
-        if pfbu.is_null() {
-            warn!("AuthSubmit contained NULL pointer");
-            let _ = self.send_state_update(crate::auth::state::AuthState::Failed);
-            return;
-        }
-
-        let fbu = unsafe { &*pfbu };
-
-        let c_pwd = unsafe { CStr::from_ptr(fbu.ptr as *const c_char) };
-        match c_pwd.to_str() {
-            Ok(password) => match self.authenticate(password.to_string()) {
-                Ok(()) => {
-                    info!("Authentication successful for '{}'", self.user);
-                    let _ = self.send_state_update(super::state::AuthState::Success);
-                    self.unlock();
-                }
-                Err(e) => {
-                    error!("Authentication error: {}", e);
-                    let _ = self.send_state_update(crate::auth::state::AuthState::Failed);
-                }
-            },
+        match self.authenticate(password) {
+            Ok(()) => {
+                info!("Authentication successful for '{}'", self.user);
+                let _ = self.send_state_update(super::state::AuthState::Success);
+                self.unlock();
+            }
             Err(e) => {
-                error!("Invalid password string received: {}", e);
+                error!("Authentication error: {}", e);
                 let _ = self.send_state_update(crate::auth::state::AuthState::Failed);
             }
         }
-
-        unsafe {
-            // Drop the object, freeing the internal buffer...
-            std::ptr::drop_in_place(pfbu);
-
-            // ...and then deallocate the object itself.
-            if let Some(d) = (*pfbu).dealloc {
-                d(pfbu as *mut c_void);
-            }
-        }
     }
 
     /// Run the authenticator event loop until `stop_flag` is set
@@ -152,10 +117,10 @@ impl AuthenticatorState {
 
             r?;
 
-            let event = self.read_event()?;
-
-            if event.event_type == EventType::AuthSubmit {
-                self.handle_auth_submit(event);
+            match self.read_event()? {
+                Some(Event::AuthSubmit { password }) => self.handle_auth_submit(&password),
+                Some(event) => warn!("Ignoring unexpected {:?} event", event),
+                None => {}
             }
         }
 
@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    mock.rs:
+        Mock `Authenticator` implementation for theme development and tests
+*/
+
+use crate::auth::Authenticator;
+use crate::config::MockAuthConfig;
+
+use std::time::Duration;
+use tracing::debug;
+
+/// Authenticator that never touches the system's user database
+///
+/// Depending on its configuration, this accepts any password, accepts a
+/// single password, or always fails. Scripted prompts are sent before each
+/// attempt, and the result can be delayed to exercise pending states.
+pub struct MockAuthenticator {
+    password: Option<String>,
+    fail: bool,
+    delay: Duration,
+    prompts: Vec<String>,
+}
+
+impl MockAuthenticator {
+    /// Create a new `MockAuthenticator` from a `MockAuthConfig`
+    pub fn new(config: &MockAuthConfig) -> Self {
+        Self {
+            password: config.password.clone(),
+            fail: config.fail,
+            delay: Duration::from_millis(config.delay_ms),
+            prompts: config.prompts.clone(),
+        }
+    }
+}
+
+impl Authenticator for MockAuthenticator {
+    fn authenticate(
+        &mut self,
+        user: &str,
+        password: &str,
+        prompt: &mut dyn FnMut(&str),
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        debug!("Mock authentication for '{}'", user);
+
+        for message in &self.prompts {
+            prompt(message);
+        }
+
+        if !self.delay.is_zero() {
+            std::thread::sleep(self.delay);
+        }
+
+        if self.fail {
+            return Err("Mock authenticator configured to fail".into());
+        }
+
+        match &self.password {
+            Some(expected) if expected != password => Err("Incorrect password".into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+}
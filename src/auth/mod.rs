@@ -1,4 +1,10 @@
+mod authenticator;
 mod event_loop;
+mod mock;
+mod pam;
 mod state;
 
+pub use authenticator::*;
+pub use mock::*;
+pub use pam::*;
 pub use state::*;
@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    pam.rs:
+        `Authenticator` implementation using PAM
+*/
+
+use crate::auth::Authenticator;
+
+use pam_rs::Client;
+
+/// PAM service used for authentication by default
+pub const DEFAULT_PAM_SERVICE: &str = "system-auth";
+
+/// Authenticates users with PAM
+pub struct PamAuthenticator {
+    service: &'static str,
+}
+
+impl PamAuthenticator {
+    /// Create a new `PamAuthenticator` using the specified PAM service
+    pub fn new(service: &'static str) -> Self {
+        Self { service }
+    }
+}
+
+impl Default for PamAuthenticator {
+    fn default() -> Self {
+        Self::new(DEFAULT_PAM_SERVICE)
+    }
+}
+
+impl Authenticator for PamAuthenticator {
+    fn authenticate(
+        &mut self,
+        user: &str,
+        password: &str,
+        _prompt: &mut dyn FnMut(&str),
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // `PamError` is converted to a string, keeping its message
+        let mut client = Client::with_password(self.service).map_err(|e| e.to_string())?;
+        client
+            .conversation_mut()
+            .set_credentials(user.to_string(), password.to_string());
+        client.authenticate().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "pam"
+    }
+}
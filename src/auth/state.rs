@@ -6,15 +6,12 @@
         Definitions of `AuthenticatorState`
 */
 
-use crate::event::{Event, EventParam, EventType};
+use crate::auth::Authenticator;
+use crate::event::{Event, EventBroadcast};
 use crate::ffi::set_auth_write_fd;
 use crate::shared::{ApplicationStatePtr, Pipe};
 
-use nix::unistd::dup;
-use std::{
-    os::fd::{AsRawFd, OwnedFd},
-    sync::atomic::AtomicBool,
-};
+use std::{os::fd::AsRawFd, sync::atomic::AtomicBool};
 use tracing::{error, info};
 use uzers::get_current_username;
 
@@ -43,12 +40,17 @@ impl TryFrom<u64> for AuthState {
 }
 
 /// Holds the state of the authenticator thread
+///
+/// Credentials are checked by `authenticator`. If `unlock_on_success` is not
+/// set, the session is never unlocked, this is used when previewing themes.
 pub struct AuthenticatorState {
     pub auth_pipe: Option<Pipe>,
     pub app_state: ApplicationStatePtr,
-    pub renderer_fd: Option<OwnedFd>,
+    pub renderers: Option<EventBroadcast>,
     pub stop_flag: &'static AtomicBool,
     pub user: String,
+    pub authenticator: Box<dyn Authenticator>,
+    pub unlock_on_success: bool,
 }
 
 impl AuthenticatorState {
@@ -58,16 +60,26 @@ impl AuthenticatorState {
     ///
     /// This function also obtains the username this process was run as for
     /// authentication.
-    pub fn new(app_state: ApplicationStatePtr, stop_flag: &'static AtomicBool) -> Option<Self> {
+    pub fn new(
+        app_state: ApplicationStatePtr,
+        stop_flag: &'static AtomicBool,
+        authenticator: Box<dyn Authenticator>,
+    ) -> Option<Self> {
         match get_current_username()?.into_string() {
             Ok(user) => {
-                info!("Running authenticator for: '{}'", user);
+                info!(
+                    "Running {} authenticator for: '{}'",
+                    authenticator.name(),
+                    user
+                );
                 Some(Self {
                     auth_pipe: None,
                     app_state: app_state,
-                    renderer_fd: None,
+                    renderers: None,
                     stop_flag,
                     user,
+                    authenticator,
+                    unlock_on_success: true,
                 })
             }
             Err(os_str) => {
@@ -77,7 +89,7 @@ impl AuthenticatorState {
         }
     }
 
-    /// Send an AuthStateUpdate event to the renderer to indicate the
+    /// Send an AuthStateUpdate event to the renderers to indicate the
     /// authentication state has changed.
     ///
     /// This function can return an error if it fails to write an event.
@@ -85,28 +97,39 @@ impl AuthenticatorState {
         &mut self,
         state: AuthState,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let event = Event::new(
-            EventType::AuthStateUpdate,
-            EventParam::from(state as u64),
-            EventParam::from(0u64),
-        );
-        event.write_to(self.renderer_fd.as_ref().unwrap())
+        let event = Event::AuthStateUpdate { state };
+        self.renderers.as_ref().unwrap().send(&event)
     }
+}
+
+/// Send an AuthPrompt event to the renderers, containing a message to display
+pub fn send_prompt(
+    renderers: &EventBroadcast,
+    message: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let event = Event::AuthPrompt {
+        message: message.to_string(),
+    };
+    renderers.send(&event)
+}
 
+impl AuthenticatorState {
     /// Initialize this `AuthenticatorState` object
     ///
-    /// This function requires the `OwnedFd` for the renderer's input pipe,
-    /// this is used to send authentication state events to the renderer.
-    ///
-    /// The file descriptor passed here will be internally duplicated.
-    pub fn initialize(&mut self, renderer_fd: &OwnedFd) -> Result<(), Box<dyn std::error::Error>> {
+    /// This function requires an `EventBroadcast` for the renderers' input
+    /// pipes, this is used to send authentication state events to the
+    /// renderer of every output.
+    pub fn initialize(
+        &mut self,
+        renderers: EventBroadcast,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         let auth_pipe = Pipe::new()?;
 
         set_auth_write_fd(self.app_state.get(), auth_pipe.write_fd().as_raw_fd());
 
         self.auth_pipe = Some(auth_pipe);
 
-        self.renderer_fd = Some(dup(renderer_fd)?);
+        self.renderers = Some(renderers);
 
         Ok(())
     }
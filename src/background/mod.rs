@@ -0,0 +1,3 @@
+mod screenshot;
+
+pub use screenshot::*;
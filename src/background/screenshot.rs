@@ -0,0 +1,335 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    screenshot.rs:
+        Captures screenshots of every output before locking, with
+        `ext_image_copy_capture_v1`
+*/
+
+use crate::buffer::bytes_per_pixel;
+use crate::shared::Screenshot;
+use crate::wayland::WaylandState;
+
+use nix::poll::{PollFd, PollFlags, PollTimeout, poll};
+use nix::sys::memfd::{MFdFlags, memfd_create};
+use std::fs::File;
+use std::os::fd::AsFd;
+use std::os::unix::fs::FileExt;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use wayland_client::{
+    Connection, Dispatch, EventQueue, QueueHandle, WEnum,
+    protocol::{
+        wl_buffer::WlBuffer,
+        wl_shm::{Format, WlShm},
+    },
+};
+use wayland_protocols::ext::{
+    image_capture_source::v1::client::ext_image_capture_source_v1::ExtImageCaptureSourceV1,
+    image_copy_capture::v1::client::{
+        ext_image_copy_capture_frame_v1::{self, ExtImageCopyCaptureFrameV1},
+        ext_image_copy_capture_manager_v1::Options,
+        ext_image_copy_capture_session_v1::{self, ExtImageCopyCaptureSessionV1},
+    },
+};
+
+/// How long to wait for every output to be captured
+///
+/// Compositors only capture an output when it is next repainted, so this is
+/// kept short to avoid delaying the lock.
+const SCREENSHOT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Formats screenshots can be captured in, in order of preference
+const SCREENSHOT_FORMATS: [Format; 2] = [Format::Xrgb8888, Format::Argb8888];
+
+/// Progress of a screenshot capture
+#[derive(Clone, Copy, PartialEq)]
+enum CaptureStatus {
+    /// Waiting for the buffer constraints of the session
+    Pending,
+    /// A frame has been requested
+    Capturing,
+    Ready,
+    Failed,
+}
+
+/// A screenshot being captured, identified by the registry name of its output
+pub struct ScreenshotCapture {
+    registry_name: u32,
+    name: String,
+    source: ExtImageCaptureSourceV1,
+    session: ExtImageCopyCaptureSessionV1,
+    frame: Option<ExtImageCopyCaptureFrameV1>,
+    buffer: Option<(WlBuffer, File)>,
+    width: u32,
+    height: u32,
+    formats: Vec<Format>,
+    format: Option<Format>,
+    status: CaptureStatus,
+}
+
+impl ScreenshotCapture {
+    /// Returns the stride of the screenshot in `format`
+    fn stride(&self, format: Format) -> Option<i32> {
+        Some(self.width as i32 * bytes_per_pixel(format)?)
+    }
+
+    /// Allocate a buffer matching the session's constraints, and request a
+    /// frame
+    fn capture(&mut self, shm: Option<&WlShm>, qh: &QueueHandle<WaylandState>) -> Option<()> {
+        let Some(format) = SCREENSHOT_FORMATS
+            .into_iter()
+            .find(|format| self.formats.contains(format))
+        else {
+            warn!("No supported screenshot format for output {}", self.name);
+            return None;
+        };
+
+        let shm = shm?;
+        let stride = self.stride(format)?;
+        let size = stride * self.height as i32;
+
+        let fd = memfd_create("tlockr_screenshot", MFdFlags::empty()).ok()?;
+        let file = File::from(fd);
+        file.set_len(size as u64).ok()?;
+
+        let pool = shm.create_pool(file.as_fd(), size, qh, ());
+        let buffer = pool.create_buffer(
+            0,
+            self.width as i32,
+            self.height as i32,
+            stride,
+            format,
+            qh,
+            (),
+        );
+        pool.destroy();
+
+        let frame = self.session.create_frame(qh, self.registry_name);
+        frame.attach_buffer(&buffer);
+        frame.damage_buffer(0, 0, self.width as i32, self.height as i32);
+        frame.capture();
+
+        self.frame = Some(frame);
+        self.buffer = Some((buffer, file));
+        self.format = Some(format);
+        self.status = CaptureStatus::Capturing;
+
+        Some(())
+    }
+
+    /// Read the captured pixels into a `Screenshot`
+    fn read(&self) -> Option<Screenshot> {
+        let (_, file) = self.buffer.as_ref()?;
+        let format = self.format?;
+        let stride = self.stride(format)?;
+
+        let mut data = vec![0u8; stride as usize * self.height as usize];
+        file.read_exact_at(&mut data, 0).ok()?;
+
+        Screenshot::new(
+            &self.name,
+            data,
+            self.width as i32,
+            self.height as i32,
+            stride,
+            format as u32,
+        )
+    }
+
+    /// Destroy the Wayland objects used for capturing
+    fn destroy(&mut self) {
+        if let Some(frame) = self.frame.take() {
+            frame.destroy();
+        }
+        if let Some((buffer, _)) = self.buffer.take() {
+            buffer.destroy();
+        }
+        self.session.destroy();
+        self.source.destroy();
+    }
+}
+
+impl WaylandState {
+    /// Returns the screenshot being captured for the output `registry_name`
+    fn screenshot_capture(&mut self, registry_name: u32) -> Option<&mut ScreenshotCapture> {
+        self.screenshots
+            .iter_mut()
+            .find(|capture| capture.registry_name == registry_name)
+    }
+
+    /// Returns whether any screenshot is still being captured
+    fn screenshots_pending(&self) -> bool {
+        self.screenshots.iter().any(|capture| {
+            matches!(
+                capture.status,
+                CaptureStatus::Pending | CaptureStatus::Capturing
+            )
+        })
+    }
+
+    /// Capture a screenshot of every output, before locking
+    ///
+    /// Outputs are identified by their name, or registry name if the
+    /// compositor does not send names. Outputs that cannot be captured in
+    /// time are skipped, so locking is never prevented.
+    pub fn capture_screenshots(
+        &mut self,
+        event_queue: &mut EventQueue<Self>,
+    ) -> Result<Vec<Screenshot>, Box<dyn std::error::Error>> {
+        let (Some(source_manager), Some(copy_manager)) = (
+            &self.image_capture_source_manager,
+            &self.image_copy_capture_manager,
+        ) else {
+            warn!(
+                "Screenshots unavailable, the compositor does not support ext_image_copy_capture_v1"
+            );
+            return Ok(Vec::new());
+        };
+
+        let qh = event_queue.handle();
+
+        let mut captures = Vec::new();
+        for info in self.outputs.iter().filter(|info| info.done) {
+            let source = source_manager.create_source(&info.output, &qh, ());
+            let session =
+                copy_manager.create_session(&source, Options::empty(), &qh, info.registry_name);
+
+            captures.push(ScreenshotCapture {
+                registry_name: info.registry_name,
+                name: info
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| info.registry_name.to_string()),
+                source,
+                session,
+                frame: None,
+                buffer: None,
+                width: 0,
+                height: 0,
+                formats: Vec::new(),
+                format: None,
+                status: CaptureStatus::Pending,
+            });
+        }
+        self.screenshots = captures;
+
+        let deadline = Instant::now() + SCREENSHOT_TIMEOUT;
+        loop {
+            event_queue.flush()?;
+            event_queue.dispatch_pending(self)?;
+
+            if !self.screenshots_pending() {
+                break;
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                warn!("Timed out capturing screenshots");
+                break;
+            }
+
+            if let Some(guard) = event_queue.prepare_read() {
+                let mut fds = [PollFd::new(guard.connection_fd(), PollFlags::POLLIN)];
+                let timeout =
+                    PollTimeout::try_from(remaining.as_millis().min(i32::MAX as u128) as i32)?;
+
+                if poll(&mut fds, timeout)? > 0 {
+                    guard.read()?;
+                }
+            }
+        }
+
+        let mut screenshots = Vec::new();
+        for mut capture in std::mem::take(&mut self.screenshots) {
+            if capture.status == CaptureStatus::Ready {
+                match capture.read() {
+                    Some(screenshot) => {
+                        debug!(
+                            "Captured output {}: {} x {} pixels",
+                            capture.name, capture.width, capture.height
+                        );
+                        screenshots.push(screenshot);
+                    }
+                    None => warn!("Failed to read screenshot of output {}", capture.name),
+                }
+            }
+
+            capture.destroy();
+        }
+
+        info!("Captured {} screenshots", screenshots.len());
+        Ok(screenshots)
+    }
+}
+
+impl Dispatch<ExtImageCopyCaptureSessionV1, u32> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _proxy: &ExtImageCopyCaptureSessionV1,
+        event: <ExtImageCopyCaptureSessionV1 as wayland_client::Proxy>::Event,
+        registry_name: &u32,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        let shm = state.buffer_manager.shm.clone();
+        let Some(capture) = state.screenshot_capture(*registry_name) else {
+            return;
+        };
+
+        match event {
+            ext_image_copy_capture_session_v1::Event::BufferSize { width, height } => {
+                capture.width = width;
+                capture.height = height;
+            }
+            ext_image_copy_capture_session_v1::Event::ShmFormat {
+                format: WEnum::Value(format),
+            } => {
+                capture.formats.push(format);
+            }
+            // Constraints can be sent again, but only one frame is captured
+            ext_image_copy_capture_session_v1::Event::Done
+                if capture.status == CaptureStatus::Pending =>
+            {
+                let captured = capture.capture(shm.as_ref(), qh);
+                if captured.is_none() {
+                    warn!("Failed to capture output {}", capture.name);
+                    capture.status = CaptureStatus::Failed;
+                }
+            }
+            ext_image_copy_capture_session_v1::Event::Stopped
+                if capture.status != CaptureStatus::Ready =>
+            {
+                capture.status = CaptureStatus::Failed;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ExtImageCopyCaptureFrameV1, u32> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _proxy: &ExtImageCopyCaptureFrameV1,
+        event: <ExtImageCopyCaptureFrameV1 as wayland_client::Proxy>::Event,
+        registry_name: &u32,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let Some(capture) = state.screenshot_capture(*registry_name) else {
+            return;
+        };
+
+        match event {
+            ext_image_copy_capture_frame_v1::Event::Ready => {
+                capture.status = CaptureStatus::Ready;
+            }
+            ext_image_copy_capture_frame_v1::Event::Failed { reason } => {
+                warn!("Failed to capture output {}: {:?}", capture.name, reason);
+                capture.status = CaptureStatus::Failed;
+            }
+            _ => {}
+        }
+    }
+}
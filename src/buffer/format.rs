@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    format.rs:
+        Collects pixel formats advertised by `wl_shm`, and selects the format
+        used for display buffers.
+*/
+
+use crate::buffer::BufferManager;
+use crate::wayland::WaylandState;
+
+use tracing::{debug, warn};
+use wayland_client::{
+    Connection, Dispatch, QueueHandle, WEnum,
+    protocol::wl_shm::{self, Format, WlShm},
+};
+
+/// Returns the number of bytes used to store a single pixel of `format`
+///
+/// Only formats that the renderer is able to produce are supported.
+pub fn bytes_per_pixel(format: Format) -> Option<i32> {
+    match format {
+        Format::Argb8888 | Format::Xrgb8888 | Format::Argb2101010 | Format::Xrgb2101010 => Some(4),
+        _ => None,
+    }
+}
+
+impl BufferManager {
+    /// Set the preferences used when selecting a buffer format
+    ///
+    /// `transparent` requests a format with an alpha channel, `deep_color`
+    /// requests a 10-bit per channel format if the compositor supports one.
+    pub fn set_format_preference(&mut self, transparent: bool, deep_color: bool) {
+        self.transparent = transparent;
+        self.deep_color = deep_color;
+    }
+
+    /// Record a pixel format advertised by the compositor
+    pub fn add_format(&mut self, format: Format) {
+        if !self.formats.contains(&format) {
+            self.formats.push(format);
+        }
+    }
+
+    /// Returns the formats to try, in order of preference
+    fn format_candidates(&self) -> Vec<Format> {
+        let mut candidates = Vec::new();
+
+        if self.transparent {
+            if self.deep_color {
+                candidates.push(Format::Argb2101010);
+            }
+            candidates.push(Format::Argb8888);
+        } else {
+            if self.deep_color {
+                candidates.push(Format::Xrgb2101010);
+            }
+            candidates.push(Format::Xrgb8888);
+            candidates.push(Format::Argb8888);
+        }
+
+        candidates
+    }
+
+    /// Select the best buffer format from those advertised by the compositor
+    ///
+    /// The selected format is stored, and used for all subsequently allocated
+    /// buffers. `Argb8888` and `Xrgb8888` are required by the `wl_shm`
+    /// protocol, so they are used as a fallback if no formats were received.
+    pub fn select_format(&mut self) -> Format {
+        let candidates = self.format_candidates();
+
+        let format = match candidates.iter().find(|f| self.formats.contains(f)) {
+            Some(format) => *format,
+            None => {
+                warn!("No preferred buffer format advertised, falling back to ARGB8888");
+                Format::Argb8888
+            }
+        };
+
+        debug!("Selected buffer format: {:?}", format);
+        self.format = Some(format);
+
+        format
+    }
+}
+
+impl Dispatch<WlShm, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _proxy: &WlShm,
+        event: <WlShm as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_shm::Event::Format { format } => match format {
+                WEnum::Value(format) => {
+                    state.buffer_manager.add_format(format);
+                }
+                WEnum::Unknown(value) => {
+                    debug!("Ignoring unknown shm format: 0x{:08x}", value);
+                }
+            },
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a `BufferManager` with `formats` advertised
+    fn manager(formats: &[Format], transparent: bool, deep_color: bool) -> BufferManager {
+        let mut manager = BufferManager::new();
+        manager.set_format_preference(transparent, deep_color);
+        for format in formats {
+            manager.add_format(*format);
+        }
+
+        manager
+    }
+
+    #[test]
+    fn formats_are_preferred_in_order() {
+        let advertised = [
+            Format::Argb8888,
+            Format::Xrgb8888,
+            Format::Argb2101010,
+            Format::Xrgb2101010,
+        ];
+
+        assert_eq!(
+            manager(&advertised, false, false).format_candidates(),
+            vec![Format::Xrgb8888, Format::Argb8888]
+        );
+        assert_eq!(
+            manager(&advertised, false, false).select_format(),
+            Format::Xrgb8888
+        );
+        assert_eq!(
+            manager(&advertised, false, true).format_candidates(),
+            vec![Format::Xrgb2101010, Format::Xrgb8888, Format::Argb8888]
+        );
+        assert_eq!(
+            manager(&advertised, true, true).format_candidates(),
+            vec![Format::Argb2101010, Format::Argb8888]
+        );
+    }
+
+    #[test]
+    fn transparency_selects_a_format_with_alpha() {
+        let advertised = [Format::Xrgb8888, Format::Argb8888];
+
+        let mut manager = manager(&advertised, true, false);
+        assert_eq!(manager.select_format(), Format::Argb8888);
+        assert_eq!(manager.format, Some(Format::Argb8888));
+    }
+
+    #[test]
+    fn deep_color_is_selected_when_advertised() {
+        let advertised = [Format::Argb8888, Format::Xrgb8888, Format::Xrgb2101010];
+        assert_eq!(
+            manager(&advertised, false, true).select_format(),
+            Format::Xrgb2101010
+        );
+
+        // Without a 10-bit format with alpha, transparency uses 8 bits
+        assert_eq!(
+            manager(&advertised, true, true).select_format(),
+            Format::Argb8888
+        );
+
+        let advertised = [Format::Argb8888, Format::Xrgb8888];
+        assert_eq!(
+            manager(&advertised, false, true).select_format(),
+            Format::Xrgb8888
+        );
+    }
+
+    #[test]
+    fn argb8888_is_used_when_nothing_matches() {
+        assert_eq!(manager(&[], false, true).select_format(), Format::Argb8888);
+        assert_eq!(
+            manager(&[Format::Rgb565], false, false).select_format(),
+            Format::Argb8888
+        );
+    }
+
+    #[test]
+    fn bytes_per_pixel_of_renderable_formats() {
+        for format in [
+            Format::Argb8888,
+            Format::Xrgb8888,
+            Format::Argb2101010,
+            Format::Xrgb2101010,
+        ] {
+            assert_eq!(bytes_per_pixel(format), Some(4));
+        }
+
+        assert_eq!(bytes_per_pixel(Format::Rgb565), None);
+    }
+}
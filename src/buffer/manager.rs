@@ -6,13 +6,15 @@
         Provides the `BufferManager` structure for alloocating and using pixel buffers.
 */
 
-use crate::event::Event;
+use crate::buffer::bytes_per_pixel;
 use crate::wayland::WaylandState;
 
-use std::os::raw::c_void;
 use tracing::error;
 use wayland_client::protocol::wl_buffer;
-use wayland_client::protocol::{wl_buffer::WlBuffer, wl_shm::WlShm};
+use wayland_client::protocol::{
+    wl_buffer::WlBuffer,
+    wl_shm::{Format, WlShm},
+};
 use wayland_client::{Connection, Dispatch, QueueHandle};
 
 /// Represents a buffer for storing pixel data
@@ -37,11 +39,18 @@ pub struct Buffer {
 /// The `width`, `height`, and `shm` members need to be set before this structure is
 /// ready to allocate buffers. This can be done via the `set_output_dimensions` and
 /// `set_shm` methods respectively.
+///
+/// The `formats` member holds the pixel formats advertised by `wl_shm`, the format
+/// used for buffers is chosen from these by `select_format`.
 pub struct BufferManager {
     pub buffers: Option<Vec<Buffer>>,
     pub shm: Option<WlShm>,
     pub width: Option<i32>,
     pub height: Option<i32>,
+    pub formats: Vec<Format>,
+    pub format: Option<Format>,
+    pub transparent: bool,
+    pub deep_color: bool,
 }
 
 impl BufferManager {
@@ -55,6 +64,28 @@ impl BufferManager {
             shm: None,
             width: None,
             height: None,
+            formats: Vec::new(),
+            format: None,
+            transparent: false,
+            deep_color: false,
+        }
+    }
+
+    /// Create a new `BufferManager` for the buffers of a single output
+    ///
+    /// The shm object, advertised formats and selected format are shared with
+    /// this object, but no buffers are. `set_output_dimensions` needs to be
+    /// used before the returned object is ready to allocate buffers.
+    pub fn for_output(&self) -> Self {
+        Self {
+            buffers: Some(Vec::new()),
+            shm: self.shm.clone(),
+            width: None,
+            height: None,
+            formats: self.formats.clone(),
+            format: self.format,
+            transparent: self.transparent,
+            deep_color: self.deep_color,
         }
     }
 
@@ -77,29 +108,36 @@ impl BufferManager {
         let height = self
             .height
             .ok_or::<Box<dyn std::error::Error>>("Invalid height".into())?;
-        let stride = width * 4;
+        let format = self
+            .format
+            .ok_or::<Box<dyn std::error::Error>>("Buffer format not selected".into())?;
+        let stride = width
+            * bytes_per_pixel(format)
+                .ok_or::<Box<dyn std::error::Error>>("Unsupported buffer format".into())?;
 
         Ok((width, height, stride, height * stride))
     }
 
-    /// Release a buffer by index
+    /// Release a buffer by its `WlBuffer`
     ///
-    /// This function sets the `in_use` member of the buffer specified by `index` to `false`.
+    /// This function sets the `in_use` member of the buffer specified by `buffer` to `false`.
     /// This function is intended to be used after receiving the `wl_buffer::Event::Release` event,
     /// signalling that the specified buffer has been released.
-    pub fn release_buffer(&mut self, index: usize) -> Result<(), Box<dyn std::error::Error>> {
-        let buffers = self
+    ///
+    /// Returns whether the buffer belongs to this `BufferManager`.
+    pub fn release_buffer(&mut self, buffer: &WlBuffer) -> bool {
+        let Some(buffer) = self
             .buffers
-            .as_mut()
-            .ok_or::<Box<dyn std::error::Error>>("Failed to obtain buffers array".into())?;
-
-        if index >= buffers.len() {
-            return Err("Buffer index out of range".into());
-        }
+            .iter_mut()
+            .flatten()
+            .find(|b| b.buffer == *buffer)
+        else {
+            return false;
+        };
 
-        buffers[index].in_use = false;
+        buffer.in_use = false;
 
-        Ok(())
+        true
     }
 
     /// Returns the next available buffer from the buffer store
@@ -107,16 +145,16 @@ impl BufferManager {
         self.buffers.as_ref()?.iter().find(|b| !b.in_use)
     }
 
-    /// Searches for a `Buffer` matching the data provided by a `RendererEvent`
-    pub fn find_buffer_from_event(
+    /// Searches for the `Buffer` with data at `address`, as sent in a renderer event
+    pub fn find_buffer_by_address(
         &mut self,
-        event: &Event,
+        address: usize,
     ) -> Result<&mut Buffer, Box<dyn std::error::Error>> {
         let buffers = self.buffers.as_mut().ok_or("Buffers unavailable")?;
 
         buffers
             .iter_mut()
-            .find(|b| b.data as *mut c_void == event.param_1.into())
+            .find(|b| b.data as usize == address)
             .ok_or("No matching buffer found".into())
     }
 }
@@ -124,17 +162,23 @@ impl BufferManager {
 impl Dispatch<WlBuffer, i32> for WaylandState {
     fn event(
         state: &mut Self,
-        _proxy: &WlBuffer,
+        proxy: &WlBuffer,
         event: <WlBuffer as wayland_client::Proxy>::Event,
-        data: &i32,
+        _data: &i32,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
     ) {
         match event {
             wl_buffer::Event::Release => {
-                // Release the buffer so it can be used again
-                if let Err(e) = state.buffer_manager.release_buffer(*data as usize) {
-                    error!("{}", e);
+                // Release the buffer so it can be used again, each output
+                // has its own buffers
+                let released = state
+                    .surfaces
+                    .iter_mut()
+                    .any(|surface| surface.buffer_manager.release_buffer(proxy));
+
+                if !released {
+                    error!("Released buffer does not belong to any output");
                 }
             }
             _ => {}
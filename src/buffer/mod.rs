@@ -1,4 +1,6 @@
+mod format;
 mod manager;
 mod pool;
 
+pub use format::*;
 pub use manager::*;
@@ -16,7 +16,6 @@ use std::os::{
     raw::c_void,
 };
 use tracing::debug;
-use wayland_client::protocol::wl_shm;
 use wayland_client::protocol::wl_shm_pool::WlShmPool;
 use wayland_client::{EventQueue, QueueHandle};
 
@@ -56,7 +55,7 @@ impl BufferManager {
 
     /// Allocate a new display buffer from the pool
     ///
-    /// This function allocates a new buffer in ARGB8888 format, and adds it to the internal buffer store.
+    /// This function allocates a new buffer in the selected format, and adds it to the internal buffer store.
     fn allocate_buffer(
         &mut self,
         pool: &WlShmPool,
@@ -64,21 +63,14 @@ impl BufferManager {
         data_ptr: *mut c_void,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let (width, height, stride, _) = self.calculate_buffer_dimensions()?;
+        let format = self.format.ok_or("Buffer format not selected")?;
 
         let index = self.buffers.as_ref().iter().count() as i32;
 
         let offset = index * stride * height;
         let ptr = unsafe { (data_ptr as *mut u8).offset(offset as isize) };
 
-        let buffer = pool.create_buffer(
-            offset,
-            width,
-            height,
-            stride,
-            wl_shm::Format::Argb8888,
-            &qh,
-            index,
-        );
+        let buffer = pool.create_buffer(offset, width, height, stride, format, &qh, index);
 
         if let Some(buffers) = &mut self.buffers {
             buffers.push(Buffer {
@@ -0,0 +1,317 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    capture.rs:
+        Drives the QML renderer offscreen, without a Wayland connection, and
+        captures the frames it produces.
+*/
+
+use crate::auth::AuthState;
+use crate::buffer::bytes_per_pixel;
+use crate::config::ColorScheme;
+use crate::event::Event;
+use crate::ffi::{
+    QmlRenderer, cleanup_renderer, initialize_renderer, set_background, set_buffer_format,
+    set_callbacks, set_color_scheme, set_renderer_write_fd, set_session, start_renderer,
+};
+use crate::shared::{
+    ApplicationState, Background, OutputDetails, OutputState, Pipe, SessionInfo, ThemeSandbox,
+    ThemeSettings,
+};
+
+use nix::poll::{PollFd, PollFlags, PollTimeout, poll};
+use std::{
+    ffi::{CString, c_void},
+    os::fd::{AsFd, AsRawFd},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+use wayland_client::protocol::wl_shm::Format;
+
+/// Maximum time to wait for the theme to draw its first frame
+const FIRST_FRAME_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum time to wait for the theme to respond to an event
+pub const EVENT_RESPONSE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Maximum time to wait for a frame in progress to finish
+const FRAME_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A frame captured from the renderer
+///
+/// `data` is in the same layout as a `wl_shm` buffer of `format`.
+pub struct CapturedFrame {
+    pub width: i32,
+    pub height: i32,
+    pub stride: i32,
+    pub format: Format,
+    pub data: Vec<u8>,
+}
+
+/// A theme to capture, and what it is shown with
+pub struct CaptureTheme {
+    pub settings: ThemeSettings,
+    pub sandbox: ThemeSandbox,
+    pub color_scheme: ColorScheme,
+    pub background: Background,
+}
+
+/// Memory the renderer draws into, in place of a `wl_shm` buffer
+///
+/// While `frozen` is set, the renderer is not given the buffer, so it can be
+/// read safely once every `outstanding` frame has completed.
+struct CaptureTarget {
+    data: *mut [u8],
+    frozen: AtomicBool,
+    outstanding: AtomicUsize,
+}
+
+/// Renders a theme offscreen, and captures its frames
+pub struct FrameCapture {
+    app_state: Box<ApplicationState>,
+    output_state: Box<OutputState>,
+    target: Box<CaptureTarget>,
+    renderer: *mut QmlRenderer,
+    renderer_read_pipe: Pipe,
+    renderer_write_pipe: Pipe,
+    width: i32,
+    height: i32,
+    stride: i32,
+    format: Format,
+    frames_rendered: usize,
+}
+
+impl FrameCapture {
+    /// Callback function for the renderer
+    ///
+    /// This function returns a pointer to the capture buffer, unless it is
+    /// currently being read.
+    unsafe extern "C" fn get_buffer_callback(user_data: *mut c_void) -> *mut c_void {
+        let target = unsafe { &*(user_data as *const CaptureTarget) };
+
+        if target.frozen.load(Ordering::Acquire) {
+            return std::ptr::null_mut();
+        }
+
+        target.outstanding.fetch_add(1, Ordering::AcqRel);
+        target.data as *mut u8 as *mut c_void
+    }
+
+    /// Create a new `FrameCapture` for the QML file at `qml_path`, showing
+    /// `theme`
+    ///
+    /// The renderer is not started until `start` is called.
+    pub fn new(
+        qml_path: CString,
+        theme: CaptureTheme,
+        width: i32,
+        height: i32,
+        format: Format,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let stride = width
+            * bytes_per_pixel(format)
+                .ok_or::<Box<dyn std::error::Error>>("Unsupported capture format".into())?;
+        let buffer = vec![0u8; (stride * height) as usize].into_boxed_slice();
+
+        // The buffer is freed when this object is dropped, after the renderer
+        let target = Box::new(CaptureTarget {
+            data: Box::into_raw(buffer),
+            frozen: AtomicBool::new(false),
+            outstanding: AtomicUsize::new(0),
+        });
+
+        let mut app_state = Box::new(ApplicationState::new());
+        let app_state_ptr = app_state.as_mut() as *mut ApplicationState;
+
+        set_buffer_format(app_state_ptr, format as u32);
+        set_session(app_state_ptr, SessionInfo::current());
+        set_color_scheme(app_state_ptr, theme.color_scheme);
+        set_background(app_state_ptr, theme.background);
+
+        let mut output_state = Box::new(OutputState::new(
+            qml_path,
+            OutputDetails::empty(),
+            theme.settings,
+            theme.sandbox,
+        ));
+        output_state.width = width;
+        output_state.height = height;
+
+        let renderer_read_pipe = Pipe::new()?;
+        let renderer_write_pipe = Pipe::new()?;
+
+        // Same arrangement as `WaylandState::initialize` and `ThemeSurface::new`
+        output_state.renderer_read_fd = renderer_write_pipe.read_fd().as_raw_fd();
+        set_renderer_write_fd(app_state_ptr, renderer_read_pipe.write_fd().as_raw_fd());
+
+        Ok(Self {
+            app_state,
+            output_state,
+            target,
+            renderer: std::ptr::null_mut(),
+            renderer_read_pipe,
+            renderer_write_pipe,
+            width,
+            height,
+            stride,
+            format,
+            frames_rendered: 0,
+        })
+    }
+
+    /// Start the renderer, and wait for the theme to draw its first frame
+    pub fn start(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let renderer = unsafe {
+            initialize_renderer(
+                self.output_state.as_mut() as *mut OutputState,
+                self.app_state.as_mut() as *mut ApplicationState,
+            )
+        };
+
+        if renderer.is_null() {
+            return Err("QML renderer initialization failed.".into());
+        }
+
+        self.renderer = renderer;
+
+        unsafe {
+            set_callbacks(
+                renderer,
+                Self::get_buffer_callback,
+                self.target.as_mut() as *mut CaptureTarget as *mut c_void,
+            );
+
+            if start_renderer(renderer) != 0 {
+                return Err("Failed to start QML renderer.".into());
+            }
+        }
+
+        let deadline = Instant::now() + FIRST_FRAME_TIMEOUT;
+        while self.frames_rendered == 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err("Theme did not render a frame, check the log for QML errors.".into());
+            }
+
+            self.process_events(deadline - now)?;
+        }
+
+        debug!("Theme rendered its first frame");
+
+        Ok(())
+    }
+
+    /// Wait up to `timeout` for renderer events, and process them
+    fn process_events(&mut self, timeout: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        let renderer_fd = self.renderer_read_pipe.read_fd();
+
+        let mut pfds = [PollFd::new(renderer_fd.as_fd(), PollFlags::POLLIN)];
+        let timeout = PollTimeout::try_from(timeout.as_millis().min(i32::MAX as u128) as i32)?;
+
+        if poll(&mut pfds, timeout)? <= 0 {
+            return Ok(());
+        }
+
+        match Event::read_from(renderer_fd)? {
+            Some(Event::Renderer { .. }) => {
+                self.target.outstanding.fetch_sub(1, Ordering::AcqRel);
+                self.frames_rendered += 1;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Wait up to `timeout` for the renderer to draw a new frame
+    ///
+    /// Returns whether a frame was drawn.
+    pub fn wait_for_frame(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let deadline = Instant::now() + timeout;
+        let frames_rendered = self.frames_rendered;
+
+        while self.frames_rendered == frames_rendered {
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(false);
+            }
+
+            self.process_events(deadline - now)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Keep the renderer running for `duration`
+    pub fn run_for(&mut self, duration: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        let deadline = Instant::now() + duration;
+
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+
+            self.process_events(deadline - now)?;
+        }
+    }
+
+    /// Send an authentication state update to the theme
+    pub fn send_auth_state(&mut self, state: AuthState) -> Result<(), Box<dyn std::error::Error>> {
+        let event = Event::AuthStateUpdate { state };
+        event.write_to(self.renderer_write_pipe.write_fd())
+    }
+
+    /// Copy the most recently rendered frame
+    pub fn capture(&mut self) -> Result<CapturedFrame, Box<dyn std::error::Error>> {
+        self.target.frozen.store(true, Ordering::Release);
+
+        // Wait for any frame that was already given the buffer to finish
+        let deadline = Instant::now() + FRAME_TIMEOUT;
+        while self.target.outstanding.load(Ordering::Acquire) > 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                self.target.frozen.store(false, Ordering::Release);
+                return Err("Timed out waiting for frame to finish.".into());
+            }
+
+            self.process_events(deadline - now)?;
+        }
+
+        let frame = CapturedFrame {
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            format: self.format,
+            data: unsafe { (*self.target.data).to_vec() },
+        };
+
+        self.target.frozen.store(false, Ordering::Release);
+
+        Ok(frame)
+    }
+}
+
+impl Drop for FrameCapture {
+    fn drop(&mut self) {
+        if !self.renderer.is_null() {
+            unsafe {
+                cleanup_renderer(self.renderer);
+            }
+            self.renderer = std::ptr::null_mut();
+        }
+
+        if self.target.outstanding.load(Ordering::Acquire) > 0 {
+            warn!("Renderer stopped with a frame in progress");
+        }
+
+        unsafe {
+            drop(Box::from_raw(self.target.data));
+        }
+    }
+}
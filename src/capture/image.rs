@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    image.rs:
+        Converts captured frames to PNG images
+*/
+
+use crate::capture::CapturedFrame;
+
+use std::{fs::File, io::BufWriter, path::Path};
+use wayland_client::protocol::wl_shm::Format;
+
+impl CapturedFrame {
+    /// Convert the frame into tightly packed, non-premultiplied RGBA rows
+    ///
+    /// `wl_shm` 8-bit formats are stored as little-endian `[B, G, R, A]`. For
+    /// `Xrgb8888`, the alpha channel is ignored and set to opaque.
+    pub fn to_rgba(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let opaque = match self.format {
+            Format::Argb8888 => false,
+            Format::Xrgb8888 => true,
+            _ => return Err(format!("Cannot convert {:?} frames to RGBA", self.format).into()),
+        };
+
+        let mut rgba = Vec::with_capacity((self.width * self.height * 4) as usize);
+
+        for row in self.data.chunks_exact(self.stride as usize) {
+            for pixel in row[..(self.width * 4) as usize].chunks_exact(4) {
+                let (b, g, r, a) = (pixel[0], pixel[1], pixel[2], pixel[3]);
+
+                if opaque {
+                    rgba.extend_from_slice(&[r, g, b, 0xff]);
+                } else if a == 0 {
+                    rgba.extend_from_slice(&[0, 0, 0, 0]);
+                } else {
+                    // The renderer produces premultiplied alpha, PNG does not use it
+                    let unpremultiply =
+                        |c: u8| ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8;
+                    rgba.extend_from_slice(&[
+                        unpremultiply(r),
+                        unpremultiply(g),
+                        unpremultiply(b),
+                        a,
+                    ]);
+                }
+            }
+        }
+
+        Ok(rgba)
+    }
+
+    /// Write the frame to `path` as a PNG image
+    pub fn write_png(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let rgba = self.to_rgba()?;
+
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create '{}': {}", path.display(), e))?;
+
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&rgba)?;
+        writer.finish()?;
+
+        Ok(())
+    }
+}
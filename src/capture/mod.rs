@@ -0,0 +1,4 @@
+mod capture;
+mod image;
+
+pub use capture::*;
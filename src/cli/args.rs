@@ -6,6 +6,10 @@
         Argument parsing with the `clap` crate
 */
 
+use crate::auth::AuthState;
+
+use std::path::PathBuf;
+
 use clap::{
     Arg, ArgAction, Command, ValueEnum,
     builder::styling::{AnsiColor, Effects, Styles},
@@ -26,6 +30,13 @@ pub enum ConfigScope {
     SYSTEM,
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum AuthStateArg {
+    PENDING,
+    FAILED,
+    SUCCESS,
+}
+
 impl LogLevel {
     pub fn to_level(self) -> tracing::Level {
         match self {
@@ -38,6 +49,36 @@ impl LogLevel {
     }
 }
 
+impl AuthStateArg {
+    pub fn to_auth_state(self) -> AuthState {
+        match self {
+            Self::PENDING => AuthState::Pending,
+            Self::FAILED => AuthState::Failed,
+            Self::SUCCESS => AuthState::Success,
+        }
+    }
+}
+
+/// Parse a size in the format `WxH`
+pub fn parse_size(s: &str) -> Result<(i32, i32), String> {
+    let (width, height) = s
+        .split_once('x')
+        .ok_or_else(|| format!("Invalid size '{s}', expected WxH"))?;
+
+    let width: i32 = width
+        .parse()
+        .map_err(|_| format!("Invalid width '{width}'"))?;
+    let height: i32 = height
+        .parse()
+        .map_err(|_| format!("Invalid height '{height}'"))?;
+
+    if width <= 0 || height <= 0 {
+        return Err(format!("Invalid size '{s}', dimensions must be positive"));
+    }
+
+    Ok((width, height))
+}
+
 /// Generate styles for use with clap's command parser
 fn styles() -> Styles {
     Styles::styled()
@@ -49,6 +90,31 @@ fn styles() -> Styles {
         .invalid(AnsiColor::BrightRed.on_default())
 }
 
+/// Arguments configuring the mock authenticator, shared by `run` and `preview`
+fn mock_auth_args() -> Vec<Arg> {
+    vec![
+        Arg::new("mock_password")
+            .help("Only accept this password with the mock authenticator")
+            .long("mock-password")
+            .value_name("PASSWORD"),
+        Arg::new("mock_fail")
+            .help("Make every mock authentication attempt fail")
+            .long("mock-fail")
+            .action(ArgAction::SetTrue),
+        Arg::new("mock_delay")
+            .help("Delay before the mock authenticator responds")
+            .long("mock-delay")
+            .value_name("MS")
+            .value_parser(clap::value_parser!(u64))
+            .default_value("0"),
+        Arg::new("mock_prompt")
+            .help("Message sent to the theme before each attempt, may be repeated")
+            .long("mock-prompt")
+            .value_name("TEXT")
+            .action(ArgAction::Append),
+    ]
+}
+
 /// Build the full CLI command tree
 pub fn build_cli() -> Command {
     Command::new("tlockr")
@@ -65,6 +131,21 @@ pub fn build_cli() -> Command {
                 .value_parser(clap::builder::EnumValueParser::<LogLevel>::new())
                 .default_value("info"),
         )
+        .arg(
+            Arg::new("log_file")
+                .help("File to append log messages to")
+                .long("log-file")
+                .global(true)
+                .value_name("FILE")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("journald")
+                .help("Send log messages to the systemd journal")
+                .long("journald")
+                .global(true)
+                .action(ArgAction::SetTrue),
+        )
         .subcommand(
             Command::new("run")
                 .about("Run the screen locker")
@@ -87,6 +168,150 @@ pub fn build_cli() -> Command {
                         .short('d')
                         .long("develop")
                         .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("mock_auth")
+                        .help("Authenticate with the mock authenticator instead of PAM")
+                        .long("mock-auth")
+                        .requires("develop")
+                        .action(ArgAction::SetTrue),
+                )
+                .args(mock_auth_args()),
+        )
+        .subcommand(
+            Command::new("preview")
+                .about("Preview a theme in a window, reloading it when files change")
+                .arg(
+                    Arg::new("theme")
+                        .help("Name or directory of theme to preview")
+                        .value_name("THEME")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("size")
+                        .help("Size of the preview window")
+                        .short('s')
+                        .long("size")
+                        .value_name("WxH")
+                        .value_parser(parse_size)
+                        .default_value("1280x720"),
+                )
+                .args(mock_auth_args()),
+        )
+        .subcommand(
+            Command::new("render")
+                .about("Render a theme offscreen, and save the result as PNG images")
+                .arg(
+                    Arg::new("theme")
+                        .help("Name or directory of theme to render")
+                        .value_name("THEME")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("size")
+                        .help("Size of the rendered images")
+                        .short('s')
+                        .long("size")
+                        .value_name("WxH")
+                        .value_parser(parse_size)
+                        .default_value("1920x1080"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .help("PNG file to write, numbered when capturing several frames")
+                        .short('o')
+                        .long("out")
+                        .value_name("FILE")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("after_ms")
+                        .help("Time to wait after the first frame before capturing")
+                        .long("after-ms")
+                        .value_name("MS")
+                        .value_parser(clap::value_parser!(u64))
+                        .default_value("0"),
+                )
+                .arg(
+                    Arg::new("frames")
+                        .help("Number of frames to capture")
+                        .short('n')
+                        .long("frames")
+                        .value_name("N")
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .default_value("1"),
+                )
+                .arg(
+                    Arg::new("interval_ms")
+                        .help("Time between captured frames")
+                        .long("interval-ms")
+                        .value_name("MS")
+                        .value_parser(clap::value_parser!(u64))
+                        .default_value("100"),
+                )
+                .arg(
+                    Arg::new("auth_state")
+                        .help("Authentication state to send to the theme before capturing")
+                        .long("auth-state")
+                        .value_name("STATE")
+                        .value_parser(clap::builder::EnumValueParser::<AuthStateArg>::new()),
+                ),
+        )
+        .subcommand(
+            Command::new("theme")
+                .about("Manage installed themes")
+                .subcommand_required(true)
+                .subcommand(Command::new("list").about("List installed themes"))
+                .subcommand(
+                    Command::new("install")
+                        .about("Install a theme from a directory or .tar.gz archive")
+                        .arg(
+                            Arg::new("source")
+                                .help("Theme directory or archive to install")
+                                .value_name("PATH")
+                                .value_parser(clap::value_parser!(PathBuf))
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("system")
+                                .help("Install the theme for all users")
+                                .long("system")
+                                .action(ArgAction::SetTrue),
+                        )
+                        .arg(
+                            Arg::new("force")
+                                .help("Replace the theme if it is already installed")
+                                .short('f')
+                                .long("force")
+                                .action(ArgAction::SetTrue),
+                        ),
+                )
+                .subcommand(
+                    Command::new("remove")
+                        .about("Remove an installed theme")
+                        .arg(
+                            Arg::new("name")
+                                .help("Name of the theme to remove")
+                                .value_name("THEME")
+                                .required(true),
+                        )
+                        .arg(
+                            Arg::new("system")
+                                .help("Remove the theme installed for all users")
+                                .long("system")
+                                .action(ArgAction::SetTrue),
+                        ),
+                )
+                .subcommand(
+                    Command::new("info")
+                        .about("Show the metadata and settings of a theme")
+                        .arg(
+                            Arg::new("theme")
+                                .help("Name or directory of the theme")
+                                .value_name("THEME")
+                                .required(true),
+                        ),
                 ),
         )
         .subcommand(
@@ -7,11 +7,33 @@
         various state objects.
 */
 
-use crate::cli::{LogLevel, build_cli, run_lock};
-use crate::config::{RunConfig, load_root_config};
+use crate::cli::{
+    AuthStateArg, LogLevel, build_cli, install_theme, list_themes, remove_theme, render_theme,
+    run_lock, theme_info,
+};
+use crate::config::{
+    CaptureConfig, LogConfig, Merge, MockAuthConfig, OutputRules, PreviewConfig, RunConfig,
+    load_root_config,
+};
+use crate::logging::init_logging;
 
+use clap::ArgMatches;
+use std::path::PathBuf;
 use tracing::{debug, error};
 
+/// Build the mock authenticator configuration from subcommand arguments
+fn mock_auth_config(args: &ArgMatches) -> MockAuthConfig {
+    MockAuthConfig {
+        password: args.get_one::<String>("mock_password").cloned(),
+        fail: args.get_flag("mock_fail"),
+        delay_ms: *args.get_one::<u64>("mock_delay").unwrap(),
+        prompts: args
+            .get_many::<String>("mock_prompt")
+            .map(|prompts| prompts.cloned().collect())
+            .unwrap_or_default(),
+    }
+}
+
 /// Run the command line parser for tlockr
 pub fn start() {
     let matches = build_cli().get_matches();
@@ -20,14 +42,17 @@ pub fn start() {
         .get_one::<LogLevel>("log_level")
         .unwrap_or(&LogLevel::INFO);
 
-    tracing_subscriber::fmt()
-        .with_timer(tracing_subscriber::fmt::time::uptime())
-        .with_max_level(log_level.to_level())
-        .init();
-
     // Load the root configuration, exiting if it is invalid
     let root_config = load_root_config();
 
+    // Command line log options take precedence over the configuration files
+    let log_config = root_config.log.unwrap_or_default().merge(LogConfig {
+        file: matches.get_one::<PathBuf>("log_file").cloned(),
+        journald: matches.get_flag("journald").then_some(true),
+    });
+
+    init_logging(log_level.to_level(), &log_config);
+
     let now = chrono::Local::now();
     debug!("tlockr started at {}", now.to_rfc3339());
 
@@ -38,15 +63,107 @@ pub fn start() {
             let develop = args.get_flag("develop");
 
             let stored_run_config = root_config.run.unwrap_or_default();
+            let render_config = root_config.render.unwrap_or_default();
+            let provider_config = root_config.providers.unwrap_or_default();
+            let media_config = root_config.media.unwrap_or_default();
+            let notification_config = root_config.notifications.unwrap_or_default();
+            let appearance_config = root_config.appearance.unwrap_or_default();
+            let background_config = root_config.background.unwrap_or_default();
+            let theme_overrides = root_config.themes.unwrap_or_default();
+            let output_rules = root_config.outputs.unwrap_or_default();
 
             let run_config = RunConfig {
                 theme: theme.or(stored_run_config.theme.as_ref()),
                 config,
                 develop,
+                render: &render_config,
+                providers: &provider_config,
+                media: &media_config,
+                notifications: &notification_config,
+                appearance: &appearance_config,
+                background: &background_config,
+                theme_overrides: &theme_overrides,
+                outputs: &output_rules,
+                preview: None,
+                mock_auth: args.get_flag("mock_auth").then(|| mock_auth_config(args)),
             };
 
             run_lock(&run_config)
         }
+        Some(("preview", args)) => {
+            let theme = args.get_one::<String>("theme");
+            let (width, height) = *args.get_one::<(i32, i32)>("size").unwrap();
+
+            let render_config = root_config.render.unwrap_or_default();
+            let provider_config = root_config.providers.unwrap_or_default();
+            let media_config = root_config.media.unwrap_or_default();
+            let notification_config = root_config.notifications.unwrap_or_default();
+            let appearance_config = root_config.appearance.unwrap_or_default();
+            let background_config = root_config.background.unwrap_or_default();
+            let theme_overrides = root_config.themes.unwrap_or_default();
+
+            let run_config = RunConfig {
+                theme,
+                config: None,
+                develop: true,
+                render: &render_config,
+                providers: &provider_config,
+                media: &media_config,
+                notifications: &notification_config,
+                appearance: &appearance_config,
+                background: &background_config,
+                theme_overrides: &theme_overrides,
+                // The previewed theme is never replaced by the theme of an output
+                outputs: &OutputRules::new(),
+                preview: Some(PreviewConfig { width, height }),
+                // Previews never authenticate against the real system
+                mock_auth: Some(mock_auth_config(args)),
+            };
+
+            run_lock(&run_config)
+        }
+        Some(("render", args)) => {
+            let theme = args.get_one::<String>("theme").unwrap();
+            let (width, height) = *args.get_one::<(i32, i32)>("size").unwrap();
+
+            let render_config = root_config.render.unwrap_or_default();
+            let appearance_config = root_config.appearance.unwrap_or_default();
+            let background_config = root_config.background.unwrap_or_default();
+            let theme_overrides = root_config.themes.unwrap_or_default();
+
+            let capture_config = CaptureConfig {
+                theme,
+                width,
+                height,
+                out: args.get_one::<PathBuf>("out").unwrap().clone(),
+                after_ms: *args.get_one::<u64>("after_ms").unwrap(),
+                frames: *args.get_one::<u32>("frames").unwrap(),
+                interval_ms: *args.get_one::<u64>("interval_ms").unwrap(),
+                auth_state: args
+                    .get_one::<AuthStateArg>("auth_state")
+                    .map(|state| state.to_auth_state()),
+                render: &render_config,
+                appearance: &appearance_config,
+                background: &background_config,
+                theme_overrides: &theme_overrides,
+            };
+
+            render_theme(&capture_config)
+        }
+        Some(("theme", args)) => match args.subcommand() {
+            Some(("list", _)) => list_themes(),
+            Some(("install", args)) => install_theme(
+                args.get_one::<PathBuf>("source").unwrap(),
+                args.get_flag("system"),
+                args.get_flag("force"),
+            ),
+            Some(("remove", args)) => remove_theme(
+                args.get_one::<String>("name").unwrap(),
+                args.get_flag("system"),
+            ),
+            Some(("info", args)) => theme_info(args.get_one::<String>("theme").unwrap()),
+            _ => unreachable!(),
+        },
         None => {
             build_cli().print_help().unwrap();
             Ok(())
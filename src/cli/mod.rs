@@ -1,7 +1,11 @@
 mod args;
 mod cli;
+mod render;
 mod run;
+mod theme;
 
 pub use args::*;
 pub use cli::*;
+pub use render::*;
 pub use run::*;
+pub use theme::*;
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    render.rs:
+        Renders a theme offscreen to PNG images, for theme regression testing
+*/
+
+use crate::capture::{CaptureTheme, EVENT_RESPONSE_TIMEOUT, FrameCapture};
+use crate::cli::load_theme;
+use crate::config::CaptureConfig;
+use crate::shared::Background;
+
+use std::os::unix::ffi::OsStrExt;
+use std::{
+    ffi::CString,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tracing::{debug, info};
+use wayland_client::protocol::wl_shm::Format;
+
+/// Returns the path to write frame `index` to
+///
+/// When capturing several frames, the frame number is added to the file
+/// name, so `frame.png` becomes `frame_0000.png`, `frame_0001.png`, etc.
+fn frame_path(out: &Path, index: u32, frames: u32) -> PathBuf {
+    if frames <= 1 {
+        return out.to_path_buf();
+    }
+
+    let stem = out.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match out.extension() {
+        Some(extension) => format!("{}_{:04}.{}", stem, index, extension.to_string_lossy()),
+        None => format!("{}_{:04}", stem, index),
+    };
+
+    out.with_file_name(name)
+}
+
+/// Render a theme offscreen, and write the captured frames as PNG images
+///
+/// This does not connect to a Wayland compositor. The theme is loaded in
+/// develop mode, so it can be loaded from any directory.
+pub fn render_theme(config: &CaptureConfig) -> Result<(), Box<dyn std::error::Error>> {
+    // The output path is relative to where tlockr was run, not the theme
+    let out = std::path::absolute(&config.out)?;
+
+    // Relative wallpaper paths are resolved before changing into the theme
+    let wallpaper = config
+        .background
+        .wallpaper
+        .as_ref()
+        .map(std::path::absolute)
+        .transpose()?;
+
+    // The system preference is not read, so renders are reproducible
+    let color_scheme = config.appearance.color_scheme.unwrap_or_default();
+    let theme = load_theme(config.theme, true, color_scheme, config.theme_overrides)?;
+    nix::unistd::chdir(&theme.dir)?; // Change into the theme's directory
+    let qml_path_cstring = CString::new(theme.qml_path.as_os_str().as_bytes())?;
+
+    // Render without a display, unless a platform was explicitly requested
+    if std::env::var_os("QT_QPA_PLATFORM").is_none() {
+        unsafe { std::env::set_var("QT_QPA_PLATFORM", "offscreen") };
+    }
+
+    let format = if config.render.transparent.unwrap_or(false) {
+        Format::Argb8888
+    } else {
+        Format::Xrgb8888
+    };
+
+    let capture_theme = CaptureTheme {
+        settings: theme.settings,
+        sandbox: theme.sandbox,
+        color_scheme,
+        background: Background::new(Vec::new(), wallpaper.as_deref()),
+    };
+
+    let mut capture = FrameCapture::new(
+        qml_path_cstring,
+        capture_theme,
+        config.width,
+        config.height,
+        format,
+    )?;
+    capture.start()?;
+
+    if let Some(auth_state) = config.auth_state {
+        debug!("Sending authentication state: {:?}", auth_state);
+        capture.send_auth_state(auth_state)?;
+
+        // Give the theme a chance to draw the new state
+        if !capture.wait_for_frame(EVENT_RESPONSE_TIMEOUT)? {
+            debug!("Theme did not redraw after authentication state change");
+        }
+    }
+
+    capture.run_for(Duration::from_millis(config.after_ms))?;
+
+    let mut frames = Vec::new();
+    for index in 0..config.frames {
+        if index > 0 {
+            capture.run_for(Duration::from_millis(config.interval_ms))?;
+        }
+        frames.push(capture.capture()?);
+    }
+
+    // Stop the renderer before writing, so it does not use any more time
+    drop(capture);
+
+    for (index, frame) in frames.iter().enumerate() {
+        let path = frame_path(&out, index as u32, config.frames);
+        frame.write_png(&path)?;
+        info!("Wrote frame to '{}'", path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_frames_are_written_to_the_output_path() {
+        let out = Path::new("/tmp/frame.png");
+        assert_eq!(frame_path(out, 0, 1), out);
+    }
+
+    #[test]
+    fn frame_numbers_are_added_to_the_file_name() {
+        let out = Path::new("/tmp/frame.png");
+        assert_eq!(frame_path(out, 0, 3), Path::new("/tmp/frame_0000.png"));
+        assert_eq!(frame_path(out, 2, 3), Path::new("/tmp/frame_0002.png"));
+        assert_eq!(
+            frame_path(Path::new("frames/frame"), 12, 20),
+            Path::new("frames/frame_0012")
+        );
+    }
+}
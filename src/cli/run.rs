@@ -6,9 +6,19 @@
         Functions and configuration for running the screen locker
 */
 
-use crate::auth::AuthenticatorState;
-use crate::config::{RunConfig, ThemeRoot, resolve_theme};
-use crate::shared::{ApplicationState, ApplicationStatePtr};
+use crate::appearance::resolve_color_scheme;
+use crate::auth::{Authenticator, AuthenticatorState, MockAuthenticator, PamAuthenticator};
+use crate::config::{
+    ColorScheme, RunConfig, ThemeChain, ThemeOverrides, resolve_settings, resolve_theme,
+    split_theme_variant,
+};
+use crate::ffi::{set_background, set_color_scheme, set_preview, set_session};
+use crate::preview::PreviewState;
+use crate::provider::ProviderState;
+use crate::shared::{
+    ApplicationState, ApplicationStatePtr, Background, OutputState, SessionInfo, ThemeSandbox,
+    ThemeSettings,
+};
 use crate::wayland::WaylandState;
 
 use nix::libc;
@@ -36,49 +46,197 @@ pub fn suppress_stderr() {
     }
 }
 
+/// A theme located by `load_theme`
+pub struct LoadedTheme {
+    /// Directory of the theme the main QML file belongs to
+    pub dir: PathBuf,
+    /// Path of the theme's main QML file
+    pub qml_path: PathBuf,
+    /// Resolved values of the theme's settings
+    pub settings: ThemeSettings,
+    /// What the theme is allowed to access
+    pub sandbox: ThemeSandbox,
+}
+
+/// Locate a theme and the themes it extends, and resolve its settings
+///
+/// `theme_name` can select a variant, as in `basic:dark`. Otherwise, if the
+/// theme has `light` and `dark` variants, the one matching `color_scheme` is
+/// used. The main QML file must be inside the directory of the theme setting
+/// it. `overrides` are looked up by the name in the theme's `theme.toml`.
+///
+/// Relative paths in the theme are resolved from `dir`, so the caller changes
+/// into it before the theme is rendered.
+pub fn load_theme(
+    theme_name: &str,
+    develop: bool,
+    color_scheme: ColorScheme,
+    overrides: &ThemeOverrides,
+) -> Result<LoadedTheme, Box<dyn std::error::Error>> {
+    let (theme_name, variant) = split_theme_variant(theme_name, develop);
+
+    let theme_config_path = resolve_theme(&theme_name.to_string(), develop)?;
+    let chain = ThemeChain::load(&theme_config_path, develop)?;
+    chain.check_variant(variant)?;
+
+    let variant = variant.or_else(|| chain.color_scheme_variant(color_scheme));
+    if let Some(variant) = variant {
+        debug!("Using variant '{}'", variant);
+    }
+
+    let settings = resolve_settings(
+        &chain,
+        variant,
+        overrides.get(&chain.theme().root.theme.name),
+    )?;
+
+    let (main_layer, theme_qml_path) = chain.main(variant)?;
+
+    // Themes can load files from every theme they extend, not only the one
+    // with the main QML file
+    let sandbox = ThemeSandbox::new(
+        chain.layers.iter().map(|layer| layer.dir.as_path()),
+        main_layer.permissions(),
+    )?;
+
+    Ok(LoadedTheme {
+        dir: main_layer.dir.clone(),
+        qml_path: theme_qml_path,
+        settings: ThemeSettings::new(settings),
+        sandbox,
+    })
+}
+
 /// Run tlockr
 ///
 /// This function is called by main to allow for better handling of errors
+///
+/// If `config.preview` is set, the theme is displayed in a window instead of
+/// locking the session, authentication is simulated, and the theme is
+/// reloaded when its files change.
 pub fn run_lock(config: &RunConfig) -> Result<(), Box<dyn std::error::Error>> {
+    // Themes are always loaded in develop mode when previewing
+    let develop = config.develop || config.preview.is_some();
+
+    if config.mock_auth.is_some() && !develop {
+        return Err("The mock authenticator can only be used in develop or preview mode.".into());
+    }
+
+    // Each output has its own theme, which is loaded once the outputs are known
+    let mut app_state = ApplicationState::new();
+    set_preview(
+        &mut app_state as *mut ApplicationState,
+        config.preview.is_some(),
+    );
+    set_session(
+        &mut app_state as *mut ApplicationState,
+        SessionInfo::current(),
+    );
+    let app_state_ptr = ApplicationStatePtr::new(&mut app_state as *mut ApplicationState);
+
+    debug!("Initializing Wayland interfaces...");
+
+    let mut state = WaylandState::new(&mut app_state as *mut ApplicationState);
+    state.output_rules = config.outputs.clone();
+    state.buffer_manager.set_format_preference(
+        config.render.transparent.unwrap_or(false),
+        config.render.deep_color.unwrap_or(false),
+    );
+
+    if let Some(preview_config) = &config.preview {
+        state.preview = Some(PreviewState::new(preview_config));
+    }
+
+    let mut event_queue = state.initialize()?;
+
+    // The first roundtrip binds globals, the second receives output details
+    state.roundtrip(&mut event_queue)?;
+    state.roundtrip(&mut event_queue)?;
+
+    let mut outputs = state.select_outputs();
+    if outputs.is_empty() {
+        return Err("No outputs to show the lock screen on".into());
+    }
+
+    if config.preview.is_some() {
+        // The preview window only shows the primary output
+        outputs.truncate(1);
+    }
+
+    // Relative wallpaper paths are resolved before changing into the theme
+    let wallpaper = config
+        .background
+        .wallpaper
+        .as_ref()
+        .map(std::path::absolute)
+        .transpose()?;
+
+    let screenshots = if config.background.screenshot.unwrap_or(false) {
+        state.capture_screenshots(&mut event_queue)?
+    } else {
+        Vec::new()
+    };
+    set_background(
+        &mut app_state as *mut ApplicationState,
+        Background::new(screenshots, wallpaper.as_deref()),
+    );
+
+    let color_scheme = resolve_color_scheme(config.appearance);
+    set_color_scheme(&mut app_state as *mut ApplicationState, color_scheme);
+
     let default_theme_name = DEFAULT_THEME_NAME.to_string();
-    let theme_name = config.theme.unwrap_or(&default_theme_name);
+    for output in outputs {
+        let theme_name = output
+            .rule
+            .as_ref()
+            .and_then(|rule| rule.theme.as_ref())
+            .or(config.theme)
+            .unwrap_or(&default_theme_name);
 
-    debug!("Loading theme '{}'", theme_name);
+        debug!("Loading theme '{}'", theme_name);
 
-    let theme_config_path = resolve_theme(theme_name, config.develop)?;
-    let theme_root_meta = ThemeRoot::read_from(&theme_config_path)?;
+        let theme = load_theme(theme_name, develop, color_scheme, config.theme_overrides)?;
 
-    let theme_config_dir = theme_config_path
-        .parent()
-        .ok_or("Failed to get theme root directory.")?;
-    nix::unistd::chdir(theme_config_dir)?; // Change into the theme's directory
+        // Relative paths are resolved from the primary output's theme
+        if output.details.primary {
+            nix::unistd::chdir(&theme.dir)?;
 
-    let mut theme_qml_path = PathBuf::new();
-    theme_qml_path.push(theme_config_dir);
-    theme_qml_path.push(DEFAULT_QML_NAME);
+            if config.preview.is_some() {
+                state.watch_theme(&theme.dir)?;
+            }
+        }
 
-    let theme_qml = theme_root_meta.qml.unwrap_or_default();
-    if theme_qml.main.is_some() {
-        theme_qml_path = theme_qml.main.unwrap();
+        let output_state = OutputState::new(
+            CString::new(theme.qml_path.as_os_str().as_bytes())?,
+            output.details,
+            theme.settings,
+            theme.sandbox,
+        );
+        state.add_surface(output.registry_name, output_state)?;
     }
 
-    let qml_path_cstring = CString::new(theme_qml_path.as_os_str().as_bytes())?;
-    let qml_path_raw = qml_path_cstring.into_raw();
+    if config.providers.enabled.unwrap_or(true) {
+        state.providers = Some(ProviderState::new(config.providers));
+    }
 
-    let mut app_state = ApplicationState::new(qml_path_raw);
-    let app_state_ptr = ApplicationStatePtr::new(&mut app_state as *mut ApplicationState);
+    let authenticator: Box<dyn Authenticator> = match &config.mock_auth {
+        Some(mock_config) => Box::new(MockAuthenticator::new(mock_config)),
+        None => Box::new(PamAuthenticator::default()),
+    };
 
-    debug!("Initializing Wayland interfaces...");
+    let mut auth_state = AuthenticatorState::new(app_state_ptr, &AUTH_STOP_FLAG, authenticator)
+        .ok_or("Failed to create authenticator state")?;
+    auth_state.unlock_on_success = config.preview.is_none();
 
-    let mut state = WaylandState::new(&mut app_state as *mut ApplicationState);
-    let mut auth_state =
-        AuthenticatorState::new(app_state_ptr, &AUTH_STOP_FLAG)
-            .ok_or::<Box<dyn std::error::Error>>("Failed to create authenticator state".into())?;
+    auth_state.initialize(state.event_broadcast()?)?;
 
-    let mut event_queue = state.initialize()?;
+    if config.media.enabled.unwrap_or(true) {
+        state.start_media(config.media)?;
+    }
 
-    // TODO: Store pipe fd in ApplicationState
-    auth_state.initialize(state.renderer_write_pipe.as_ref().unwrap().write_fd())?;
+    if config.notifications.enabled.unwrap_or(false) {
+        state.start_notifications(config.notifications)?;
+    }
 
     let auth_thread = std::thread::spawn(move || {
         info!("Authentication thread started");
@@ -99,7 +257,7 @@ pub fn run_lock(config: &RunConfig) -> Result<(), Box<dyn std::error::Error>> {
 
     state.run_event_loop(&mut event_queue)?;
 
-    state.destroy_renderer();
+    state.destroy_renderers();
 
     AUTH_STOP_FLAG.store(true, Ordering::Relaxed);
 
@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    theme.rs:
+        Commands for listing, installing and removing themes
+*/
+
+use crate::cli::DEFAULT_QML_NAME;
+use crate::config::{
+    THEME_CONFIG_NAME, ThemeChain, ThemeRoot, is_valid_theme_name, resolve_settings, resolve_theme,
+    system_theme_dir, theme_search_dirs, user_theme_dir,
+};
+
+use nix::unistd::mkdtemp;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, info, warn};
+
+/// Returns the directory to install themes to, or remove them from
+fn install_dir(system: bool) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if system {
+        return Ok(system_theme_dir());
+    }
+
+    user_theme_dir().ok_or("Failed to find the user configuration directory".into())
+}
+
+/// Check that the theme in `dir` can be loaded, and return it with the
+/// themes it extends
+///
+/// The theme name must be usable as a directory name, themes it extends must
+/// be installed, and the main QML file of the theme and each variant must be
+/// inside the theme setting it.
+fn validate_theme(dir: &Path) -> Result<ThemeChain, Box<dyn std::error::Error>> {
+    let config_path = dir.join(THEME_CONFIG_NAME);
+    let theme = ThemeRoot::read_from(&config_path)
+        .map_err(|e| format!("Invalid {}: {}", THEME_CONFIG_NAME, e))?;
+
+    if !is_valid_theme_name(&theme.theme.name) {
+        return Err(format!("Invalid theme name '{}'", theme.theme.name).into());
+    }
+
+    let chain = ThemeChain::load(&config_path, false)?;
+
+    let variants = chain.variants();
+    for variant in std::iter::once(None).chain(variants.iter().map(|v| Some(v.as_str()))) {
+        chain.main(variant)?;
+        resolve_settings(&chain, variant, None)?;
+    }
+
+    Ok(chain)
+}
+
+/// Find the theme directory in an extracted archive
+///
+/// Archives either contain the theme files directly, or a single directory
+/// holding them.
+fn find_archive_theme(dir: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if dir.join(THEME_CONFIG_NAME).is_file() {
+        return Ok(dir.to_path_buf());
+    }
+
+    let entries = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+
+    match entries.as_slice() {
+        [single] if single.join(THEME_CONFIG_NAME).is_file() => Ok(single.clone()),
+        _ => Err(format!("Archive does not contain a {}", THEME_CONFIG_NAME).into()),
+    }
+}
+
+/// Copy the contents of directory `from` into a new directory `to`
+///
+/// Symbolic links are skipped, so an installed theme cannot refer to files
+/// outside its directory.
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir(to)?;
+
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = to.join(entry.file_name());
+
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_file() {
+            std::fs::copy(entry.path(), &target)?;
+        } else {
+            warn!("Skipping {}, not a regular file", entry.path().display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Install the theme in `source` into `themes_dir`
+///
+/// The theme is copied to a temporary directory first, so a failed copy
+/// never leaves a partial theme behind.
+fn install_from_dir(
+    source: &Path,
+    themes_dir: &Path,
+    force: bool,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let chain = validate_theme(source)?;
+    let name = chain.theme().root.theme.name.clone();
+
+    let target = themes_dir.join(&name);
+    if target.exists() && !force {
+        return Err(format!(
+            "Theme '{}' is already installed in {}, use --force to replace it",
+            name,
+            themes_dir.display()
+        )
+        .into());
+    }
+
+    std::fs::create_dir_all(themes_dir)?;
+
+    let staging = themes_dir.join(format!(".{}.install", name));
+    let _ = std::fs::remove_dir_all(&staging);
+
+    if let Err(e) = copy_dir(source, &staging) {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(format!("Failed to copy theme: {}", e).into());
+    }
+
+    if target.exists() {
+        std::fs::remove_dir_all(&target)?;
+    }
+    std::fs::rename(&staging, &target)?;
+
+    Ok(name)
+}
+
+/// Install a theme from a directory or a `.tar.gz` archive
+///
+/// Themes are installed for the current user, or for all users if `system`
+/// is set. Archives are extracted with the system `tar` command.
+pub fn install_theme(
+    source: &Path,
+    system: bool,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let themes_dir = install_dir(system)?;
+
+    let name = if source.is_dir() {
+        install_from_dir(source, &themes_dir, force)?
+    } else {
+        // A private directory with an unpredictable name, so other users
+        // cannot tamper with the files being installed
+        let extract_dir = mkdtemp(&std::env::temp_dir().join("tlockr-install-XXXXXX"))
+            .map_err(|e| format!("Failed to create temporary directory: {}", e))?;
+
+        debug!(
+            "Extracting {} to {}",
+            source.display(),
+            extract_dir.display()
+        );
+
+        let result = Command::new("tar")
+            .arg("--no-same-owner")
+            .arg("--no-same-permissions")
+            .arg("-xzf")
+            .arg(source)
+            .arg("-C")
+            .arg(&extract_dir)
+            .status()
+            .map_err(|e| -> Box<dyn std::error::Error> {
+                format!("Failed to run tar: {}", e).into()
+            })
+            .and_then(|status| {
+                if !status.success() {
+                    return Err(format!("Failed to extract {}", source.display()).into());
+                }
+
+                let theme_dir = find_archive_theme(&extract_dir)?;
+                install_from_dir(&theme_dir, &themes_dir, force)
+            });
+
+        let _ = std::fs::remove_dir_all(&extract_dir);
+        result?
+    };
+
+    info!("Installed theme '{}' to {}", name, themes_dir.display());
+    Ok(())
+}
+
+/// Remove an installed theme
+pub fn remove_theme(name: &str, system: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if !is_valid_theme_name(name) {
+        return Err(format!("Invalid theme name '{}'", name).into());
+    }
+
+    let themes_dir = install_dir(system)?;
+    let theme_dir = themes_dir.join(name);
+
+    if !theme_dir.join(THEME_CONFIG_NAME).is_file() {
+        return Err(format!(
+            "Theme '{}' is not installed in {}",
+            name,
+            themes_dir.display()
+        )
+        .into());
+    }
+
+    std::fs::remove_dir_all(&theme_dir)?;
+
+    info!("Removed theme '{}' from {}", name, themes_dir.display());
+    Ok(())
+}
+
+/// Print every installed theme, with its metadata and directory
+///
+/// Themes with the same name as one in a directory with higher precedence
+/// are marked as hidden, as they are never loaded.
+pub fn list_themes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut seen = Vec::new();
+
+    for themes_dir in theme_search_dirs() {
+        let Ok(entries) = std::fs::read_dir(&themes_dir) else {
+            continue;
+        };
+
+        let mut names = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| !name.starts_with('.'))
+            .collect::<Vec<_>>();
+        names.sort();
+
+        for name in names {
+            let theme_dir = themes_dir.join(&name);
+            let Ok(theme) = ThemeRoot::read_from(&theme_dir.join(THEME_CONFIG_NAME)) else {
+                continue;
+            };
+
+            let meta = theme.theme;
+            println!(
+                "{}{}",
+                name,
+                if seen.contains(&name) {
+                    " (hidden)"
+                } else {
+                    ""
+                }
+            );
+            println!("    Name:      {}", meta.name);
+            println!("    Version:   {}", meta.version.as_deref().unwrap_or("-"));
+            println!(
+                "    Author:    {}",
+                meta.author.map(|a| a.join(", ")).as_deref().unwrap_or("-")
+            );
+            println!("    License:   {}", meta.license.as_deref().unwrap_or("-"));
+            println!("    Directory: {}", theme_dir.display());
+
+            seen.push(name);
+        }
+    }
+
+    if seen.is_empty() {
+        println!("No themes installed");
+    }
+
+    Ok(())
+}
+
+/// Print the metadata and settings of a theme
+///
+/// `theme` is the name of an installed theme, or the directory of a theme.
+pub fn theme_info(theme: &String) -> Result<(), Box<dyn std::error::Error>> {
+    let config_path = resolve_theme(theme, true)?;
+    let theme_dir = config_path
+        .parent()
+        .ok_or("Failed to get theme root directory.")?;
+    let root = ThemeRoot::read_from(&config_path)?;
+
+    let main = match root.qml.as_ref().and_then(|qml| qml.main.as_ref()) {
+        Some(main) => main.display().to_string(),
+        None if root.extends.is_some() => "(inherited)".to_string(),
+        None => DEFAULT_QML_NAME.to_string(),
+    };
+
+    let meta = &root.theme;
+    println!("Name:      {}", meta.name);
+    println!("Version:   {}", meta.version.as_deref().unwrap_or("-"));
+    println!(
+        "Author:    {}",
+        meta.author
+            .as_ref()
+            .map(|a| a.join(", "))
+            .as_deref()
+            .unwrap_or("-")
+    );
+    println!("License:   {}", meta.license.as_deref().unwrap_or("-"));
+    println!("Directory: {}", theme_dir.display());
+    println!("Extends:   {}", root.extends.as_deref().unwrap_or("-"));
+    println!("Main QML:  {}", main);
+
+    if let Some(variants) = &root.variants {
+        println!(
+            "Variants:  {}",
+            variants.keys().cloned().collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    if let Some(settings) = &root.settings {
+        println!("Settings:");
+        for (name, setting) in settings {
+            println!(
+                "    {} ({}, default {}){}",
+                name,
+                format!("{:?}", setting.kind).to_lowercase(),
+                setting.default,
+                setting
+                    .description
+                    .as_ref()
+                    .map(|d| format!(": {}", d))
+                    .unwrap_or_default()
+            );
+        }
+    }
+
+    if let Err(e) = validate_theme(theme_dir) {
+        warn!("Theme is not valid: {}", e);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    chain.rs:
+        Loads a theme together with the themes it extends, and resolves its
+        main QML file and variants
+*/
+
+use crate::cli::DEFAULT_QML_NAME;
+use crate::config::{
+    ColorScheme, THEME_CONFIG_NAME, ThemePermissions, ThemeRoot, ThemeVariant, is_valid_theme_name,
+    resolve_theme, theme_path,
+};
+
+use std::path::{Path, PathBuf};
+
+/// Maximum number of themes in a chain of `extends`
+const MAX_THEME_DEPTH: usize = 8;
+
+/// A theme in a `ThemeChain`, with the directory it was loaded from
+pub struct ThemeLayer {
+    pub dir: PathBuf,
+    pub root: ThemeRoot,
+}
+
+impl ThemeLayer {
+    /// Returns the variant `name` declared by this theme, if any
+    pub fn variant(&self, name: &str) -> Option<&ThemeVariant> {
+        self.root.variants.as_ref()?.get(name)
+    }
+
+    /// Returns the permissions declared by this theme
+    pub fn permissions(&self) -> ThemePermissions {
+        self.root.permissions.unwrap_or_default()
+    }
+}
+
+/// A theme and the themes it extends, most derived first
+pub struct ThemeChain {
+    pub layers: Vec<ThemeLayer>,
+}
+
+/// Split a theme argument such as `basic:dark` into the theme and variant
+///
+/// In develop mode, an existing directory is never split, so theme
+/// directories can contain `:`.
+pub fn split_theme_variant(theme: &str, develop: bool) -> (&str, Option<&str>) {
+    if develop && Path::new(theme).is_dir() {
+        return (theme, None);
+    }
+
+    match theme.rsplit_once(':') {
+        Some((theme, variant)) if !variant.is_empty() => (theme, Some(variant)),
+        _ => (theme, None),
+    }
+}
+
+/// Locate the theme `name` extended by the theme in `dir`
+///
+/// In develop mode, `name` can also be a directory relative to `dir`.
+/// Otherwise it must be a valid theme name, so an installed theme cannot
+/// extend a directory outside the theme search directories.
+fn resolve_parent(
+    dir: &Path,
+    name: &String,
+    develop: bool,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if !develop && !is_valid_theme_name(name) {
+        return Err(format!("Invalid theme name '{}'", name).into());
+    }
+
+    if develop {
+        let path = dir.join(name).join(THEME_CONFIG_NAME);
+        if path.is_file() {
+            return Ok(path);
+        }
+    }
+
+    resolve_theme(name, false)
+}
+
+impl ThemeChain {
+    /// Load the theme at `config_path`, and every theme it extends
+    pub fn load(config_path: &Path, develop: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut layers: Vec<ThemeLayer> = Vec::new();
+        let mut path = config_path.to_path_buf();
+
+        loop {
+            let root = ThemeRoot::read_from(&path)?;
+            let dir = path
+                .parent()
+                .ok_or("Failed to get theme root directory.")?
+                .canonicalize()?;
+
+            if layers.iter().any(|layer| layer.dir == dir) {
+                return Err(format!("Theme '{}' extends itself", root.theme.name).into());
+            }
+
+            let extends = root.extends.clone();
+            layers.push(ThemeLayer { dir, root });
+
+            let Some(parent) = extends else {
+                break;
+            };
+
+            if layers.len() >= MAX_THEME_DEPTH {
+                return Err(format!(
+                    "Themes can extend at most {} other themes",
+                    MAX_THEME_DEPTH - 1
+                )
+                .into());
+            }
+
+            let dir = &layers[layers.len() - 1].dir;
+            path = resolve_parent(dir, &parent, develop)
+                .map_err(|e| format!("Failed to load parent theme '{}': {}", parent, e))?;
+        }
+
+        Ok(Self { layers })
+    }
+
+    /// Returns the most derived theme, which was requested by the user
+    pub fn theme(&self) -> &ThemeLayer {
+        &self.layers[0]
+    }
+
+    /// Returns the names of every variant declared in the chain, sorted
+    pub fn variants(&self) -> Vec<String> {
+        let mut variants = self
+            .layers
+            .iter()
+            .filter_map(|layer| layer.root.variants.as_ref())
+            .flat_map(|variants| variants.keys().cloned())
+            .collect::<Vec<_>>();
+        variants.sort();
+        variants.dedup();
+        variants
+    }
+
+    /// Returns the variant to use for `scheme`, if the chain declares both
+    /// `light` and `dark` variants
+    pub fn color_scheme_variant(&self, scheme: ColorScheme) -> Option<&'static str> {
+        let name = match scheme {
+            ColorScheme::Dark => "dark",
+            ColorScheme::Light => "light",
+            ColorScheme::Default => return None,
+        };
+
+        let variants = self.variants();
+        let declared = |name: &str| variants.iter().any(|variant| variant == name);
+
+        (declared("light") && declared("dark")).then_some(name)
+    }
+
+    /// Check that `variant` is declared by a theme in the chain
+    pub fn check_variant(&self, variant: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(variant) = variant else {
+            return Ok(());
+        };
+
+        let variants = self.variants();
+        if variants.iter().any(|name| name == variant) {
+            return Ok(());
+        }
+
+        Err(format!(
+            "Theme '{}' has no variant '{}', available variants: {}",
+            self.theme().root.theme.name,
+            variant,
+            if variants.is_empty() {
+                "none".to_string()
+            } else {
+                variants.join(", ")
+            }
+        )
+        .into())
+    }
+
+    /// Resolve the main QML file of `variant`, or of the theme if `None`
+    ///
+    /// The most derived theme setting `main`, in the variant or `[qml]`, is
+    /// used, falling back to `main.qml` in the base theme. Returns the theme
+    /// the file belongs to, and its path, which must be inside that theme.
+    pub fn main(
+        &self,
+        variant: Option<&str>,
+    ) -> Result<(&ThemeLayer, PathBuf), Box<dyn std::error::Error>> {
+        let (layer, main) = self
+            .layers
+            .iter()
+            .find_map(|layer| {
+                variant
+                    .and_then(|variant| layer.variant(variant))
+                    .and_then(|variant| variant.main.clone())
+                    .or_else(|| layer.root.qml.as_ref().and_then(|qml| qml.main.clone()))
+                    .map(|main| (layer, main))
+            })
+            .unwrap_or_else(|| {
+                let base = &self.layers[self.layers.len() - 1];
+                (base, PathBuf::from(DEFAULT_QML_NAME))
+            });
+
+        let path =
+            theme_path(&layer.dir, &main).map_err(|e| format!("Invalid main QML file: {}", e))?;
+
+        if !path.is_file() {
+            return Err(format!("Main QML file '{}' does not exist", main.display()).into());
+        }
+
+        Ok((layer, path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    /// Write a theme named `name` to `dir`, extending `extends`
+    fn write_theme(root: &TempDir, dir: &str, name: &str, extends: &str) -> PathBuf {
+        root.write(
+            &format!("{}/{}", dir, THEME_CONFIG_NAME),
+            &format!("extends = {:?}\n\n[theme]\nname = {:?}\n", extends, name),
+        )
+    }
+
+    #[test]
+    fn traversing_extends_is_rejected() {
+        let root = TempDir::new();
+        root.write(
+            &format!("parent/{}", THEME_CONFIG_NAME),
+            "[theme]\nname = \"parent\"\n",
+        );
+        let relative = write_theme(&root, "themes/relative", "relative", "../../parent");
+        let absolute = write_theme(
+            &root,
+            "themes/absolute",
+            "absolute",
+            &root.path().join("parent").to_string_lossy(),
+        );
+
+        for path in [&relative, &absolute] {
+            let error = ThemeChain::load(path, false).err().unwrap().to_string();
+            assert!(error.contains("Invalid theme name"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn relative_extends_is_allowed_in_develop_mode() {
+        let root = TempDir::new();
+        root.write(
+            &format!("parent/{}", THEME_CONFIG_NAME),
+            "[theme]\nname = \"parent\"\n",
+        );
+        let path = write_theme(&root, "themes/child", "child", "../../parent");
+
+        let chain = ThemeChain::load(&path, true).unwrap();
+        assert_eq!(chain.layers.len(), 2);
+        assert_eq!(chain.layers[1].root.theme.name, "parent");
+    }
+}
@@ -6,6 +6,7 @@
         Configuration structs for tlockr configuration files.
 */
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use crate::config::Merge;
@@ -38,10 +39,218 @@ impl Merge for GlobalConfig {
     }
 }
 
+#[derive(Serialize, Deserialize, Default)]
+pub struct RenderConfig {
+    pub transparent: Option<bool>,
+    pub deep_color: Option<bool>,
+}
+
+impl Merge for RenderConfig {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            transparent: other.transparent.or(self.transparent),
+            deep_color: other.deep_color.or(self.deep_color),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct LogConfig {
+    pub file: Option<PathBuf>,
+    pub journald: Option<bool>,
+}
+
+impl Merge for LogConfig {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            file: other.file.or(self.file),
+            journald: other.journald.or(self.journald),
+        }
+    }
+}
+
+/// Configuration for the status providers
+///
+/// The sysfs paths can be changed to read from fake directories.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct ProviderConfig {
+    pub enabled: Option<bool>,
+    pub interval_ms: Option<u64>,
+    pub power_supply_path: Option<PathBuf>,
+    pub net_path: Option<PathBuf>,
+}
+
+impl Merge for ProviderConfig {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            enabled: other.enabled.or(self.enabled),
+            interval_ms: other.interval_ms.or(self.interval_ms),
+            power_supply_path: other.power_supply_path.or(self.power_supply_path),
+            net_path: other.net_path.or(self.net_path),
+        }
+    }
+}
+
+/// Configuration for media player support
+///
+/// If `controls` is false, players are shown but cannot be controlled.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct MediaConfig {
+    pub enabled: Option<bool>,
+    pub controls: Option<bool>,
+}
+
+impl Merge for MediaConfig {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            enabled: other.enabled.or(self.enabled),
+            controls: other.controls.or(self.controls),
+        }
+    }
+}
+
+/// How much of each notification is shown while locked
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationPrivacy {
+    /// Only the number of notifications
+    #[default]
+    Count,
+    /// The name of the application sending each notification
+    App,
+    /// The application name, summary and body of each notification
+    Full,
+}
+
+/// Configuration for showing notifications received while locked
+///
+/// Notifications are disabled unless `enabled` is set.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct NotificationConfig {
+    pub enabled: Option<bool>,
+    pub privacy: Option<NotificationPrivacy>,
+}
+
+impl Merge for NotificationConfig {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            enabled: other.enabled.or(self.enabled),
+            privacy: other.privacy.or(self.privacy),
+        }
+    }
+}
+
+/// Configuration for the images themes can use as their background
+///
+/// If `screenshot` is set, every output is captured before locking.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct BackgroundConfig {
+    pub screenshot: Option<bool>,
+    pub wallpaper: Option<PathBuf>,
+}
+
+impl Merge for BackgroundConfig {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            screenshot: other.screenshot.or(self.screenshot),
+            wallpaper: other.wallpaper.or(self.wallpaper),
+        }
+    }
+}
+
+/// Preferred colour scheme, matching the values of the portal's
+/// `org.freedesktop.appearance` `color-scheme` setting
+///
+/// This enum is C-compatible.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u32)]
+pub enum ColorScheme {
+    /// No preference
+    #[default]
+    Default = 0,
+    Dark = 1,
+    Light = 2,
+}
+
+/// Configuration for the appearance of themes
+///
+/// If `color_scheme` is not set, the system preference is used.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct AppearanceConfig {
+    pub color_scheme: Option<ColorScheme>,
+}
+
+impl Merge for AppearanceConfig {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            color_scheme: other.color_scheme.or(self.color_scheme),
+        }
+    }
+}
+
+/// Configuration for outputs matching `name` and `description`
+///
+/// `name` must equal the output name, e.g. `DP-1`, and `description` must be
+/// part of the output description. A rule with neither matches every output.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct OutputConfig {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Theme shown on matching outputs, instead of the configured theme
+    pub theme: Option<String>,
+    /// Role passed to the theme, so it can adapt to the output
+    pub role: Option<String>,
+    /// Whether matching outputs are preferred as the primary output
+    pub primary: Option<bool>,
+}
+
+impl Merge for OutputConfig {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            name: other.name.or(self.name),
+            description: other.description.or(self.description),
+            theme: other.theme.or(self.theme),
+            role: other.role.or(self.role),
+            primary: other.primary.or(self.primary),
+        }
+    }
+}
+
+impl OutputConfig {
+    /// Returns whether an output with `name` and `description` matches
+    pub fn matches(&self, name: Option<&str>, description: Option<&str>) -> bool {
+        let name_matches = self
+            .name
+            .as_ref()
+            .is_none_or(|pattern| name == Some(pattern.as_str()));
+        let description_matches = self.description.as_ref().is_none_or(|pattern| {
+            description.is_some_and(|description| description.contains(pattern.as_str()))
+        });
+
+        name_matches && description_matches
+    }
+}
+
+/// Output rules, by a label chosen by the user
+pub type OutputRules = BTreeMap<String, OutputConfig>;
+
+/// User overrides of theme settings, by theme name
+pub type ThemeOverrides = BTreeMap<String, toml::Table>;
+
 #[derive(Serialize, Deserialize, Default)]
 pub struct RootConfig {
     pub run: Option<StoredRunConfig>,
     pub global: Option<GlobalConfig>,
+    pub render: Option<RenderConfig>,
+    pub log: Option<LogConfig>,
+    pub providers: Option<ProviderConfig>,
+    pub media: Option<MediaConfig>,
+    pub notifications: Option<NotificationConfig>,
+    pub appearance: Option<AppearanceConfig>,
+    pub background: Option<BackgroundConfig>,
+    pub themes: Option<ThemeOverrides>,
+    pub outputs: Option<OutputRules>,
 }
 
 impl Merge for RootConfig {
@@ -49,6 +258,15 @@ impl Merge for RootConfig {
         Self {
             run: self.run.merge(other.run),
             global: self.global.merge(other.global),
+            render: self.render.merge(other.render),
+            log: self.log.merge(other.log),
+            providers: self.providers.merge(other.providers),
+            media: self.media.merge(other.media),
+            notifications: self.notifications.merge(other.notifications),
+            appearance: self.appearance.merge(other.appearance),
+            background: self.background.merge(other.background),
+            themes: self.themes.merge(other.themes),
+            outputs: self.outputs.merge(other.outputs),
         }
     }
 }
@@ -61,3 +279,49 @@ impl RootConfig {
         Ok(c)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a rule matching `name` and `description`
+    fn rule(name: Option<&str>, description: Option<&str>) -> OutputConfig {
+        OutputConfig {
+            name: name.map(str::to_string),
+            description: description.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rule_without_patterns_matches_every_output() {
+        let rule = rule(None, None);
+        assert!(rule.matches(Some("DP-1"), Some("Dell U2720Q")));
+        assert!(rule.matches(None, None));
+    }
+
+    #[test]
+    fn name_must_match_exactly() {
+        let rule = rule(Some("DP-1"), None);
+        assert!(rule.matches(Some("DP-1"), None));
+        assert!(!rule.matches(Some("DP-10"), None));
+        assert!(!rule.matches(Some("dp-1"), None));
+        assert!(!rule.matches(None, Some("DP-1")));
+    }
+
+    #[test]
+    fn description_matches_part_of_the_description() {
+        let rule = rule(None, Some("U2720Q"));
+        assert!(rule.matches(None, Some("Dell Inc. DELL U2720Q 1234")));
+        assert!(!rule.matches(None, Some("Dell Inc. DELL U2719D 1234")));
+        assert!(!rule.matches(Some("U2720Q"), None));
+    }
+
+    #[test]
+    fn name_and_description_must_both_match() {
+        let rule = rule(Some("DP-1"), Some("Dell"));
+        assert!(rule.matches(Some("DP-1"), Some("Dell Inc.")));
+        assert!(!rule.matches(Some("DP-2"), Some("Dell Inc.")));
+        assert!(!rule.matches(Some("DP-1"), Some("BOE")));
+    }
+}
@@ -3,43 +3,87 @@
 
 use crate::config::{Merge, RootConfig};
 
+use dirs::{config_dir, data_dir};
+use std::env;
 use std::path::PathBuf;
-use dirs::config_dir;
 
-pub const SYSTEM_CONFIG_DIR: &str = "/etc";
+/// System configuration directory, set at build time with `TLOCKR_SYSCONFDIR`
+pub const SYSTEM_CONFIG_DIR: &str = match option_env!("TLOCKR_SYSCONFDIR") {
+    Some(dir) => dir,
+    None => "/etc",
+};
+
+/// System data directory, set at build time with `TLOCKR_DATADIR`
+pub const SYSTEM_DATA_DIR: &str = match option_env!("TLOCKR_DATADIR") {
+    Some(dir) => dir,
+    None => "/usr/share",
+};
+
 pub const CONFIG_SUB_DIR: &str = "tlockr";
 pub const ROOT_CONFIG_NAME: &str = "tlockr.toml";
 
-/// Load the tlockr root configuration from user and system config files
-pub fn load_root_config() -> RootConfig {
-    let mut root_config = RootConfig::default();
+/// Returns the absolute paths in the colon-separated list in environment
+/// variable `var`, or in `default` if it is unset or empty
+///
+/// Relative paths are ignored, as required by the XDG base directory
+/// specification.
+fn xdg_dirs(var: &str, default: &str) -> Vec<PathBuf> {
+    let value = env::var(var)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| default.to_string());
 
-    // Merge system config
-    let mut system_config_path = PathBuf::new();
-    system_config_path.push(SYSTEM_CONFIG_DIR);
-    system_config_path.push(CONFIG_SUB_DIR);
-    system_config_path.push(ROOT_CONFIG_NAME);
+    value
+        .split(':')
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .collect()
+}
 
-    // TODO: Errors here should be propagated, if they relate to TOML formatting
-    let system_config = RootConfig::read_from(system_config_path);
-    if let Ok(cfg) = system_config {
-        root_config = root_config.merge(cfg);
-    }
+/// Returns the tlockr directories holding `tlockr.toml` and themes, highest
+/// precedence first
+///
+/// The directories are, in order:
+/// 1. `$XDG_CONFIG_HOME/tlockr`
+/// 2. `$XDG_DATA_HOME/tlockr`
+/// 3. `tlockr` in each of `$XDG_CONFIG_DIRS`
+/// 4. `tlockr` in the system configuration directory
+/// 5. `tlockr` in each of `$XDG_DATA_DIRS`
+/// 6. `tlockr` in the system data directory
+pub fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
 
-    // Merge user config
-    let opt_user_config_path = config_dir();
-    if opt_user_config_path.is_none() {
-        return root_config;
+    dirs.extend(config_dir());
+    dirs.extend(data_dir());
+    dirs.extend(xdg_dirs("XDG_CONFIG_DIRS", "/etc/xdg"));
+    dirs.push(PathBuf::from(SYSTEM_CONFIG_DIR));
+    dirs.extend(xdg_dirs("XDG_DATA_DIRS", "/usr/local/share:/usr/share"));
+    dirs.push(PathBuf::from(SYSTEM_DATA_DIR));
+
+    let mut unique: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        let dir = dir.join(CONFIG_SUB_DIR);
+        if !unique.contains(&dir) {
+            unique.push(dir);
+        }
     }
 
-    let mut user_config_path = opt_user_config_path.unwrap();
-    user_config_path.push(CONFIG_SUB_DIR);
-    user_config_path.push(ROOT_CONFIG_NAME);
+    unique
+}
 
-    let user_config = RootConfig::read_from(user_config_path);
-    if let Ok(cfg) = user_config {
-        root_config = root_config.merge(cfg);
+/// Load the tlockr root configuration from user and system config files
+///
+/// Every `tlockr.toml` in the search directories is merged, so values from
+/// directories with higher precedence override those from lower ones.
+pub fn load_root_config() -> RootConfig {
+    let mut root_config = RootConfig::default();
+
+    for dir in search_dirs().into_iter().rev() {
+        // TODO: Errors here should be propagated, if they relate to TOML formatting
+        if let Ok(cfg) = RootConfig::read_from(dir.join(ROOT_CONFIG_NAME)) {
+            root_config = root_config.merge(cfg);
+        }
     }
-    
+
     root_config
 }
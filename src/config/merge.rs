@@ -6,6 +6,8 @@
         `Merge` trait for configuration structs.
 */
 
+use std::collections::BTreeMap;
+
 pub trait Merge {
     fn merge(self, other: Self) -> Self;
 }
@@ -20,3 +22,25 @@ impl<T: Merge> Merge for Option<T> {
         }
     }
 }
+
+/// Merge maps by key, so entries in `other` take precedence
+impl<T: Merge> Merge for BTreeMap<String, T> {
+    fn merge(mut self, other: Self) -> Self {
+        for (key, value) in other {
+            let value = match self.remove(&key) {
+                Some(existing) => existing.merge(value),
+                None => value,
+            };
+            self.insert(key, value);
+        }
+        self
+    }
+}
+
+/// Merge TOML tables by key, values in `other` replace those in `self`
+impl Merge for toml::Table {
+    fn merge(mut self, other: Self) -> Self {
+        self.extend(other);
+        self
+    }
+}
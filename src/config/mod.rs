@@ -1,11 +1,15 @@
+mod chain;
 mod global;
+mod loader;
 mod merge;
 mod run;
+mod settings;
 mod theme;
-mod loader;
 
+pub use chain::*;
 pub use global::*;
+pub use loader::*;
 pub use merge::*;
 pub use run::*;
+pub use settings::*;
 pub use theme::*;
-pub use loader::*;
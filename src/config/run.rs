@@ -1,9 +1,79 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 // Copyright (C) 2025, Nathan Gill
 
+/*
+    run.rs:
+        Configuration structs for running the screen locker
+*/
+
+use crate::auth::AuthState;
+use crate::config::{
+    AppearanceConfig, BackgroundConfig, MediaConfig, NotificationConfig, OutputRules,
+    ProviderConfig, RenderConfig, ThemeOverrides,
+};
+
+use std::path::PathBuf;
+
+/// Configuration for previewing a theme in a window
+#[derive(Clone, Copy)]
+pub struct PreviewConfig {
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Configuration for rendering a theme to image files
+///
+/// The first frame is captured `after_ms` milliseconds after the theme is
+/// first drawn. If `frames` is greater than one, further frames are captured
+/// every `interval_ms` milliseconds.
+///
+/// If `auth_state` is set, it is sent to the theme before capturing.
+pub struct CaptureConfig<'a> {
+    pub theme: &'a String,
+    pub width: i32,
+    pub height: i32,
+    pub out: PathBuf,
+    pub after_ms: u64,
+    pub frames: u32,
+    pub interval_ms: u64,
+    pub auth_state: Option<AuthState>,
+    pub render: &'a RenderConfig,
+    pub appearance: &'a AppearanceConfig,
+    pub background: &'a BackgroundConfig,
+    pub theme_overrides: &'a ThemeOverrides,
+}
+
+/// Configuration for the mock authenticator
+///
+/// If `password` is set, only that password is accepted, otherwise any
+/// password is accepted. `fail` causes every attempt to fail.
+#[derive(Clone, Default)]
+pub struct MockAuthConfig {
+    pub password: Option<String>,
+    pub fail: bool,
+    pub delay_ms: u64,
+    pub prompts: Vec<String>,
+}
+
 /// Configuration struct for running the screen locker
+///
+/// If `preview` is set, the theme is shown in a regular window instead of
+/// locking the session.
+///
+/// If `mock_auth` is set, the mock authenticator is used instead of PAM. This
+/// is only allowed in develop and preview modes.
 pub struct RunConfig<'a> {
     pub theme: Option<&'a String>,
     pub config: Option<&'a String>,
     pub develop: bool,
+    pub render: &'a RenderConfig,
+    pub providers: &'a ProviderConfig,
+    pub media: &'a MediaConfig,
+    pub notifications: &'a NotificationConfig,
+    pub appearance: &'a AppearanceConfig,
+    pub background: &'a BackgroundConfig,
+    pub theme_overrides: &'a ThemeOverrides,
+    pub outputs: &'a OutputRules,
+    pub preview: Option<PreviewConfig>,
+    pub mock_auth: Option<MockAuthConfig>,
 }
@@ -0,0 +1,376 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    settings.rs:
+        Resolves the values of theme settings, from their defaults and user
+        overrides
+*/
+
+use crate::config::{SettingKind, ThemeChain, ThemeSetting, theme_path};
+
+use dirs::home_dir;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+use tracing::warn;
+
+/// The value of a theme setting
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingValue {
+    String(String),
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Color(String),
+    Path(PathBuf),
+}
+
+/// Returns whether `color` is a colour name, or `#` followed by 3, 6 or 8
+/// hexadecimal digits
+fn is_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic()),
+    }
+}
+
+impl SettingValue {
+    /// Convert a TOML value to a setting of type `kind`
+    ///
+    /// Relative paths are resolved against `base`, and paths starting with
+    /// `~/` against the home directory. If `base` is `None`, relative paths
+    /// are rejected.
+    pub fn from_toml(
+        kind: SettingKind,
+        value: &Value,
+        base: Option<&Path>,
+    ) -> Result<Self, String> {
+        let setting = match (kind, value) {
+            (SettingKind::String, Value::String(s)) => SettingValue::String(s.clone()),
+            (SettingKind::Bool, Value::Boolean(b)) => SettingValue::Bool(*b),
+            (SettingKind::Int, Value::Integer(i)) => SettingValue::Int(*i),
+            (SettingKind::Float, Value::Float(f)) => SettingValue::Float(*f),
+            (SettingKind::Float, Value::Integer(i)) => SettingValue::Float(*i as f64),
+            (SettingKind::Color, Value::String(s)) if is_color(s) => SettingValue::Color(s.clone()),
+            (SettingKind::Color, Value::String(s)) => {
+                return Err(format!("'{s}' is not a valid colour"));
+            }
+            (SettingKind::Path, Value::String(s)) => {
+                let path = Path::new(s);
+                if let Ok(rest) = path.strip_prefix("~") {
+                    let home = home_dir().ok_or("Failed to find the home directory")?;
+                    SettingValue::Path(home.join(rest))
+                } else if path.is_absolute() {
+                    SettingValue::Path(path.to_path_buf())
+                } else if let Some(base) = base {
+                    SettingValue::Path(base.join(path))
+                } else {
+                    return Err(format!("'{s}' must be an absolute path"));
+                }
+            }
+            (kind, value) => {
+                return Err(format!("expected {:?}, found {}", kind, value.type_str()));
+            }
+        };
+
+        Ok(setting)
+    }
+}
+
+/// A setting declaration, with the default from the most derived theme or
+/// variant that sets it
+struct Declared<'a> {
+    setting: ThemeSetting,
+    /// Directory of the theme the default came from
+    base: &'a Path,
+    /// Whether that theme can refer to files outside its directory
+    files: bool,
+}
+
+/// Resolve the value of every setting declared by a theme and the themes it
+/// extends
+///
+/// Derived themes can redeclare settings, and `variant` can set new defaults.
+/// `overrides` are the user's values from the theme's section of
+/// `tlockr.toml`. Overrides of unknown settings, or with the wrong type, are
+/// ignored with a warning, so a mistake never prevents locking. Invalid
+/// defaults are an error, as are default paths outside the directory of the
+/// theme setting them, unless it has the `files` permission.
+pub fn resolve_settings(
+    chain: &ThemeChain,
+    variant: Option<&str>,
+    overrides: Option<&Table>,
+) -> Result<Vec<(String, SettingValue)>, Box<dyn std::error::Error>> {
+    let theme_name = &chain.theme().root.theme.name;
+    let mut declared = BTreeMap::new();
+
+    for layer in chain.layers.iter().rev() {
+        let files = layer.permissions().files.unwrap_or(false);
+
+        for (name, setting) in layer.root.settings.iter().flatten() {
+            let setting = setting.clone();
+            let base = layer.dir.as_path();
+            declared.insert(
+                name.clone(),
+                Declared {
+                    setting,
+                    base,
+                    files,
+                },
+            );
+        }
+
+        let defaults = variant
+            .and_then(|variant| layer.variant(variant))
+            .and_then(|variant| variant.settings.as_ref());
+
+        for (name, value) in defaults.into_iter().flatten() {
+            let Some(declared) = declared.get_mut(name) else {
+                return Err(format!(
+                    "Variant '{}' of theme '{}' sets undeclared setting '{}'",
+                    variant.unwrap_or_default(),
+                    layer.root.theme.name,
+                    name
+                )
+                .into());
+            };
+
+            declared.setting.default = value.clone();
+            declared.base = &layer.dir;
+            declared.files = files;
+        }
+    }
+
+    if let Some(overrides) = overrides {
+        for name in overrides
+            .keys()
+            .filter(|name| !declared.contains_key(*name))
+        {
+            warn!(
+                "Ignoring override of '{}', theme '{}' has no such setting",
+                name, theme_name
+            );
+        }
+    }
+
+    let mut settings = Vec::new();
+    for (name, declared) in declared {
+        let setting = declared.setting;
+        let default = SettingValue::from_toml(setting.kind, &setting.default, Some(declared.base))
+            .and_then(|value| match value {
+                SettingValue::Path(path) if !declared.files => {
+                    theme_path(declared.base, &path).map(SettingValue::Path)
+                }
+                value => Ok(value),
+            })
+            .map_err(|e| format!("Invalid default for theme setting '{name}': {e}"))?;
+
+        let value = match overrides.and_then(|overrides| overrides.get(&name)) {
+            Some(value) => SettingValue::from_toml(setting.kind, value, None).unwrap_or_else(|e| {
+                warn!("Ignoring override of theme setting '{}': {}", name, e);
+                default
+            }),
+            None => default,
+        };
+
+        settings.push((name, value));
+    }
+
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::THEME_CONFIG_NAME;
+    use crate::testing::TempDir;
+
+    /// Settings declared by the themes written by `write_theme`
+    const SETTINGS: &str = "[settings]\n\
+        background = { type = \"color\", default = \"#996633\" }\n\
+        enabled = { type = \"bool\", default = false }\n\
+        label = { type = \"string\", default = \"Default\" }\n\
+        scale = { type = \"float\", default = 1 }\n";
+
+    /// Write the theme `name` to `root`, with `contents` before its metadata
+    fn write_theme(root: &TempDir, name: &str, contents: &str) -> PathBuf {
+        root.write(
+            &format!("{}/{}", name, THEME_CONFIG_NAME),
+            &format!("{contents}\n[theme]\nname = \"{name}\"\nversion = \"0.1.0\"\n"),
+        )
+    }
+
+    /// Resolve the settings of the theme at `path`
+    fn resolve(
+        path: &Path,
+        variant: Option<&str>,
+        overrides: &str,
+    ) -> Result<Vec<(String, SettingValue)>, Box<dyn std::error::Error>> {
+        let chain = ThemeChain::load(path, true).unwrap();
+        let overrides: Table = toml::from_str(overrides).unwrap();
+        resolve_settings(&chain, variant, Some(&overrides))
+    }
+
+    /// Returns the value of the setting `name`
+    fn value<'a>(settings: &'a [(String, SettingValue)], name: &str) -> &'a SettingValue {
+        &settings
+            .iter()
+            .find(|(setting, _)| setting == name)
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn defaults_are_used_without_overrides() {
+        let root = TempDir::new();
+        let theme = write_theme(&root, "theme", SETTINGS);
+
+        let settings = resolve(&theme, None, "").unwrap();
+        assert_eq!(
+            settings,
+            vec![
+                (
+                    "background".to_string(),
+                    SettingValue::Color("#996633".to_string())
+                ),
+                ("enabled".to_string(), SettingValue::Bool(false)),
+                (
+                    "label".to_string(),
+                    SettingValue::String("Default".to_string())
+                ),
+                ("scale".to_string(), SettingValue::Float(1.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn overrides_replace_defaults() {
+        let root = TempDir::new();
+        let theme = write_theme(&root, "theme", SETTINGS);
+
+        let settings = resolve(&theme, None, "background = \"red\"\nscale = 1.5\n").unwrap();
+        assert_eq!(
+            value(&settings, "background"),
+            &SettingValue::Color("red".to_string())
+        );
+        assert_eq!(value(&settings, "scale"), &SettingValue::Float(1.5));
+    }
+
+    #[test]
+    fn invalid_and_unknown_overrides_are_ignored() {
+        let root = TempDir::new();
+        let theme = write_theme(&root, "theme", SETTINGS);
+
+        let settings = resolve(
+            &theme,
+            None,
+            "background = \"#12\"\nlabel = 5\nunknown = \"value\"\n",
+        )
+        .unwrap();
+        assert_eq!(settings.len(), 4);
+        assert_eq!(
+            value(&settings, "background"),
+            &SettingValue::Color("#996633".to_string())
+        );
+        assert_eq!(
+            value(&settings, "label"),
+            &SettingValue::String("Default".to_string())
+        );
+    }
+
+    #[test]
+    fn variants_set_defaults_of_inherited_settings() {
+        let root = TempDir::new();
+        write_theme(&root, "base", SETTINGS);
+        let theme = write_theme(
+            &root,
+            "derived",
+            "extends = \"../base\"\n\n\
+            [variants.dark]\n\
+            settings = { background = \"#333333\" }\n",
+        );
+
+        let settings = resolve(&theme, Some("dark"), "").unwrap();
+        assert_eq!(
+            value(&settings, "background"),
+            &SettingValue::Color("#333333".to_string())
+        );
+
+        let settings = resolve(&theme, None, "").unwrap();
+        assert_eq!(
+            value(&settings, "background"),
+            &SettingValue::Color("#996633".to_string())
+        );
+    }
+
+    #[test]
+    fn variant_setting_must_be_declared() {
+        let root = TempDir::new();
+        let theme = write_theme(
+            &root,
+            "theme",
+            &format!("{SETTINGS}\n[variants.dark]\nsettings = {{ unknown = true }}\n"),
+        );
+
+        assert!(resolve(&theme, Some("dark"), "").is_err());
+    }
+
+    #[test]
+    fn invalid_default_is_an_error() {
+        let root = TempDir::new();
+        let theme = write_theme(
+            &root,
+            "theme",
+            "[settings]\nenabled = { type = \"bool\", default = \"yes\" }\n",
+        );
+
+        assert!(resolve(&theme, None, "").is_err());
+    }
+
+    #[test]
+    fn default_paths_must_be_inside_the_theme() {
+        let root = TempDir::new();
+        let theme = write_theme(
+            &root,
+            "theme",
+            "[settings]\nimage = { type = \"path\", default = \"../outside.png\" }\n",
+        );
+        assert!(resolve(&theme, None, "").is_err());
+
+        let theme = write_theme(
+            &root,
+            "files",
+            "[permissions]\nfiles = true\n\n\
+            [settings]\nimage = { type = \"path\", default = \"../outside.png\" }\n",
+        );
+        let settings = resolve(&theme, None, "").unwrap();
+        let SettingValue::Path(path) = value(&settings, "image") else {
+            panic!("expected a path");
+        };
+        assert!(path.ends_with("files/../outside.png"));
+    }
+
+    #[test]
+    fn relative_paths_need_a_base() {
+        let value = Value::String("image.png".to_string());
+        assert!(SettingValue::from_toml(SettingKind::Path, &value, None).is_err());
+        assert_eq!(
+            SettingValue::from_toml(SettingKind::Path, &value, Some(Path::new("/theme"))),
+            Ok(SettingValue::Path(PathBuf::from("/theme/image.png")))
+        );
+    }
+
+    #[test]
+    fn colours_are_checked() {
+        assert!(is_color("red"));
+        assert!(is_color("#abc"));
+        assert!(is_color("#336699"));
+        assert!(is_color("#ff336699"));
+        assert!(!is_color(""));
+        assert!(!is_color("#"));
+        assert!(!is_color("#3366"));
+        assert!(!is_color("#33669g"));
+        assert!(!is_color("dark-red"));
+    }
+}
@@ -8,9 +8,10 @@
 
 use dirs::config_dir;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
 
-use crate::config::{CONFIG_SUB_DIR, SYSTEM_CONFIG_DIR};
+use crate::config::{CONFIG_SUB_DIR, SYSTEM_CONFIG_DIR, search_dirs};
 
 #[derive(Serialize, Deserialize)]
 pub struct ThemeMeta {
@@ -25,17 +26,136 @@ pub struct ThemeQml {
     pub main: Option<PathBuf>,
 }
 
+/// Type of a theme setting
+///
+/// This enum is C-compatible.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u32)]
+pub enum SettingKind {
+    String = 0,
+    Bool = 1,
+    Int = 2,
+    Float = 3,
+    /// A colour name, or `#RGB`, `#RRGGBB` or `#AARRGGBB`
+    Color = 4,
+    /// A file path, relative to the theme directory in `theme.toml`
+    Path = 5,
+}
+
+/// A setting declared by a theme, which users can override
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ThemeSetting {
+    #[serde(rename = "type")]
+    pub kind: SettingKind,
+    pub default: toml::Value,
+    pub description: Option<String>,
+}
+
+/// Access a theme needs outside its own directory
+///
+/// Without these, a theme can only load files from its directory and the
+/// files tlockr gives it, such as the user's avatar.
+#[derive(Serialize, Deserialize, Default, Clone, Copy)]
+pub struct ThemePermissions {
+    /// Load resources over the network
+    pub network: Option<bool>,
+    /// Load any local file
+    pub files: Option<bool>,
+}
+
+/// A named variant of a theme, such as `light` or `dark`
+///
+/// Paths are relative to the directory of the theme declaring the variant.
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct ThemeVariant {
+    /// Main QML file used instead of the theme's
+    pub main: Option<PathBuf>,
+    /// New defaults for settings declared by the theme
+    pub settings: Option<toml::Table>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ThemeRoot {
+    /// Name of a theme to inherit the QML, settings and variants of
+    pub extends: Option<String>,
     pub theme: ThemeMeta,
     pub qml: Option<ThemeQml>,
+    pub settings: Option<BTreeMap<String, ThemeSetting>>,
+    pub variants: Option<BTreeMap<String, ThemeVariant>>,
+    pub permissions: Option<ThemePermissions>,
 }
 
 pub const THEME_SUB_DIR: &str = "themes";
 pub const THEME_CONFIG_NAME: &str = "theme.toml";
 
+/// Returns the directory of themes installed for the current user
+pub fn user_theme_dir() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(CONFIG_SUB_DIR).join(THEME_SUB_DIR))
+}
+
+/// Returns the directory of themes installed for all users
+pub fn system_theme_dir() -> PathBuf {
+    PathBuf::from(SYSTEM_CONFIG_DIR)
+        .join(CONFIG_SUB_DIR)
+        .join(THEME_SUB_DIR)
+}
+
+/// Returns the directories searched for installed themes, highest precedence
+/// first
+///
+/// These are the `themes` directories in each of the search directories.
+pub fn theme_search_dirs() -> Vec<PathBuf> {
+    search_dirs()
+        .into_iter()
+        .map(|dir| dir.join(THEME_SUB_DIR))
+        .collect()
+}
+
+/// Resolve `path` relative to the theme directory `theme_dir`, rejecting it
+/// if it is outside the theme
+///
+/// Paths are rejected if they are absolute, contain `..`, or lead outside
+/// the theme through a symbolic link. The path does not need to exist.
+pub fn theme_path(theme_dir: &Path, path: &Path) -> Result<PathBuf, String> {
+    let escapes = || format!("'{}' is outside the theme directory", path.display());
+
+    let relative = path.strip_prefix(theme_dir).unwrap_or(path);
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(escapes());
+    }
+
+    let resolved = theme_dir.join(relative);
+    if let (Ok(root), Ok(canonical)) = (theme_dir.canonicalize(), resolved.canonicalize())
+        && !canonical.starts_with(root)
+    {
+        return Err(escapes());
+    }
+
+    Ok(resolved)
+}
+
+/// Returns whether `name` can be used as the directory name of a theme
+pub fn is_valid_theme_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
 /// Resolve a theme name to a theme configuration path
+///
+/// Outside develop mode, `theme` must be a valid theme name, so it cannot
+/// name a directory outside the theme search directories.
 pub fn resolve_theme(theme: &String, develop: bool) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if !develop && !is_valid_theme_name(theme) {
+        return Err(format!("Invalid theme name '{theme}'").into());
+    }
+
     if develop {
         // In develop mode, themes can be loaded from any directory
         let mut path = PathBuf::new();
@@ -47,29 +167,11 @@ pub fn resolve_theme(theme: &String, develop: bool) -> Result<PathBuf, Box<dyn s
         }
     }
 
-    if let Some(mut user_config_path) = config_dir() {
-        user_config_path.push(CONFIG_SUB_DIR);
-        user_config_path.push(THEME_SUB_DIR);
-        user_config_path.push(theme);
-        user_config_path.push(THEME_CONFIG_NAME);
-
-        if user_config_path.is_file() {
-            return Ok(user_config_path);
-        }
-    }
-
-    let mut system_config_path = PathBuf::new();
-    system_config_path.push(SYSTEM_CONFIG_DIR);
-    system_config_path.push(CONFIG_SUB_DIR);
-    system_config_path.push(THEME_SUB_DIR);
-    system_config_path.push(theme);
-    system_config_path.push(THEME_CONFIG_NAME);
-
-    if system_config_path.is_file() {
-        return Ok(system_config_path);
-    }
-
-    Err(format!("Failed to locate theme '{theme}'.").into())
+    theme_search_dirs()
+        .into_iter()
+        .map(|dir| dir.join(theme).join(THEME_CONFIG_NAME))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("Failed to locate theme '{theme}'.").into())
 }
 
 impl ThemeRoot {
@@ -80,3 +182,80 @@ impl ThemeRoot {
         Ok(c)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn paths_inside_the_theme_are_resolved() {
+        let theme = TempDir::new();
+        let dir = theme.path();
+
+        assert_eq!(
+            theme_path(dir, Path::new("main.qml")),
+            Ok(dir.join("main.qml"))
+        );
+        assert_eq!(
+            theme_path(dir, Path::new("./images/background.png")),
+            Ok(dir.join("./images/background.png"))
+        );
+        assert_eq!(
+            theme_path(dir, &dir.join("main.qml")),
+            Ok(dir.join("main.qml"))
+        );
+    }
+
+    #[test]
+    fn paths_outside_the_theme_are_rejected() {
+        let theme = TempDir::new();
+        let dir = theme.path();
+
+        assert!(theme_path(dir, Path::new("../main.qml")).is_err());
+        assert!(theme_path(dir, Path::new("images/../../main.qml")).is_err());
+        assert!(theme_path(dir, Path::new("/etc/passwd")).is_err());
+        assert!(theme_path(dir, &dir.join("../main.qml")).is_err());
+    }
+
+    #[test]
+    fn theme_names_are_validated() {
+        assert!(is_valid_theme_name("basic"));
+        assert!(is_valid_theme_name("my_theme-2.0"));
+        assert!(!is_valid_theme_name(""));
+        assert!(!is_valid_theme_name(".hidden"));
+        assert!(!is_valid_theme_name(".."));
+        assert!(!is_valid_theme_name("../basic"));
+        assert!(!is_valid_theme_name("themes/basic"));
+    }
+
+    #[test]
+    fn theme_paths_are_only_resolved_in_develop_mode() {
+        let root = TempDir::new();
+        root.write("theme/theme.toml", "");
+        let dir = root.path().join("theme").to_string_lossy().into_owned();
+
+        assert!(resolve_theme(&dir, false).is_err());
+        assert!(resolve_theme(&"../theme".to_string(), false).is_err());
+        assert_eq!(
+            resolve_theme(&dir, true).unwrap(),
+            root.path().join("theme").join(THEME_CONFIG_NAME)
+        );
+    }
+
+    #[test]
+    fn symbolic_links_out_of_the_theme_are_rejected() {
+        let root = TempDir::new();
+        let dir = root.create_dir("theme");
+        let outside = root.write("outside.qml", "");
+        root.write("theme/inside.qml", "");
+        std::os::unix::fs::symlink(&outside, dir.join("outside.qml")).unwrap();
+        std::os::unix::fs::symlink("inside.qml", dir.join("link.qml")).unwrap();
+
+        assert!(theme_path(&dir, Path::new("outside.qml")).is_err());
+        assert_eq!(
+            theme_path(&dir, Path::new("link.qml")),
+            Ok(dir.join("link.qml"))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    broadcast.rs:
+        `EventBroadcast` object for sending events to the renderer of every
+        output from other threads.
+*/
+
+use crate::event::Event;
+
+use nix::unistd::dup;
+use std::os::fd::OwnedFd;
+
+/// Sends events to the renderers of every output
+///
+/// The file descriptors of the renderer pipes are duplicated, so this object
+/// can be moved to another thread.
+pub struct EventBroadcast {
+    fds: Vec<OwnedFd>,
+}
+
+impl EventBroadcast {
+    /// Create a new `EventBroadcast` for the write ends of renderer pipes
+    pub fn new<'a>(
+        fds: impl IntoIterator<Item = &'a OwnedFd>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let fds = fds.into_iter().map(dup).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { fds })
+    }
+
+    /// Send `event` to every renderer
+    ///
+    /// The event is still sent to the other renderers if one fails, and the
+    /// last error is returned.
+    pub fn send(&self, event: &Event) -> Result<(), Box<dyn std::error::Error>> {
+        let mut result = Ok(());
+
+        for fd in &self.fds {
+            if let Err(e) = event.write_to(fd) {
+                result = Err(e);
+            }
+        }
+
+        result
+    }
+}
@@ -3,59 +3,1092 @@
 
 /*
     event.rs:
-        `Event` and `EventType` objects for event handling
+        `Event` messages, and their serialization
 */
 
-use crate::event::{EventParam, EventType};
+use crate::auth::AuthState;
+use crate::event::EventType;
+use crate::event::message::{
+    HEADER_SIZE, MAX_PAYLOAD_SIZE, MessageHeader, PayloadReader, PayloadWriter,
+};
 
-use std::os::fd::OwnedFd;
+use nix::errno::Errno;
+use std::os::fd::{OwnedFd, RawFd};
+use tracing::warn;
 
-/// Event structure containing event serial, type, and parameters
+/// An event sent between Rust and the renderer
 ///
-/// This structure is C-compatible, and is intended to be sent with a `Pipe`
-/// object.
-#[derive(Debug, Clone, Copy)]
-#[repr(C)]
-pub struct Event {
-    pub event_type: EventType,
-    pub param_1: EventParam,
-    pub param_2: EventParam,
+/// Each event is sent as a `MessageHeader` followed by its payload. Payload
+/// fields are in native byte order, in the order listed here:
+///
+/// | Event                | Payload                                                   |
+/// |----------------------|-----------------------------------------------------------|
+/// | `Renderer`           | `u64 buffer`                                              |
+/// | `KeyboardKeymap`     | `u32 seat, i32 fd, u32 size`                              |
+/// | `KeyboardKey`        | `u32 seat, u32 key, u32 state`                            |
+/// | `KeyboardModifiers`  | `u32 seat, u32 depressed, latched, locked, group`         |
+/// | `KeyboardRepeatInfo` | `u32 seat, i32 rate, i32 delay`                           |
+/// | `PointerMotion`      | `u32 seat, f64 x, f64 y`                                  |
+/// | `PointerButton`      | `u32 seat, u32 button, u32 state`                         |
+/// | `AuthSubmit`         | UTF-8 password, the whole payload                         |
+/// | `AuthStateUpdate`    | `u32 state`                                               |
+/// | `ThemeReload`        | empty                                                     |
+/// | `AuthPrompt`         | UTF-8 message, the whole payload                          |
+/// | `PointerEnter`       | `u32 seat, f64 x, f64 y`                                  |
+/// | `PointerLeave`       | `u32 seat`                                                |
+/// | `PointerAxis`        | `u32 seat, f64, f64, i32, i32, u32, u32`, in field order  |
+/// | `TouchFrame`         | `u32 seat`, then `f64 x, f64 y, i32 id, u32 state` each   |
+/// | `TouchCancel`        | `u32 seat`                                                |
+/// | `SeatName`           | `u32 seat`, then the UTF-8 name filling the payload       |
+/// | `SeatRemoved`        | `u32 seat`                                                |
+/// | `TextInputUpdate`    | `u32 enabled, u32 hint, u32 purpose, i32 x, y, w, h`      |
+/// | `TextInputPreedit`   | `u32 seat, i32 cursor_begin, cursor_end`, then UTF-8      |
+/// | `TextInputCommit`    | `u32 seat, u32 delete_before, delete_after`, then UTF-8   |
+/// | `PowerStatus`        | `u32 present, u32 level, u32 charging, u32 plugged_in`    |
+/// | `NetworkStatus`      | `u32 connected, u32 wireless`, then the UTF-8 interface   |
+/// | `MediaStatus`        | `u32 available, u32 status, u32 capabilities`, then text  |
+/// | `MediaAction`        | `u32 action`                                              |
+/// | `Notification`       | `u32 key`, then text                                      |
+///
+/// `seat` is the registry name of the `wl_seat` the input came from.
+///
+/// `MediaStatus` is followed by the player, title, artist, album and art URL,
+/// each as a `u32` byte length and UTF-8 text. `Notification` is followed by
+/// the application name, summary and body in the same way.
+///
+/// The C++ decoders are in `cpp/src/event.hpp`, and must be kept in sync.
+#[derive(Clone, PartialEq)]
+pub enum Event {
+    /// A frame has been rendered into the buffer at address `buffer`
+    ///
+    /// The address only identifies the buffer, it is never dereferenced.
+    Renderer {
+        buffer: usize,
+    },
+
+    /// A keymap file descriptor, owned by the receiver
+    KeyboardKeymap {
+        seat: u32,
+        fd: RawFd,
+        size: u32,
+    },
+    KeyboardKey {
+        seat: u32,
+        key: u32,
+        state: u32,
+    },
+    KeyboardModifiers {
+        seat: u32,
+        depressed: u32,
+        latched: u32,
+        locked: u32,
+        group: u32,
+    },
+    KeyboardRepeatInfo {
+        seat: u32,
+        rate: i32,
+        delay: i32,
+    },
+
+    PointerMotion {
+        seat: u32,
+        x: f64,
+        y: f64,
+    },
+    PointerButton {
+        seat: u32,
+        button: u32,
+        state: u32,
+    },
+
+    AuthSubmit {
+        password: String,
+    },
+    AuthStateUpdate {
+        state: AuthState,
+    },
+
+    ThemeReload,
+
+    AuthPrompt {
+        message: String,
+    },
+
+    PointerEnter {
+        seat: u32,
+        x: f64,
+        y: f64,
+    },
+    PointerLeave {
+        seat: u32,
+    },
+
+    /// Scrolling over one pointer frame
+    ///
+    /// `horizontal` and `vertical` are in surface pixels, the `_120` values
+    /// are in fractions of a wheel step, where 120 is one step. `source` is
+    /// a `wl_pointer` axis source, or `AXIS_SOURCE_UNKNOWN`. `stop` is a
+    /// combination of the `AXIS_STOP_*` flags.
+    PointerAxis {
+        seat: u32,
+        horizontal: f64,
+        vertical: f64,
+        horizontal_120: i32,
+        vertical_120: i32,
+        source: u32,
+        stop: u32,
+    },
+
+    /// Touch points that changed over one touch frame
+    TouchFrame {
+        seat: u32,
+        points: Vec<TouchPoint>,
+    },
+    TouchCancel {
+        seat: u32,
+    },
+
+    /// The compositor named a seat, or renamed it
+    SeatName {
+        seat: u32,
+        name: String,
+    },
+    SeatRemoved {
+        seat: u32,
+    },
+
+    /// The text field focused in the renderer changed, or its cursor moved
+    ///
+    /// `hint` and `purpose` are `zwp_text_input_v3` content types, and the
+    /// cursor rectangle is in surface coordinates. The other fields are
+    /// meaningless when `enabled` is false.
+    TextInputUpdate {
+        enabled: bool,
+        hint: u32,
+        purpose: u32,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+
+    /// Composing text from an input method, replacing any previous text
+    ///
+    /// The cursor positions are byte offsets into `text`, the cursor is
+    /// hidden when both are -1.
+    TextInputPreedit {
+        seat: u32,
+        cursor_begin: i32,
+        cursor_end: i32,
+        text: String,
+    },
+
+    /// Text from an input method, inserted at the cursor
+    ///
+    /// `delete_before` and `delete_after` are the number of bytes around the
+    /// cursor to delete before inserting `text`.
+    TextInputCommit {
+        seat: u32,
+        delete_before: u32,
+        delete_after: u32,
+        text: String,
+    },
+
+    /// Status of the batteries and power adapters
+    ///
+    /// `level` is a percentage. The battery fields are meaningless when
+    /// `present` is false.
+    PowerStatus {
+        present: bool,
+        level: u32,
+        charging: bool,
+        plugged_in: bool,
+    },
+
+    /// Status of the network connection
+    ///
+    /// `interface` is empty when not connected.
+    NetworkStatus {
+        connected: bool,
+        wireless: bool,
+        interface: String,
+    },
+
+    /// Status of the active media player
+    ///
+    /// `capabilities` is a combination of the `MEDIA_CAN_*` flags. The other
+    /// fields are meaningless when `available` is false.
+    MediaStatus {
+        available: bool,
+        status: MediaPlaybackStatus,
+        capabilities: u32,
+        player: String,
+        title: String,
+        artist: String,
+        album: String,
+        art_url: String,
+    },
+
+    /// The renderer asked to control the active media player
+    MediaAction {
+        action: MediaAction,
+    },
+
+    /// A notification was received while locked
+    ///
+    /// A notification replacing an earlier one has the same `key`. Text hidden
+    /// by the privacy level is empty.
+    Notification {
+        key: u32,
+        app_name: String,
+        summary: String,
+        body: String,
+    },
+}
+
+/// How a touch point changed during a touch frame
+///
+/// This enum is C-compatible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum TouchPointState {
+    Down = 1,
+    Motion = 2,
+    Up = 3,
+}
+
+impl TryFrom<u32> for TouchPointState {
+    type Error = &'static str;
+
+    fn try_from(tag: u32) -> Result<Self, Self::Error> {
+        match tag {
+            1 => Ok(TouchPointState::Down),
+            2 => Ok(TouchPointState::Motion),
+            3 => Ok(TouchPointState::Up),
+            _ => Err("Unknown TouchPointState tag"),
+        }
+    }
+}
+
+/// A touch point sent in a `TouchFrame` event
+///
+/// The position of an `Up` point is not known, the receiver uses the last
+/// position of the point instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchPoint {
+    pub id: i32,
+    pub state: TouchPointState,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// `PointerAxis` source, when the compositor did not send one
+pub const AXIS_SOURCE_UNKNOWN: u32 = u32::MAX;
+
+/// `PointerAxis` flag, set when vertical scrolling has stopped
+pub const AXIS_STOP_VERTICAL: u32 = 1 << 0;
+
+/// `PointerAxis` flag, set when horizontal scrolling has stopped
+pub const AXIS_STOP_HORIZONTAL: u32 = 1 << 1;
+
+/// Playback status of a media player
+///
+/// This enum is C-compatible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[repr(u32)]
+pub enum MediaPlaybackStatus {
+    #[default]
+    Stopped = 0,
+    Paused = 1,
+    Playing = 2,
+}
+
+impl TryFrom<u32> for MediaPlaybackStatus {
+    type Error = &'static str;
+
+    fn try_from(tag: u32) -> Result<Self, Self::Error> {
+        match tag {
+            0 => Ok(MediaPlaybackStatus::Stopped),
+            1 => Ok(MediaPlaybackStatus::Paused),
+            2 => Ok(MediaPlaybackStatus::Playing),
+            _ => Err("Unknown MediaPlaybackStatus tag"),
+        }
+    }
+}
+
+/// A control action for the active media player
+///
+/// This enum is C-compatible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum MediaAction {
+    PlayPause = 1,
+    Play = 2,
+    Pause = 3,
+    Next = 4,
+    Previous = 5,
+}
+
+impl TryFrom<u32> for MediaAction {
+    type Error = &'static str;
+
+    fn try_from(tag: u32) -> Result<Self, Self::Error> {
+        match tag {
+            1 => Ok(MediaAction::PlayPause),
+            2 => Ok(MediaAction::Play),
+            3 => Ok(MediaAction::Pause),
+            4 => Ok(MediaAction::Next),
+            5 => Ok(MediaAction::Previous),
+            _ => Err("Unknown MediaAction tag"),
+        }
+    }
+}
+
+/// `MediaStatus` flag, set when the player can start playing
+pub const MEDIA_CAN_PLAY: u32 = 1 << 0;
+
+/// `MediaStatus` flag, set when the player can pause
+pub const MEDIA_CAN_PAUSE: u32 = 1 << 1;
+
+/// `MediaStatus` flag, set when the player can skip to the next track
+pub const MEDIA_CAN_GO_NEXT: u32 = 1 << 2;
+
+/// `MediaStatus` flag, set when the player can go to the previous track
+pub const MEDIA_CAN_GO_PREVIOUS: u32 = 1 << 3;
+
+impl std::fmt::Debug for Event {
+    /// Format the event type only, so passwords are never logged
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.event_type())
+    }
 }
 
 impl Event {
-    /// Create a new event object of a specified type, with two parameters
-    pub fn new(event_type: EventType, param_1: EventParam, param_2: EventParam) -> Self {
-        Self {
-            event_type,
-            param_1,
-            param_2,
+    /// Returns the `EventType` of this event
+    pub fn event_type(&self) -> EventType {
+        match self {
+            Event::Renderer { .. } => EventType::Renderer,
+            Event::KeyboardKeymap { .. } => EventType::KeyboardKeymap,
+            Event::KeyboardKey { .. } => EventType::KeyboardKey,
+            Event::KeyboardModifiers { .. } => EventType::KeyboardModifiers,
+            Event::KeyboardRepeatInfo { .. } => EventType::KeyboardRepeatInfo,
+            Event::PointerMotion { .. } => EventType::PointerMotion,
+            Event::PointerButton { .. } => EventType::PointerButton,
+            Event::AuthSubmit { .. } => EventType::AuthSubmit,
+            Event::AuthStateUpdate { .. } => EventType::AuthStateUpdate,
+            Event::ThemeReload => EventType::ThemeReload,
+            Event::AuthPrompt { .. } => EventType::AuthPrompt,
+            Event::PointerEnter { .. } => EventType::PointerEnter,
+            Event::PointerLeave { .. } => EventType::PointerLeave,
+            Event::PointerAxis { .. } => EventType::PointerAxis,
+            Event::TouchFrame { .. } => EventType::TouchFrame,
+            Event::TouchCancel { .. } => EventType::TouchCancel,
+            Event::SeatName { .. } => EventType::SeatName,
+            Event::SeatRemoved { .. } => EventType::SeatRemoved,
+            Event::TextInputUpdate { .. } => EventType::TextInputUpdate,
+            Event::TextInputPreedit { .. } => EventType::TextInputPreedit,
+            Event::TextInputCommit { .. } => EventType::TextInputCommit,
+            Event::PowerStatus { .. } => EventType::PowerStatus,
+            Event::NetworkStatus { .. } => EventType::NetworkStatus,
+            Event::MediaStatus { .. } => EventType::MediaStatus,
+            Event::MediaAction { .. } => EventType::MediaAction,
+            Event::Notification { .. } => EventType::Notification,
         }
     }
 
-    /// Dereference a constant event pointer into an `Event`
-    ///
-    /// This function handles raw pointers, thus, is unsafe.
-    /// This function is not responsible for proper pointer management.
-    pub unsafe fn from_ptr(ptr: *const Event) -> Self {
-        unsafe { std::ptr::read(ptr) }
+    /// Encode the payload of this event
+    fn encode_payload(&self) -> Vec<u8> {
+        let payload = PayloadWriter::new();
+
+        match self {
+            Event::Renderer { buffer } => payload.u64(*buffer as u64),
+            Event::KeyboardKeymap { seat, fd, size } => payload.u32(*seat).i32(*fd).u32(*size),
+            Event::KeyboardKey { seat, key, state } => payload.u32(*seat).u32(*key).u32(*state),
+            Event::KeyboardModifiers {
+                seat,
+                depressed,
+                latched,
+                locked,
+                group,
+            } => payload
+                .u32(*seat)
+                .u32(*depressed)
+                .u32(*latched)
+                .u32(*locked)
+                .u32(*group),
+            Event::KeyboardRepeatInfo { seat, rate, delay } => {
+                payload.u32(*seat).i32(*rate).i32(*delay)
+            }
+            Event::PointerMotion { seat, x, y } => payload.u32(*seat).f64(*x).f64(*y),
+            Event::PointerButton {
+                seat,
+                button,
+                state,
+            } => payload.u32(*seat).u32(*button).u32(*state),
+            Event::AuthSubmit { password } => payload.bytes(password.as_bytes()),
+            Event::AuthStateUpdate { state } => payload.u32(*state as u32),
+            Event::ThemeReload => payload,
+            Event::AuthPrompt { message } => payload.bytes(message.as_bytes()),
+            Event::PointerEnter { seat, x, y } => payload.u32(*seat).f64(*x).f64(*y),
+            Event::PointerLeave { seat } => payload.u32(*seat),
+            Event::PointerAxis {
+                seat,
+                horizontal,
+                vertical,
+                horizontal_120,
+                vertical_120,
+                source,
+                stop,
+            } => payload
+                .u32(*seat)
+                .f64(*horizontal)
+                .f64(*vertical)
+                .i32(*horizontal_120)
+                .i32(*vertical_120)
+                .u32(*source)
+                .u32(*stop),
+            Event::TouchFrame { seat, points } => {
+                points.iter().fold(payload.u32(*seat), |payload, point| {
+                    payload
+                        .f64(point.x)
+                        .f64(point.y)
+                        .i32(point.id)
+                        .u32(point.state as u32)
+                })
+            }
+            Event::TouchCancel { seat } => payload.u32(*seat),
+            Event::SeatName { seat, name } => payload.u32(*seat).bytes(name.as_bytes()),
+            Event::SeatRemoved { seat } => payload.u32(*seat),
+            Event::TextInputUpdate {
+                enabled,
+                hint,
+                purpose,
+                x,
+                y,
+                width,
+                height,
+            } => payload
+                .u32(*enabled as u32)
+                .u32(*hint)
+                .u32(*purpose)
+                .i32(*x)
+                .i32(*y)
+                .i32(*width)
+                .i32(*height),
+            Event::TextInputPreedit {
+                seat,
+                cursor_begin,
+                cursor_end,
+                text,
+            } => payload
+                .u32(*seat)
+                .i32(*cursor_begin)
+                .i32(*cursor_end)
+                .bytes(text.as_bytes()),
+            Event::TextInputCommit {
+                seat,
+                delete_before,
+                delete_after,
+                text,
+            } => payload
+                .u32(*seat)
+                .u32(*delete_before)
+                .u32(*delete_after)
+                .bytes(text.as_bytes()),
+            Event::PowerStatus {
+                present,
+                level,
+                charging,
+                plugged_in,
+            } => payload
+                .u32(*present as u32)
+                .u32(*level)
+                .u32(*charging as u32)
+                .u32(*plugged_in as u32),
+            Event::NetworkStatus {
+                connected,
+                wireless,
+                interface,
+            } => payload
+                .u32(*connected as u32)
+                .u32(*wireless as u32)
+                .bytes(interface.as_bytes()),
+            Event::MediaStatus {
+                available,
+                status,
+                capabilities,
+                player,
+                title,
+                artist,
+                album,
+                art_url,
+            } => payload
+                .u32(*available as u32)
+                .u32(*status as u32)
+                .u32(*capabilities)
+                .text(player)
+                .text(title)
+                .text(artist)
+                .text(album)
+                .text(art_url),
+            Event::MediaAction { action } => payload.u32(*action as u32),
+            Event::Notification {
+                key,
+                app_name,
+                summary,
+                body,
+            } => payload.u32(*key).text(app_name).text(summary).text(body),
+        }
+        .finish()
     }
 
-    /// Dereference a mutable event pointer into an `Event`
+    /// Decode and validate the payload of an event of type `event_type`
+    fn decode_payload(
+        event_type: EventType,
+        payload: &[u8],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut reader = PayloadReader::new(payload);
+
+        let event = match event_type {
+            EventType::Wayland => return Err("Wayland events are not sent as messages".into()),
+            EventType::Renderer => Event::Renderer {
+                buffer: reader.u64()? as usize,
+            },
+            EventType::KeyboardKeymap => Event::KeyboardKeymap {
+                seat: reader.u32()?,
+                fd: reader.i32()?,
+                size: reader.u32()?,
+            },
+            EventType::KeyboardKey => Event::KeyboardKey {
+                seat: reader.u32()?,
+                key: reader.u32()?,
+                state: reader.u32()?,
+            },
+            EventType::KeyboardModifiers => Event::KeyboardModifiers {
+                seat: reader.u32()?,
+                depressed: reader.u32()?,
+                latched: reader.u32()?,
+                locked: reader.u32()?,
+                group: reader.u32()?,
+            },
+            EventType::KeyboardRepeatInfo => Event::KeyboardRepeatInfo {
+                seat: reader.u32()?,
+                rate: reader.i32()?,
+                delay: reader.i32()?,
+            },
+            EventType::PointerMotion => Event::PointerMotion {
+                seat: reader.u32()?,
+                x: reader.f64()?,
+                y: reader.f64()?,
+            },
+            EventType::PointerButton => Event::PointerButton {
+                seat: reader.u32()?,
+                button: reader.u32()?,
+                state: reader.u32()?,
+            },
+            EventType::AuthSubmit => Event::AuthSubmit {
+                password: reader.string()?,
+            },
+            EventType::AuthStateUpdate => Event::AuthStateUpdate {
+                state: AuthState::try_from(reader.u32()? as u64)?,
+            },
+            EventType::ThemeReload => Event::ThemeReload,
+            EventType::AuthPrompt => Event::AuthPrompt {
+                message: reader.string()?,
+            },
+            EventType::PointerEnter => Event::PointerEnter {
+                seat: reader.u32()?,
+                x: reader.f64()?,
+                y: reader.f64()?,
+            },
+            EventType::PointerLeave => Event::PointerLeave {
+                seat: reader.u32()?,
+            },
+            EventType::PointerAxis => Event::PointerAxis {
+                seat: reader.u32()?,
+                horizontal: reader.f64()?,
+                vertical: reader.f64()?,
+                horizontal_120: reader.i32()?,
+                vertical_120: reader.i32()?,
+                source: reader.u32()?,
+                stop: reader.u32()?,
+            },
+            EventType::TouchFrame => {
+                let seat = reader.u32()?;
+                let mut points = Vec::new();
+                while !reader.is_empty() {
+                    let (x, y) = (reader.f64()?, reader.f64()?);
+                    points.push(TouchPoint {
+                        id: reader.i32()?,
+                        state: TouchPointState::try_from(reader.u32()?)?,
+                        x,
+                        y,
+                    });
+                }
+                Event::TouchFrame { seat, points }
+            }
+            EventType::TouchCancel => Event::TouchCancel {
+                seat: reader.u32()?,
+            },
+            EventType::SeatName => Event::SeatName {
+                seat: reader.u32()?,
+                name: reader.string()?,
+            },
+            EventType::SeatRemoved => Event::SeatRemoved {
+                seat: reader.u32()?,
+            },
+            EventType::TextInputUpdate => Event::TextInputUpdate {
+                enabled: reader.u32()? != 0,
+                hint: reader.u32()?,
+                purpose: reader.u32()?,
+                x: reader.i32()?,
+                y: reader.i32()?,
+                width: reader.i32()?,
+                height: reader.i32()?,
+            },
+            EventType::TextInputPreedit => Event::TextInputPreedit {
+                seat: reader.u32()?,
+                cursor_begin: reader.i32()?,
+                cursor_end: reader.i32()?,
+                text: reader.string()?,
+            },
+            EventType::TextInputCommit => Event::TextInputCommit {
+                seat: reader.u32()?,
+                delete_before: reader.u32()?,
+                delete_after: reader.u32()?,
+                text: reader.string()?,
+            },
+            EventType::PowerStatus => Event::PowerStatus {
+                present: reader.u32()? != 0,
+                level: reader.u32()?,
+                charging: reader.u32()? != 0,
+                plugged_in: reader.u32()? != 0,
+            },
+            EventType::NetworkStatus => Event::NetworkStatus {
+                connected: reader.u32()? != 0,
+                wireless: reader.u32()? != 0,
+                interface: reader.string()?,
+            },
+            EventType::MediaStatus => Event::MediaStatus {
+                available: reader.u32()? != 0,
+                status: MediaPlaybackStatus::try_from(reader.u32()?)?,
+                capabilities: reader.u32()?,
+                player: reader.text()?,
+                title: reader.text()?,
+                artist: reader.text()?,
+                album: reader.text()?,
+                art_url: reader.text()?,
+            },
+            EventType::MediaAction => Event::MediaAction {
+                action: MediaAction::try_from(reader.u32()?)?,
+            },
+            EventType::Notification => Event::Notification {
+                key: reader.u32()?,
+                app_name: reader.text()?,
+                summary: reader.text()?,
+                body: reader.text()?,
+            },
+        };
+
+        reader.finish()?;
+
+        Ok(event)
+    }
+
+    /// Encode this event as a complete message, with its header
     ///
-    /// This function handles raw pointers, thus, is unsafe.
-    /// This function is not responsible for proper pointer management.
-    pub unsafe fn from_mut_ptr(ptr: *mut Event) -> Self {
-        unsafe { std::ptr::read(ptr) }
+    /// This function returns an error if the payload is too large to be sent.
+    pub fn encode(&self) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let payload = self.encode_payload();
+        if payload.len() > MAX_PAYLOAD_SIZE {
+            return Err(format!(
+                "{:?} event payload of {} bytes exceeds the maximum of {} bytes",
+                self.event_type(),
+                payload.len(),
+                MAX_PAYLOAD_SIZE
+            )
+            .into());
+        }
+
+        let header = MessageHeader {
+            event_type: self.event_type() as u16,
+            length: payload.len() as u32,
+        };
+
+        let mut message = Vec::with_capacity(HEADER_SIZE + payload.len());
+        message.extend_from_slice(&header.encode());
+        message.extend_from_slice(&payload);
+
+        Ok(message)
     }
 
-    /// Write this object into a file specified by `fd`
+    /// Write this event as a single message into a file specified by `fd`
     pub fn write_to(&self, fd: &OwnedFd) -> Result<(), Box<dyn std::error::Error>> {
-        let event_ptr = self as *const Event as *const u8;
-        let event_buf =
-            unsafe { std::slice::from_raw_parts(event_ptr, std::mem::size_of::<Event>()) };
+        let message = self.encode()?;
+        let bytes_written = nix::unistd::write(fd, &message)?;
 
-        nix::unistd::write(fd, event_buf)?;
+        if bytes_written != message.len() {
+            return Err(format!(
+                "Failed to write event, expected {} bytes, wrote {}.",
+                message.len(),
+                bytes_written
+            )
+            .into());
+        }
 
         Ok(())
     }
+
+    /// Read a single message from a file specified by `fd`
+    ///
+    /// Messages with an unknown type or an invalid payload are discarded, and
+    /// `None` is returned. An error is returned if the message is truncated or
+    /// its header is invalid, since the rest of the stream cannot be trusted.
+    pub fn read_from(fd: &OwnedFd) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        let mut header_buffer = [0u8; HEADER_SIZE];
+        read_exact(fd, &mut header_buffer, "header")?;
+        let header = MessageHeader::decode(&header_buffer)?;
+
+        // Always read the whole payload, so the next message can be read
+        let mut payload = vec![0u8; header.length as usize];
+        read_exact(fd, &mut payload, "payload")?;
+
+        let event_type = match EventType::try_from(header.event_type as u64) {
+            Ok(event_type) => event_type,
+            Err(_) => {
+                warn!("Rejected event with unknown type {}", header.event_type);
+                return Ok(None);
+            }
+        };
+
+        match Self::decode_payload(event_type, &payload) {
+            Ok(event) => Ok(Some(event)),
+            Err(e) => {
+                warn!("Rejected invalid {:?} event: {}", event_type, e);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Read exactly `buffer.len()` bytes of a message from `fd`
+///
+/// Messages are written atomically, so a short read means the message was
+/// truncated.
+fn read_exact(
+    fd: &OwnedFd,
+    buffer: &mut [u8],
+    part: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if buffer.is_empty() {
+        return Ok(());
+    }
+
+    let bytes_read = loop {
+        match nix::unistd::read(fd, buffer) {
+            Err(Errno::EINTR) => continue,
+            result => break result?,
+        }
+    };
+
+    if bytes_read != buffer.len() {
+        return Err(format!(
+            "Truncated event {}, expected {} bytes, got {}.",
+            part,
+            buffer.len(),
+            bytes_read
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns one event of every type sent as a message
+    fn every_event() -> Vec<Event> {
+        vec![
+            Event::Renderer { buffer: 0x1234 },
+            Event::KeyboardKeymap {
+                seat: 1,
+                fd: 5,
+                size: 4096,
+            },
+            Event::KeyboardKey {
+                seat: 1,
+                key: 30,
+                state: 1,
+            },
+            Event::KeyboardModifiers {
+                seat: 1,
+                depressed: 1,
+                latched: 2,
+                locked: 4,
+                group: 1,
+            },
+            Event::KeyboardRepeatInfo {
+                seat: 1,
+                rate: 25,
+                delay: 600,
+            },
+            Event::PointerMotion {
+                seat: 2,
+                x: 10.5,
+                y: -3.25,
+            },
+            Event::PointerButton {
+                seat: 2,
+                button: 0x110,
+                state: 1,
+            },
+            Event::AuthSubmit {
+                password: "pässword".to_string(),
+            },
+            Event::AuthStateUpdate {
+                state: AuthState::Failed,
+            },
+            Event::ThemeReload,
+            Event::AuthPrompt {
+                message: "Password:".to_string(),
+            },
+            Event::PointerEnter {
+                seat: 2,
+                x: 1.0,
+                y: 2.0,
+            },
+            Event::PointerLeave { seat: 2 },
+            Event::PointerAxis {
+                seat: 2,
+                horizontal: 0.0,
+                vertical: 15.0,
+                horizontal_120: 0,
+                vertical_120: 120,
+                source: AXIS_SOURCE_UNKNOWN,
+                stop: AXIS_STOP_HORIZONTAL,
+            },
+            Event::TouchFrame {
+                seat: 3,
+                points: vec![
+                    TouchPoint {
+                        id: 0,
+                        state: TouchPointState::Down,
+                        x: 5.0,
+                        y: 6.0,
+                    },
+                    TouchPoint {
+                        id: 1,
+                        state: TouchPointState::Up,
+                        x: 0.0,
+                        y: 0.0,
+                    },
+                ],
+            },
+            Event::TouchCancel { seat: 3 },
+            Event::SeatName {
+                seat: 3,
+                name: "seat0".to_string(),
+            },
+            Event::SeatRemoved { seat: 3 },
+            Event::TextInputUpdate {
+                enabled: true,
+                hint: 1,
+                purpose: 8,
+                x: 10,
+                y: 20,
+                width: 100,
+                height: 30,
+            },
+            Event::TextInputPreedit {
+                seat: 1,
+                cursor_begin: 0,
+                cursor_end: 3,
+                text: "にほ".to_string(),
+            },
+            Event::TextInputCommit {
+                seat: 1,
+                delete_before: 1,
+                delete_after: 0,
+                text: "日本".to_string(),
+            },
+            Event::PowerStatus {
+                present: true,
+                level: 80,
+                charging: false,
+                plugged_in: true,
+            },
+            Event::NetworkStatus {
+                connected: true,
+                wireless: true,
+                interface: "wlan0".to_string(),
+            },
+            Event::MediaStatus {
+                available: true,
+                status: MediaPlaybackStatus::Playing,
+                capabilities: MEDIA_CAN_PAUSE | MEDIA_CAN_GO_NEXT,
+                player: "Player".to_string(),
+                title: "Title".to_string(),
+                artist: String::new(),
+                album: "Album".to_string(),
+                art_url: "file:///art.png".to_string(),
+            },
+            Event::MediaAction {
+                action: MediaAction::Next,
+            },
+            Event::Notification {
+                key: 4,
+                app_name: "App".to_string(),
+                summary: "Summary".to_string(),
+                body: String::new(),
+            },
+        ]
+    }
+
+    /// Returns a message with a valid header of type `event_type`
+    fn message(event_type: u16, payload: &[u8]) -> Vec<u8> {
+        let header = MessageHeader {
+            event_type,
+            length: payload.len() as u32,
+        };
+
+        let mut message = header.encode().to_vec();
+        message.extend_from_slice(payload);
+        message
+    }
+
+    #[test]
+    fn every_event_round_trips() {
+        let (read_fd, write_fd) = nix::unistd::pipe().unwrap();
+
+        for event in every_event() {
+            event.write_to(&write_fd).unwrap();
+            let decoded = Event::read_from(&read_fd).unwrap();
+
+            assert!(
+                decoded.as_ref() == Some(&event),
+                "{:?} event did not round-trip",
+                event
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_event_type_is_skipped() {
+        let (read_fd, write_fd) = nix::unistd::pipe().unwrap();
+
+        nix::unistd::write(&write_fd, &message(u16::MAX, &[1, 2, 3])).unwrap();
+        Event::ThemeReload.write_to(&write_fd).unwrap();
+
+        assert!(Event::read_from(&read_fd).unwrap().is_none());
+        assert!(Event::read_from(&read_fd).unwrap() == Some(Event::ThemeReload));
+    }
+
+    #[test]
+    fn invalid_header_is_an_error() {
+        let (read_fd, write_fd) = nix::unistd::pipe().unwrap();
+
+        let mut message = Event::ThemeReload.encode().unwrap();
+        message[0] ^= 0xff;
+        nix::unistd::write(&write_fd, &message).unwrap();
+
+        assert!(Event::read_from(&read_fd).is_err());
+    }
+
+    /// Returns whether the payload of `event` ends with data filling the rest
+    /// of the payload, so its end cannot be checked
+    fn takes_rest(event: &Event) -> bool {
+        matches!(
+            event,
+            Event::AuthSubmit { .. }
+                | Event::AuthPrompt { .. }
+                | Event::SeatName { .. }
+                | Event::TextInputPreedit { .. }
+                | Event::TextInputCommit { .. }
+                | Event::NetworkStatus { .. }
+                | Event::TouchFrame { .. }
+        )
+    }
+
+    #[test]
+    fn truncated_payload_is_rejected() {
+        for event in every_event() {
+            let payload = event.encode_payload();
+
+            // These payloads are only text, which can be cut anywhere
+            if payload.is_empty()
+                || matches!(event, Event::AuthSubmit { .. } | Event::AuthPrompt { .. })
+            {
+                continue;
+            }
+
+            let mut truncated = vec![&payload[..3]];
+            if !takes_rest(&event) {
+                truncated.push(&payload[..payload.len() - 1]);
+            }
+
+            for payload in truncated {
+                assert!(
+                    Event::decode_payload(event.event_type(), payload).is_err(),
+                    "Truncated {:?} event was accepted",
+                    event
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() {
+        for event in every_event().into_iter().filter(|event| !takes_rest(event)) {
+            let mut payload = event.encode_payload();
+            payload.extend_from_slice(&[0, 0, 0, 0]);
+
+            assert!(
+                Event::decode_payload(event.event_type(), &payload).is_err(),
+                "{:?} event with trailing bytes was accepted",
+                event
+            );
+        }
+    }
+
+    #[test]
+    fn partial_touch_point_is_rejected() {
+        let mut payload = every_event()
+            .into_iter()
+            .find(|event| event.event_type() == EventType::TouchFrame)
+            .unwrap()
+            .encode_payload();
+        payload.extend_from_slice(&[0, 0, 0, 0]);
+
+        assert!(Event::decode_payload(EventType::TouchFrame, &payload).is_err());
+    }
+
+    #[test]
+    fn invalid_payload_is_skipped() {
+        let (read_fd, write_fd) = nix::unistd::pipe().unwrap();
+
+        let payload = PayloadWriter::new().u32(99).finish();
+        nix::unistd::write(&write_fd, &message(EventType::MediaAction as u16, &payload)).unwrap();
+
+        assert!(Event::read_from(&read_fd).unwrap().is_none());
+    }
+
+    #[test]
+    fn oversized_payload_is_rejected() {
+        let event = Event::AuthSubmit {
+            password: "a".repeat(MAX_PAYLOAD_SIZE),
+        };
+        assert!(event.encode().is_ok());
+
+        let event = Event::AuthSubmit {
+            password: "a".repeat(MAX_PAYLOAD_SIZE + 1),
+        };
+        assert!(event.encode().is_err());
+
+        let mut header = message(EventType::AuthSubmit as u16, &[]);
+        header[8..12].copy_from_slice(&(MAX_PAYLOAD_SIZE as u32 + 1).to_ne_bytes());
+        let header: [u8; HEADER_SIZE] = header.try_into().unwrap();
+        assert!(MessageHeader::decode(&header).is_err());
+    }
 }
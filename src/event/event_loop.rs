@@ -25,10 +25,12 @@ use wayland_client::backend::ReadEventsGuard;
 /// Contains a `nix::sys::Epoll` object which can be waited on
 /// Contains an events array for processing
 /// Contains the renderer file descriptor, which is added to `Epoll`
+/// Contains any additional event source file descriptors added to `Epoll`
 struct EventLoop {
     epoll: Epoll,
     events: [EpollEvent; 10],
     renderer_fd: BorrowedFd<'static>,
+    sources: Vec<BorrowedFd<'static>>,
 }
 
 impl EventLoop {
@@ -47,13 +49,36 @@ impl EventLoop {
             epoll,
             events,
             renderer_fd,
+            sources: Vec::new(),
         })
     }
+
+    /// Add a raw file descriptor as an event source, tagged with `event_type`
+    ///
+    /// The file descriptor must remain open for the lifetime of the event loop,
+    /// it is removed from `Epoll` when dropping.
+    fn add_source(
+        &mut self,
+        fd: RawFd,
+        event_type: EventType,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+
+        let event = EpollEvent::new(EpollFlags::EPOLLIN, event_type as u64);
+        self.epoll.add(fd, event)?;
+        self.sources.push(fd);
+
+        Ok(())
+    }
 }
 
 impl Drop for EventLoop {
     fn drop(&mut self) {
         let _ = self.epoll.delete(self.renderer_fd);
+
+        for fd in &self.sources {
+            let _ = self.epoll.delete(fd);
+        }
     }
 }
 
@@ -91,6 +116,9 @@ impl WaylandState {
                 EventType::Renderer => {
                     self.handle_renderer_event()?;
                 }
+                EventType::ThemeReload => {
+                    self.handle_theme_watch_event()?;
+                }
                 _ => {}
             }
         }
@@ -176,8 +204,13 @@ impl WaylandState {
                 .as_raw_fd(),
         )?;
 
+        if let Some(fd) = self.theme_watch_fd() {
+            event_loop.add_source(fd, EventType::ThemeReload)?;
+        }
+
         while self.continue_running()? {
             self.update_states(event_queue)?;
+            self.update_pointer();
 
             event_queue.flush()?;
             event_queue.dispatch_pending(self)?;
@@ -25,6 +25,32 @@ pub enum EventType {
 
     AuthSubmit = 9,
     AuthStateUpdate = 10,
+
+    ThemeReload = 11,
+
+    AuthPrompt = 12,
+
+    PointerEnter = 13,
+    PointerLeave = 14,
+    PointerAxis = 15,
+
+    TouchFrame = 16,
+    TouchCancel = 17,
+
+    SeatName = 18,
+    SeatRemoved = 19,
+
+    TextInputUpdate = 20,
+    TextInputPreedit = 21,
+    TextInputCommit = 22,
+
+    PowerStatus = 23,
+    NetworkStatus = 24,
+
+    MediaStatus = 25,
+    MediaAction = 26,
+
+    Notification = 27,
 }
 
 impl TryFrom<u64> for EventType {
@@ -46,6 +72,32 @@ impl TryFrom<u64> for EventType {
 
             9 => Ok(EventType::AuthSubmit),
             10 => Ok(EventType::AuthStateUpdate),
+
+            11 => Ok(EventType::ThemeReload),
+
+            12 => Ok(EventType::AuthPrompt),
+
+            13 => Ok(EventType::PointerEnter),
+            14 => Ok(EventType::PointerLeave),
+            15 => Ok(EventType::PointerAxis),
+
+            16 => Ok(EventType::TouchFrame),
+            17 => Ok(EventType::TouchCancel),
+
+            18 => Ok(EventType::SeatName),
+            19 => Ok(EventType::SeatRemoved),
+
+            20 => Ok(EventType::TextInputUpdate),
+            21 => Ok(EventType::TextInputPreedit),
+            22 => Ok(EventType::TextInputCommit),
+
+            23 => Ok(EventType::PowerStatus),
+            24 => Ok(EventType::NetworkStatus),
+
+            25 => Ok(EventType::MediaStatus),
+            26 => Ok(EventType::MediaAction),
+
+            27 => Ok(EventType::Notification),
             _ => Err("Invalid EventType tag"),
         }
     }
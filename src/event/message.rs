@@ -0,0 +1,328 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    message.rs:
+        Framing and payload encoding for event messages sent between Rust and
+        the renderer. The layout must match `cpp/src/event.hpp`.
+*/
+
+/// Marks the start of every message header, "TLKR" in native byte order
+pub const MESSAGE_MAGIC: u32 = 0x524b_4c54;
+
+/// Version of the event protocol, incremented on any incompatible change
+pub const PROTOCOL_VERSION: u16 = 2;
+
+/// Size of a `MessageHeader` on the wire
+pub const HEADER_SIZE: usize = 12;
+
+/// Maximum size of a message, including its header
+///
+/// Pipe writes of up to `PIPE_BUF` bytes are atomic, so a message is never
+/// split or interleaved with a message written by another thread.
+pub const MAX_MESSAGE_SIZE: usize = 4096;
+
+/// Maximum size of a message payload
+pub const MAX_PAYLOAD_SIZE: usize = MAX_MESSAGE_SIZE - HEADER_SIZE;
+
+/// Header sent before every message payload
+///
+/// On the wire, this is `{ u32 magic, u16 version, u16 event_type, u32 length }`
+/// in native byte order, where `length` is the size of the payload in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageHeader {
+    pub event_type: u16,
+    pub length: u32,
+}
+
+impl MessageHeader {
+    /// Encode this header for the current protocol version
+    pub fn encode(&self) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0u8; HEADER_SIZE];
+        bytes[0..4].copy_from_slice(&MESSAGE_MAGIC.to_ne_bytes());
+        bytes[4..6].copy_from_slice(&PROTOCOL_VERSION.to_ne_bytes());
+        bytes[6..8].copy_from_slice(&self.event_type.to_ne_bytes());
+        bytes[8..12].copy_from_slice(&self.length.to_ne_bytes());
+        bytes
+    }
+
+    /// Decode and validate a header
+    ///
+    /// This function returns an error if the magic or version do not match,
+    /// or if the payload would be larger than `MAX_PAYLOAD_SIZE`.
+    pub fn decode(bytes: &[u8; HEADER_SIZE]) -> Result<Self, Box<dyn std::error::Error>> {
+        let magic = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        if magic != MESSAGE_MAGIC {
+            return Err(format!("Invalid event message magic: {:#010x}", magic).into());
+        }
+
+        let version = u16::from_ne_bytes([bytes[4], bytes[5]]);
+        if version != PROTOCOL_VERSION {
+            return Err(format!(
+                "Unsupported event protocol version {}, expected {}",
+                version, PROTOCOL_VERSION
+            )
+            .into());
+        }
+
+        let event_type = u16::from_ne_bytes([bytes[6], bytes[7]]);
+        let length = u32::from_ne_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+        if length as usize > MAX_PAYLOAD_SIZE {
+            return Err(format!(
+                "Event payload of {} bytes exceeds the maximum of {} bytes",
+                length, MAX_PAYLOAD_SIZE
+            )
+            .into());
+        }
+
+        Ok(Self { event_type, length })
+    }
+}
+
+/// Builds a message payload, field by field, in native byte order
+#[derive(Default)]
+pub struct PayloadWriter {
+    data: Vec<u8>,
+}
+
+impl PayloadWriter {
+    /// Create a new, empty `PayloadWriter`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a `u32` to the payload
+    pub fn u32(mut self, value: u32) -> Self {
+        self.data.extend_from_slice(&value.to_ne_bytes());
+        self
+    }
+
+    /// Append an `i32` to the payload
+    pub fn i32(mut self, value: i32) -> Self {
+        self.data.extend_from_slice(&value.to_ne_bytes());
+        self
+    }
+
+    /// Append a `u64` to the payload
+    pub fn u64(mut self, value: u64) -> Self {
+        self.data.extend_from_slice(&value.to_ne_bytes());
+        self
+    }
+
+    /// Append a `f64` to the payload
+    pub fn f64(mut self, value: f64) -> Self {
+        self.data.extend_from_slice(&value.to_ne_bytes());
+        self
+    }
+
+    /// Append raw bytes to the payload
+    pub fn bytes(mut self, value: &[u8]) -> Self {
+        self.data.extend_from_slice(value);
+        self
+    }
+
+    /// Append a UTF-8 string to the payload, preceded by its length in bytes
+    /// as a `u32`
+    pub fn text(self, value: &str) -> Self {
+        self.u32(value.len() as u32).bytes(value.as_bytes())
+    }
+
+    /// Returns the encoded payload
+    pub fn finish(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// Reads fields from a message payload, in native byte order
+///
+/// Reading past the end of the payload returns an error, as does calling
+/// `finish` with bytes left over, so truncated or oversized payloads are
+/// rejected.
+pub struct PayloadReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> PayloadReader<'a> {
+    /// Create a new `PayloadReader` over `data`
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Take the next `N` bytes from the payload
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], Box<dyn std::error::Error>> {
+        if self.data.len() < N {
+            return Err("Event payload is truncated".into());
+        }
+
+        let (head, tail) = self.data.split_at(N);
+        self.data = tail;
+
+        Ok(head.try_into()?)
+    }
+
+    /// Read the next `u32` from the payload
+    pub fn u32(&mut self) -> Result<u32, Box<dyn std::error::Error>> {
+        Ok(u32::from_ne_bytes(self.take()?))
+    }
+
+    /// Read the next `i32` from the payload
+    pub fn i32(&mut self) -> Result<i32, Box<dyn std::error::Error>> {
+        Ok(i32::from_ne_bytes(self.take()?))
+    }
+
+    /// Read the next `u64` from the payload
+    pub fn u64(&mut self) -> Result<u64, Box<dyn std::error::Error>> {
+        Ok(u64::from_ne_bytes(self.take()?))
+    }
+
+    /// Read the next `f64` from the payload
+    pub fn f64(&mut self) -> Result<f64, Box<dyn std::error::Error>> {
+        Ok(f64::from_ne_bytes(self.take()?))
+    }
+
+    /// Returns whether the whole payload has been read
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Read the next UTF-8 string from the payload, preceded by its length in
+    /// bytes as a `u32`
+    pub fn text(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let length = self.u32()? as usize;
+        if self.data.len() < length {
+            return Err("Event payload is truncated".into());
+        }
+
+        let (head, tail) = self.data.split_at(length);
+        let value = std::str::from_utf8(head)
+            .map_err(|e| format!("Event payload is not valid UTF-8: {}", e))?
+            .to_string();
+        self.data = tail;
+
+        Ok(value)
+    }
+
+    /// Take the rest of the payload as a UTF-8 string
+    pub fn string(&mut self) -> Result<String, Box<dyn std::error::Error>> {
+        let value = std::str::from_utf8(self.data)
+            .map_err(|e| format!("Event payload is not valid UTF-8: {}", e))?
+            .to_string();
+        self.data = &[];
+
+        Ok(value)
+    }
+
+    /// Check that the whole payload has been read
+    pub fn finish(self) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.data.is_empty() {
+            return Err(format!(
+                "Event payload has {} unexpected trailing bytes",
+                self.data.len()
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns an encoded header with the given fields
+    fn header_bytes(magic: u32, version: u16, event_type: u16, length: u32) -> [u8; HEADER_SIZE] {
+        let mut bytes = [0u8; HEADER_SIZE];
+        bytes[0..4].copy_from_slice(&magic.to_ne_bytes());
+        bytes[4..6].copy_from_slice(&version.to_ne_bytes());
+        bytes[6..8].copy_from_slice(&event_type.to_ne_bytes());
+        bytes[8..12].copy_from_slice(&length.to_ne_bytes());
+        bytes
+    }
+
+    #[test]
+    fn header_round_trips() {
+        let header = MessageHeader {
+            event_type: 7,
+            length: 20,
+        };
+
+        assert_eq!(MessageHeader::decode(&header.encode()).unwrap(), header);
+    }
+
+    #[test]
+    fn header_with_bad_magic_is_rejected() {
+        let bytes = header_bytes(0xdead_beef, PROTOCOL_VERSION, 7, 0);
+        assert!(MessageHeader::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn header_with_bad_version_is_rejected() {
+        let bytes = header_bytes(MESSAGE_MAGIC, PROTOCOL_VERSION + 1, 7, 0);
+        assert!(MessageHeader::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn header_with_oversized_payload_is_rejected() {
+        let bytes = header_bytes(MESSAGE_MAGIC, PROTOCOL_VERSION, 7, MAX_PAYLOAD_SIZE as u32);
+        assert!(MessageHeader::decode(&bytes).is_ok());
+
+        let bytes = header_bytes(
+            MESSAGE_MAGIC,
+            PROTOCOL_VERSION,
+            7,
+            MAX_PAYLOAD_SIZE as u32 + 1,
+        );
+        assert!(MessageHeader::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn payload_fields_round_trip() {
+        let payload = PayloadWriter::new()
+            .u32(1)
+            .i32(-2)
+            .u64(3)
+            .f64(4.5)
+            .text("text")
+            .bytes("rest".as_bytes())
+            .finish();
+
+        let mut reader = PayloadReader::new(&payload);
+        assert_eq!(reader.u32().unwrap(), 1);
+        assert_eq!(reader.i32().unwrap(), -2);
+        assert_eq!(reader.u64().unwrap(), 3);
+        assert_eq!(reader.f64().unwrap(), 4.5);
+        assert_eq!(reader.text().unwrap(), "text");
+        assert_eq!(reader.string().unwrap(), "rest");
+        assert!(reader.finish().is_ok());
+    }
+
+    #[test]
+    fn truncated_payload_is_rejected() {
+        let payload = PayloadWriter::new().u32(1).finish();
+        assert!(PayloadReader::new(&payload[..3]).u32().is_err());
+
+        let mut reader = PayloadReader::new(&payload);
+        reader.u32().unwrap();
+        assert!(reader.u32().is_err());
+
+        // The length of the text is larger than the rest of the payload
+        let payload = PayloadWriter::new().u32(10).bytes(b"short").finish();
+        assert!(PayloadReader::new(&payload).text().is_err());
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() {
+        let payload = PayloadWriter::new().u32(1).u32(2).finish();
+
+        let mut reader = PayloadReader::new(&payload);
+        reader.u32().unwrap();
+        assert!(reader.finish().is_err());
+    }
+
+    #[test]
+    fn invalid_utf8_is_rejected() {
+        let payload = PayloadWriter::new().bytes(&[0xff, 0xfe]).finish();
+        assert!(PayloadReader::new(&payload).string().is_err());
+    }
+}
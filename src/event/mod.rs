@@ -1,10 +1,12 @@
+mod broadcast;
 mod event;
 mod event_loop;
-mod event_param;
 mod event_type;
+mod message;
 mod tagged_fd;
 
-pub use event::Event;
-pub use event_param::EventParam;
+pub use broadcast::*;
+pub use event::*;
 pub use event_type::EventType;
+pub use message::*;
 pub use tagged_fd::*;
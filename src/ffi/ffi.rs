@@ -6,9 +6,9 @@
         Prototypes for C++ renderer FFI.
 */
 
-use crate::shared::ApplicationState;
+use crate::shared::{ApplicationState, OutputState};
 
-use std::os::raw::{c_char, c_int, c_void};
+use std::os::raw::{c_int, c_void};
 
 #[repr(C)]
 pub struct QmlRenderer {
@@ -24,9 +24,7 @@ pub type RsGetBufferCallback = unsafe extern "C" fn(user_data: *mut c_void) -> *
 
 unsafe extern "C" {
     pub fn initialize_renderer(
-        width: c_int,
-        height: c_int,
-        qml_path: *const c_char,
+        output: *mut OutputState,
         app_state: *mut ApplicationState,
     ) -> *mut QmlRenderer;
 
@@ -40,23 +38,3 @@ unsafe extern "C" {
 
     pub fn cleanup_renderer(renderer: *mut QmlRenderer);
 }
-
-/// `ForeignBuffer` structure for sending and receiving buffers safely from
-/// threads written in languages other than Rust.
-///
-/// The `dealloc` member of this struct is a function pointer to the
-/// deallocator to use to avoid undefined behaviour.
-#[repr(C)]
-pub struct ForeignBuffer {
-    pub ptr: *mut u8,
-    pub len: usize,
-    pub dealloc: Option<extern "C" fn(*mut c_void)>,
-}
-
-impl Drop for ForeignBuffer {
-    fn drop(&mut self) {
-        if let Some(d) = self.dealloc {
-            d(self.ptr as *mut c_void);
-        }
-    }
-}
@@ -6,10 +6,10 @@
         Safe getter/setter methods for the ApplicationState object.
 */
 
-use crate::ffi::QmlRenderer;
-use crate::shared::{ApplicationState, State};
+use crate::config::ColorScheme;
+use crate::shared::{ApplicationState, Background, SessionInfo, State};
 
-use std::os::raw::{c_char, c_int};
+use std::os::raw::{c_int, c_uint};
 
 macro_rules! safe_getter {
     ($fn_name:ident, $field:ident, $return_type:ty) => {
@@ -37,21 +37,18 @@ macro_rules! safe_setter {
 }
 
 safe_getter!(get_state, state, State);
-safe_getter!(get_renderer_read_fd, renderer_read_fd, c_int);
 safe_getter!(get_renderer_write_fd, renderer_write_fd, c_int);
 safe_getter!(get_auth_read_fd, auth_read_fd, c_int);
 safe_getter!(get_auth_write_fd, auth_write_fd, c_int);
-safe_getter!(get_renderer, renderer, *mut QmlRenderer);
-safe_getter!(get_qml_path, qml_path, *mut c_char);
-safe_getter!(get_output_width, output_width, c_int);
-safe_getter!(get_output_height, output_height, c_int);
+safe_getter!(get_buffer_format, buffer_format, c_uint);
+safe_getter!(get_preview, preview, bool);
 
 safe_setter!(set_state, state, State);
-safe_setter!(set_renderer_read_fd, renderer_read_fd, c_int);
 safe_setter!(set_renderer_write_fd, renderer_write_fd, c_int);
 safe_setter!(set_auth_read_fd, auth_read_fd, c_int);
 safe_setter!(set_auth_write_fd, auth_write_fd, c_int);
-safe_setter!(set_renderer, renderer, *mut QmlRenderer);
-safe_setter!(set_qml_path, qml_path, *mut c_char);
-safe_setter!(set_output_width, output_width, c_int);
-safe_setter!(set_output_height, output_height, c_int);
+safe_setter!(set_buffer_format, buffer_format, c_uint);
+safe_setter!(set_preview, preview, bool);
+safe_setter!(set_session, session, SessionInfo);
+safe_setter!(set_color_scheme, color_scheme, ColorScheme);
+safe_setter!(set_background, background, Background);
@@ -4,55 +4,98 @@
 /*
     log.rs:
         Exposed logging wrappers for external code
+
+        All messages are emitted as `tracing` events with the target
+        `LOG_TARGET`, and structured fields identifying where they came from.
 */
 
-use std::ffi::{CStr, c_char};
+use std::ffi::{CStr, c_char, c_int};
 
-unsafe fn cstr_to_str<'a>(ptr: *const c_char) -> &'a str {
-    unsafe { CStr::from_ptr(ptr).to_str().unwrap_or("<invalid utf8>") }
-}
+/// Target used for all events logged by the renderer
+pub const LOG_TARGET: &str = "tlockr_qt";
 
-#[unsafe(no_mangle)]
-pub extern "C" fn trace_log(file: *const c_char, msg: *const c_char) {
-    unsafe {
-        let file_str = cstr_to_str(file);
-        let msg_str = cstr_to_str(msg);
-        log::trace!(target: file_str, "{}", msg_str);
+/// Convert a C string into a `&str`, treating `NULL` as an empty string
+unsafe fn cstr_to_str<'a>(ptr: *const c_char) -> &'a str {
+    if ptr.is_null() {
+        return "";
     }
+    unsafe { CStr::from_ptr(ptr).to_str().unwrap_or("<invalid utf8>") }
 }
 
-#[unsafe(no_mangle)]
-pub extern "C" fn debug_log(file: *const c_char, msg: *const c_char) {
-    unsafe {
-        let file_str = cstr_to_str(file);
-        let msg_str = cstr_to_str(msg);
-        log::debug!(target: file_str, "{}", msg_str);
-    }
+/// Emit a `tracing` event at a log level passed from C++
+///
+/// Log levels match the `LogLevel` enum in `logging.hpp`, unknown levels are
+/// logged as errors.
+macro_rules! ffi_event {
+    ($level:expr, $($args:tt)+) => {
+        match $level {
+            0 => tracing::trace!(target: LOG_TARGET, $($args)+),
+            1 => tracing::debug!(target: LOG_TARGET, $($args)+),
+            2 => tracing::info!(target: LOG_TARGET, $($args)+),
+            3 => tracing::warn!(target: LOG_TARGET, $($args)+),
+            _ => tracing::error!(target: LOG_TARGET, $($args)+),
+        }
+    };
 }
 
+/// Log a message from the C++ renderer code
+///
+/// `component` identifies the source file the message originated from.
 #[unsafe(no_mangle)]
-pub extern "C" fn info_log(file: *const c_char, msg: *const c_char) {
+pub extern "C" fn renderer_log(level: c_int, component: *const c_char, msg: *const c_char) {
     unsafe {
-        let file_str = cstr_to_str(file);
+        let component_str = cstr_to_str(component);
         let msg_str = cstr_to_str(msg);
-        log::info!(target: file_str, "{}", msg_str);
+        ffi_event!(level, component = component_str, "{}", msg_str);
     }
 }
 
+/// Log a message received by the Qt message handler
+///
+/// The category, file, line, and function are taken from the
+/// `QMessageLogContext` of the message, and may be empty.
 #[unsafe(no_mangle)]
-pub extern "C" fn warn_log(file: *const c_char, msg: *const c_char) {
+pub extern "C" fn qt_log(
+    level: c_int,
+    category: *const c_char,
+    file: *const c_char,
+    line: c_int,
+    function: *const c_char,
+    msg: *const c_char,
+) {
     unsafe {
+        let category_str = cstr_to_str(category);
         let file_str = cstr_to_str(file);
+        let function_str = cstr_to_str(function);
         let msg_str = cstr_to_str(msg);
-        log::warn!(target: file_str, "{}", msg_str);
+        ffi_event!(
+            level,
+            component = "qt",
+            category = category_str,
+            file = file_str,
+            line,
+            function = function_str,
+            "{}",
+            msg_str
+        );
     }
 }
 
+/// Log a message sent by a theme through the `tlockr` QML interface
+///
+/// `qml_file` and `line` give the location of the call in the theme.
 #[unsafe(no_mangle)]
-pub extern "C" fn error_log(file: *const c_char, msg: *const c_char) {
+pub extern "C" fn qml_log(level: c_int, qml_file: *const c_char, line: c_int, msg: *const c_char) {
     unsafe {
-        let file_str = cstr_to_str(file);
+        let qml_file_str = cstr_to_str(qml_file);
         let msg_str = cstr_to_str(msg);
-        log::error!(target: file_str, "{}", msg_str);
+        ffi_event!(
+            level,
+            component = "qml",
+            qml_file = qml_file_str,
+            line,
+            "{}",
+            msg_str
+        );
     }
 }
@@ -1,2 +1,6 @@
 mod output;
 mod renderer;
+mod surface;
+
+pub use output::*;
+pub use surface::*;
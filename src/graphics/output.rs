@@ -3,10 +3,12 @@
 
 /*
     output.rs:
-        Contains a dispatch method to obtain the output display dimensions.
+        Tracks the outputs advertised by the compositor, and selects the
+        outputs the lock screen is shown on.
 */
 
-use crate::ffi::{set_output_height, set_output_width};
+use crate::config::OutputConfig;
+use crate::shared::OutputDetails;
 use crate::wayland::WaylandState;
 
 use tracing::debug;
@@ -15,33 +17,158 @@ use wayland_client::{
     protocol::wl_output::{self, WlOutput},
 };
 
-impl Dispatch<WlOutput, ()> for WaylandState {
+/// An output advertised by the compositor
+///
+/// Outputs are identified by their registry name. `name` and `description`
+/// are only sent by compositors supporting version 4 of `wl_output`.
+pub struct OutputInfo {
+    pub registry_name: u32,
+    pub output: WlOutput,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub width: i32,
+    pub height: i32,
+    /// Whether the compositor has sent all information about the output
+    pub done: bool,
+}
+
+impl OutputInfo {
+    /// Create a new `OutputInfo`, before any information has been received
+    pub fn new(registry_name: u32, output: WlOutput) -> Self {
+        Self {
+            registry_name,
+            output,
+            name: None,
+            description: None,
+            width: -1,
+            height: -1,
+            done: false,
+        }
+    }
+}
+
+/// An output selected to show the lock screen on
+///
+/// `rule` is the first output rule matching the output, and `details`
+/// describe the output to its theme.
+pub struct SelectedOutput {
+    pub registry_name: u32,
+    pub rule: Option<OutputConfig>,
+    pub details: OutputDetails,
+}
+
+impl WaylandState {
+    /// Returns the first output rule matching `info`, in order of label
+    fn output_rule(&self, info: &OutputInfo) -> Option<&OutputConfig> {
+        self.output_rules
+            .values()
+            .find(|rule| rule.matches(info.name.as_deref(), info.description.as_deref()))
+    }
+
+    /// Select the outputs the lock screen is shown on, with their rules
+    ///
+    /// Every output the compositor has finished describing is selected. The
+    /// primary output comes first, which is the first output with a rule
+    /// setting `primary`, or the first output if there is none.
+    pub fn select_outputs(&self) -> Vec<SelectedOutput> {
+        let mut outputs = self
+            .outputs
+            .iter()
+            .filter(|info| info.done)
+            .map(|info| (info, self.output_rule(info)))
+            .collect::<Vec<_>>();
+
+        let primary = outputs
+            .iter()
+            .position(|(_, rule)| rule.and_then(|rule| rule.primary).unwrap_or(false))
+            .unwrap_or(0);
+        if primary < outputs.len() {
+            outputs[..=primary].rotate_right(1);
+        }
+
+        outputs
+            .into_iter()
+            .enumerate()
+            .map(|(index, (info, rule))| SelectedOutput {
+                registry_name: info.registry_name,
+                details: OutputDetails::new(
+                    info.name.as_deref(),
+                    info.description.as_deref(),
+                    rule.and_then(|rule| rule.role.as_deref()),
+                    index == 0,
+                ),
+                rule: rule.cloned(),
+            })
+            .collect()
+    }
+
+    /// Forget an output removed by the compositor
+    ///
+    /// Outputs the lock screen is shown on are kept, as their lock surfaces
+    /// are still in use.
+    pub fn remove_output(&mut self, registry_name: u32) {
+        if !self
+            .surfaces
+            .iter()
+            .any(|surface| surface.output_name == registry_name)
+        {
+            self.outputs
+                .retain(|info| info.registry_name != registry_name);
+        }
+    }
+}
+
+impl Dispatch<WlOutput, u32> for WaylandState {
     fn event(
         state: &mut Self,
         _proxy: &WlOutput,
         event: <WlOutput as wayland_client::Proxy>::Event,
-        _data: &(),
+        registry_name: &u32,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
     ) {
+        let Some(info) = state
+            .outputs
+            .iter_mut()
+            .find(|info| info.registry_name == *registry_name)
+        else {
+            return;
+        };
+
         match event {
+            // Compositors usually mark the current mode as preferred too
             wl_output::Event::Mode {
                 flags,
                 width,
                 height,
                 refresh: _,
-            } => {
-                if flags.into_result().unwrap() == wl_output::Mode::Current {
-                    debug!("Output mode: {} x {} pixels", width, height);
-                    state.width = width;
-                    state.height = height;
-
-                    set_output_width(state.app_state, width);
-                    set_output_height(state.app_state, height);
+            } if flags
+                .into_result()
+                .is_ok_and(|flags| flags.contains(wl_output::Mode::Current)) =>
+            {
+                debug!("Output mode: {} x {} pixels", width, height);
+                info.width = width;
+                info.height = height;
+
+                if let Some(surface) = state
+                    .surfaces
+                    .iter_mut()
+                    .find(|surface| surface.output_name == *registry_name)
+                {
+                    surface.output_state.width = width;
+                    surface.output_state.height = height;
                 }
             }
+            wl_output::Event::Name { name } => {
+                debug!("Output name: {}", name);
+                info.name = Some(name);
+            }
+            wl_output::Event::Description { description } => {
+                debug!("Output description: {}", description);
+                info.description = Some(description);
+            }
             wl_output::Event::Done => {
-                state.output_configured = true;
+                info.done = true;
             }
             _ => {}
         }
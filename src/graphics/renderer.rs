@@ -9,14 +9,12 @@
 
 use crate::buffer::BufferManager;
 use crate::event::Event;
-use crate::ffi::{
-    cleanup_renderer, get_qml_path, get_renderer, initialize_renderer, set_callbacks, set_renderer,
-};
+use crate::ffi::{cleanup_renderer, initialize_renderer, set_callbacks};
+use crate::input::TextInputField;
 use crate::wayland::WaylandState;
 
 use std::{ffi::c_void, i32};
-use wayland_client::protocol::wl_surface::WlSurface;
-use wayland_protocols::wp::viewporter::client::wp_viewport::WpViewport;
+use tracing::warn;
 
 impl WaylandState {
     /// Callback function for the renderer.
@@ -36,113 +34,111 @@ impl WaylandState {
 
     /// Reads a single `Event` from the renderer event pipe
     ///
-    /// This function reads a pointer to a `Event` from the renderer's file descriptor.
-    /// This event contains information about which buffer is ready to be displayed.
-    fn read_renderer_event(&self) -> Result<Event, Box<dyn std::error::Error>> {
+    /// Invalid messages are discarded, and `None` is returned.
+    fn read_renderer_event(&self) -> Result<Option<Event>, Box<dyn std::error::Error>> {
         let renderer_fd = self
             .renderer_read_pipe
             .as_ref()
             .ok_or("Renderer file descriptor not set")?
             .read_fd();
 
-        let mut event_buffer = [0u8; std::mem::size_of::<Event>()];
-        let bytes_read = nix::unistd::read(renderer_fd, &mut event_buffer)?;
-
-        if bytes_read != std::mem::size_of::<Event>() {
-            return Err(format!(
-                "Failed to read renderer event pipe, expected {} byets, got {}.",
-                std::mem::size_of::<Event>(),
-                bytes_read
-            )
-            .into());
-        }
-
-        // https://stackoverflow.com/questions/42499049/how-to-transmute-a-u8-buffer-to-struct-in-rust
-        let (head, body, _tail) = unsafe { event_buffer.align_to::<Event>() };
-        assert!(head.is_empty(), "Event data was not aligned");
-        let event = body[0];
-
-        Ok(event)
+        Event::read_from(renderer_fd)
     }
 
-    /// Retrieves the currently active surface and viewport
-    fn get_surface_and_viewport(
-        &self,
-    ) -> Result<(&WlSurface, &WpViewport), Box<dyn std::error::Error>> {
-        match (&self.surface, &self.viewport) {
-            (Some(surface), Some(viewport)) => Ok((surface, viewport)),
-            _ => Err("Surface or viewport unavailable".into()),
-        }
-    }
-
-    /// Updates a buffer associated with a renderer event
+    /// Updates the buffer rendered into by a renderer
     ///
-    /// This functions takes a `Event` object, and finds a `Buffer` associated with it.
-    /// This buffer is then attached to the currently active Wayland surface, and committed.
-    fn update_buffer(&mut self, event: &Event) -> Result<(), Box<dyn std::error::Error>> {
-        let width = self.width;
-        let height = self.height;
-
-        let (surface_ptr, viewport_ptr) = {
-            let (surface, viewport) = self.get_surface_and_viewport()?;
-            (surface as *const WlSurface, viewport as *const WpViewport)
-        };
-        let buffer = self.buffer_manager.find_buffer_from_event(event)?;
-
-        let surface = unsafe { &*surface_ptr };
-        let viewport = unsafe { &*viewport_ptr };
-
-        surface.attach(Some(&buffer.buffer), 0, 0);
-        surface.damage_buffer(0, 0, i32::MAX, i32::MAX);
-        viewport.set_destination(width, height);
-        buffer.in_use = true;
-        surface.commit();
+    /// This functions takes the buffer address from a renderer event, and finds the `Buffer` associated with it.
+    /// Each output has its own buffers, so this buffer is then attached to the Wayland surface of the output
+    /// it belongs to, and committed.
+    fn update_buffer(&mut self, address: usize) -> Result<(), Box<dyn std::error::Error>> {
+        for theme_surface in self.surfaces.iter_mut() {
+            let (Some(surface), Some(viewport)) = (&theme_surface.surface, &theme_surface.viewport)
+            else {
+                continue;
+            };
+            let Ok(buffer) = theme_surface.buffer_manager.find_buffer_by_address(address) else {
+                continue;
+            };
+
+            surface.attach(Some(&buffer.buffer), 0, 0);
+            surface.damage_buffer(0, 0, i32::MAX, i32::MAX);
+            viewport.set_destination(
+                theme_surface.output_state.width,
+                theme_surface.output_state.height,
+            );
+            buffer.in_use = true;
+            surface.commit();
 
-        Ok(())
+            return Ok(());
+        }
+
+        Err("No matching buffer found".into())
     }
 
     /// Read and process a single renderer event from the renderer event pipe
     pub fn handle_renderer_event(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let event = self.read_renderer_event()?;
-        self.update_buffer(&event)?;
+        match self.read_renderer_event()? {
+            Some(Event::Renderer { buffer }) => self.update_buffer(buffer)?,
+            Some(Event::TextInputUpdate {
+                enabled,
+                hint,
+                purpose,
+                x,
+                y,
+                width,
+                height,
+            }) => self.update_text_input_field(enabled.then_some(TextInputField {
+                hint,
+                purpose,
+                x,
+                y,
+                width,
+                height,
+            })),
+            Some(Event::MediaAction { action }) => self.handle_media_action(action),
+            Some(event) => warn!("Ignoring unexpected {:?} event from renderer", event),
+            None => {}
+        }
 
         Ok(())
     }
 
-    /// Initialize the renderer
+    /// Initialize the renderer of every output
     ///
-    /// This function creates a new `QmlRenderer` object with the appropriate callbacks set for rendering.
-    /// The QML content path is obtained from the current application state.
-    /// The created renderer is stored in the current application state.
-    pub fn initialize_renderer(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let qml_path = get_qml_path(self.app_state).ok_or("Failed to get QML path")?;
-
-        let renderer =
-            unsafe { initialize_renderer(self.width, self.height, qml_path, self.app_state) };
-
-        if renderer.is_null() {
-            return Err("QML renderer initialization failed.".into());
-        }
+    /// This function creates a new `QmlRenderer` object for each surface, with the appropriate callbacks set for
+    /// rendering into the surface's buffers. The QML content path is obtained from the surface's output state.
+    /// The created renderers are stored in their surfaces.
+    pub fn initialize_renderers(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        for surface in self.surfaces.iter_mut() {
+            let renderer =
+                unsafe { initialize_renderer(surface.output_state.as_mut(), self.app_state) };
+
+            if renderer.is_null() {
+                return Err("QML renderer initialization failed.".into());
+            }
 
-        set_renderer(self.app_state, renderer);
+            surface.renderer = renderer;
 
-        unsafe {
-            set_callbacks(
-                renderer,
-                Self::get_buffer_callback,
-                &mut self.buffer_manager as *mut _ as *mut c_void,
-            );
+            unsafe {
+                set_callbacks(
+                    renderer,
+                    Self::get_buffer_callback,
+                    surface.buffer_manager.as_mut() as *mut BufferManager as *mut c_void,
+                );
+            }
         }
 
         Ok(())
     }
 
-    /// Clean up the renderer object and thread
-    pub fn destroy_renderer(&mut self) {
-        if let Some(renderer) = get_renderer(self.app_state) {
-            unsafe {
-                cleanup_renderer(renderer);
-                set_renderer(self.app_state, std::ptr::null_mut());
+    /// Clean up the renderer objects, and the renderer thread
+    pub fn destroy_renderers(&mut self) {
+        for surface in self.surfaces.iter_mut() {
+            if !surface.renderer.is_null() {
+                unsafe {
+                    cleanup_renderer(surface.renderer);
+                }
+                surface.renderer = std::ptr::null_mut();
             }
         }
     }
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    surface.rs:
+        Defines the ThemeSurface object, which shows the theme on a single
+        output, with its own buffers, renderer and renderer event pipe.
+*/
+
+use crate::buffer::BufferManager;
+use crate::event::{Event, EventBroadcast};
+use crate::ffi::{QmlRenderer, start_renderer};
+use crate::shared::{OutputState, Pipe};
+use crate::wayland::WaylandState;
+
+use std::os::fd::AsRawFd;
+use tracing::{error, info};
+use wayland_client::protocol::{wl_output::WlOutput, wl_surface::WlSurface};
+use wayland_protocols::{
+    ext::session_lock::v1::client::ext_session_lock_surface_v1::ExtSessionLockSurfaceV1,
+    wp::viewporter::client::wp_viewport::WpViewport,
+};
+
+/// A surface showing the theme on a single output
+///
+/// The lock screen has one `ThemeSurface` for each output, and the preview
+/// window has one for the primary output. Each has its own renderer, so the
+/// buffer manager and output state are boxed, as the renderer holds pointers
+/// to them.
+///
+/// `output_name` is the registry name of the output.
+pub struct ThemeSurface {
+    pub output_name: u32,
+    pub output: WlOutput,
+
+    pub surface: Option<WlSurface>,
+    pub viewport: Option<WpViewport>,
+    pub lock_surface: Option<ExtSessionLockSurfaceV1>,
+
+    pub buffer_manager: Box<BufferManager>,
+    pub output_state: Box<OutputState>,
+
+    pub renderer: *mut QmlRenderer,
+    /// Whether the renderer has been started, after the surface was first
+    /// configured
+    pub started: bool,
+
+    /// Pipe for events sent to this surface's renderer
+    pub renderer_write_pipe: Pipe,
+}
+
+impl ThemeSurface {
+    /// Create a new `ThemeSurface` for `output`, showing the theme described
+    /// by `output_state`
+    ///
+    /// The Wayland surface is created when locking, or opening the preview
+    /// window.
+    pub fn new(
+        output_name: u32,
+        output: WlOutput,
+        mut output_state: OutputState,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let renderer_write_pipe = Pipe::new()?;
+
+        // The renderer's read fd is the readable side of our write pipe
+        output_state.renderer_read_fd = renderer_write_pipe.read_fd().as_raw_fd();
+
+        Ok(Self {
+            output_name,
+            output,
+            surface: None,
+            viewport: None,
+            lock_surface: None,
+            buffer_manager: Box::new(BufferManager::new()),
+            output_state: Box::new(output_state),
+            renderer: std::ptr::null_mut(),
+            started: false,
+            renderer_write_pipe,
+        })
+    }
+
+    /// Returns whether this surface shows the theme on the primary output
+    pub fn primary(&self) -> bool {
+        self.output_state.details.primary
+    }
+
+    /// Send `event` to this surface's renderer
+    pub fn send(&self, event: &Event) -> Result<(), Box<dyn std::error::Error>> {
+        event.write_to(self.renderer_write_pipe.write_fd())
+    }
+
+    /// Start the renderer, if it has not been started yet
+    ///
+    /// Surfaces can be configured more than once, but the renderer is only
+    /// started on the first configure.
+    pub fn start_renderer(&mut self) {
+        if self.started {
+            return;
+        }
+
+        if self.renderer.is_null() {
+            error!("Renderer was None");
+            return;
+        }
+
+        self.started = true;
+        unsafe {
+            start_renderer(self.renderer);
+        }
+    }
+}
+
+impl WaylandState {
+    /// Add a surface showing the theme on the output with registry name
+    /// `output_name`
+    ///
+    /// The size of the output is copied into `output_state`.
+    pub fn add_surface(
+        &mut self,
+        output_name: u32,
+        mut output_state: OutputState,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let info = self
+            .outputs
+            .iter()
+            .find(|info| info.registry_name == output_name)
+            .ok_or("Output not found")?;
+
+        info!(
+            "Showing the lock screen on output {} ({}){}",
+            info.name.as_deref().unwrap_or("unknown"),
+            info.description.as_deref().unwrap_or("no description"),
+            if output_state.details.primary {
+                ", as the primary output"
+            } else {
+                ""
+            }
+        );
+
+        output_state.width = info.width;
+        output_state.height = info.height;
+
+        let surface = ThemeSurface::new(output_name, info.output.clone(), output_state)?;
+        self.surfaces.push(surface);
+
+        Ok(())
+    }
+
+    /// Returns the surface on the primary output
+    pub fn primary_surface(&mut self) -> Option<&mut ThemeSurface> {
+        self.surfaces.iter_mut().find(|surface| surface.primary())
+    }
+
+    /// Returns an `EventBroadcast` for sending events to the renderer of
+    /// every output from another thread
+    pub fn event_broadcast(&self) -> Result<EventBroadcast, Box<dyn std::error::Error>> {
+        EventBroadcast::new(
+            self.surfaces
+                .iter()
+                .map(|surface| surface.renderer_write_pipe.write_fd()),
+        )
+    }
+
+    /// Send `event` to the renderer of every output
+    ///
+    /// The event is still sent to the other renderers if one fails, and the
+    /// last error is returned.
+    pub fn broadcast(&self, event: &Event) -> Result<(), Box<dyn std::error::Error>> {
+        let mut result = Ok(());
+
+        for surface in &self.surfaces {
+            if let Err(e) = surface.send(event) {
+                result = Err(e);
+            }
+        }
+
+        result
+    }
+}
@@ -6,62 +6,71 @@
         Redirects keyboard events to Qt
 */
 
-use crate::event::{Event, EventParam, EventType};
+use crate::event::Event;
 use crate::wayland::WaylandState;
 
+use nix::unistd::dup;
 use std::os::fd::IntoRawFd;
 use wayland_client::{
     Connection, Dispatch, QueueHandle, WEnum,
     protocol::wl_keyboard::{self, KeymapFormat, WlKeyboard},
 };
 
-impl Dispatch<WlKeyboard, ()> for WaylandState {
+impl Dispatch<WlKeyboard, u32> for WaylandState {
     fn event(
         wayland_state: &mut Self,
         _proxy: &WlKeyboard,
         event: <WlKeyboard as wayland_client::Proxy>::Event,
-        _data: &(),
+        data: &u32,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
     ) {
-        // Redirect events into the Qt event loop for further processing
+        let seat = *data;
+        let Some((seat_state, sender)) = wayland_state.seat_mut(seat) else {
+            return;
+        };
+
+        // Redirect events into the Qt event loop for further processing. Key
+        // events go to the renderer of the focused output, and keyboard state
+        // is sent to every renderer
         match event {
             wl_keyboard::Event::Keymap { format, fd, size } => {
                 if format == WEnum::Value(KeymapFormat::XkbV1) {
-                    let event = Event::new(
-                        EventType::KeyboardKeymap,
-                        EventParam::from(fd.into_raw_fd() as u64),
-                        EventParam::from(size as u64),
-                    );
-                    let _ = event.write_to(
-                        wayland_state
-                            .renderer_write_pipe
-                            .as_ref()
-                            .unwrap()
-                            .write_fd(),
-                    );
+                    // Each renderer closes the keymap it receives, so each
+                    // gets its own duplicate
+                    sender.send_each(|| {
+                        dup(&fd).ok().map(|fd| Event::KeyboardKeymap {
+                            seat,
+                            fd: fd.into_raw_fd(),
+                            size,
+                        })
+                    });
                 }
             }
+            wl_keyboard::Event::Enter { surface, .. } => {
+                seat_state.keyboard_focus = Some(surface);
+            }
+            wl_keyboard::Event::Leave { surface, .. }
+                if seat_state.keyboard_focus.as_ref() == Some(&surface) =>
+            {
+                seat_state.keyboard_focus = None;
+            }
             wl_keyboard::Event::Key {
                 serial: _,
                 time: _,
                 key,
                 state,
             } => {
-                let event = Event::new(
-                    EventType::KeyboardKey,
-                    EventParam::from(key as u64),
-                    EventParam::from(match state {
-                        WEnum::Value(val) => val as u64,
-                        WEnum::Unknown(val) => val as u64,
-                    }),
-                );
-                let _ = event.write_to(
-                    wayland_state
-                        .renderer_write_pipe
-                        .as_ref()
-                        .unwrap()
-                        .write_fd(),
+                sender.send_to(
+                    seat_state.keyboard_focus.as_ref(),
+                    Event::KeyboardKey {
+                        seat,
+                        key,
+                        state: match state {
+                            WEnum::Value(val) => val as u32,
+                            WEnum::Unknown(val) => val,
+                        },
+                    },
                 );
             }
             wl_keyboard::Event::Modifiers {
@@ -71,31 +80,16 @@ impl Dispatch<WlKeyboard, ()> for WaylandState {
                 mods_locked,
                 group,
             } => {
-                let param_1 =
-                    EventParam::from(((mods_depressed as u64) << 32) | (mods_latched as u64));
-                let param_2 = EventParam::from(((mods_locked as u64) << 32) | (group as u64));
-                let event = Event::new(EventType::KeyboardModifiers, param_1, param_2);
-                let _ = event.write_to(
-                    wayland_state
-                        .renderer_write_pipe
-                        .as_ref()
-                        .unwrap()
-                        .write_fd(),
-                );
+                sender.send(Event::KeyboardModifiers {
+                    seat,
+                    depressed: mods_depressed,
+                    latched: mods_latched,
+                    locked: mods_locked,
+                    group,
+                });
             }
             wl_keyboard::Event::RepeatInfo { rate, delay } => {
-                let event = Event::new(
-                    EventType::KeyboardRepeatInfo,
-                    EventParam::from(rate as u64),
-                    EventParam::from(delay as u64),
-                );
-                let _ = event.write_to(
-                    wayland_state
-                        .renderer_write_pipe
-                        .as_ref()
-                        .unwrap()
-                        .write_fd(),
-                );
+                sender.send(Event::KeyboardRepeatInfo { seat, rate, delay });
             }
             _ => {}
         }
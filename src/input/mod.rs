@@ -1,3 +1,10 @@
 mod keyboard;
 mod pointer;
 mod seat;
+mod text_input;
+mod touch;
+
+pub use pointer::*;
+pub use seat::*;
+pub use text_input::*;
+pub use touch::*;
@@ -6,64 +6,221 @@
         Redirects pointer events to Qt
 */
 
-use crate::event::{Event, EventParam, EventType};
+use crate::event::{AXIS_SOURCE_UNKNOWN, AXIS_STOP_HORIZONTAL, AXIS_STOP_VERTICAL, Event};
+use crate::input::{InputSender, Seat};
 use crate::wayland::WaylandState;
 
 use std::time::{Duration, Instant};
 use wayland_client::{
-    Connection, Dispatch, QueueHandle, WEnum,
-    protocol::wl_pointer::{self, WlPointer},
+    Connection, Dispatch, Proxy, QueueHandle, WEnum,
+    protocol::{
+        wl_pointer::{self, Axis, WlPointer},
+        wl_surface::WlSurface,
+    },
 };
 
-impl Dispatch<WlPointer, ()> for WaylandState {
+/// Minimum time between motion events, to avoid filling the event queue
+const MOTION_INTERVAL: Duration = Duration::from_millis(1000 / 60);
+
+/// First `wl_pointer` version to group events with `frame`
+const FRAME_SINCE_VERSION: u32 = 5;
+
+/// Scrolling received over one pointer frame
+#[derive(Default, Clone, Copy)]
+struct AxisFrame {
+    source: Option<u32>,
+    horizontal: f64,
+    vertical: f64,
+    horizontal_120: i32,
+    vertical_120: i32,
+    stop: u32,
+}
+
+/// Pointer events received since the last `wl_pointer.frame`
+#[derive(Default)]
+struct PointerFrame {
+    enter: Option<(f64, f64)>,
+    motion: Option<(f64, f64)>,
+    buttons: Vec<(u32, u32)>,
+    axis: Option<AxisFrame>,
+    leave: bool,
+}
+
+/// Holds the state of the pointer between events
+///
+/// Motion is coalesced, so at most one motion event is sent every
+/// `MOTION_INTERVAL`. The last position is held in `pending_motion` until it
+/// can be sent, so it is never lost.
+///
+/// Events are sent to the renderer of `surface`, the surface the pointer last
+/// entered.
+#[derive(Default)]
+pub struct PointerState {
+    surface: Option<WlSurface>,
+    frame: PointerFrame,
+    pending_motion: Option<(f64, f64)>,
+    last_motion: Option<Instant>,
+}
+
+impl Seat {
+    /// Send `event` to the renderer of the surface the pointer is on
+    fn send_pointer(&self, sender: InputSender, event: Event) {
+        sender.send_to(self.pointer_state.surface.as_ref(), event);
+    }
+
+    /// Send the pending pointer position to the renderer
+    ///
+    /// Unless `force` is set, the position is only sent if `MOTION_INTERVAL`
+    /// has passed since the last motion event.
+    fn flush_pointer_motion(&mut self, sender: InputSender, force: bool) {
+        let Some((x, y)) = self.pointer_state.pending_motion else {
+            return;
+        };
+
+        let now = Instant::now();
+        let due = self
+            .pointer_state
+            .last_motion
+            .is_none_or(|last| now.duration_since(last) >= MOTION_INTERVAL);
+
+        if force || due {
+            self.pointer_state.pending_motion = None;
+            self.pointer_state.last_motion = Some(now);
+            self.send_pointer(
+                sender,
+                Event::PointerMotion {
+                    seat: self.id,
+                    x,
+                    y,
+                },
+            );
+        }
+    }
+
+    /// Send the events received over a pointer frame to the renderer
+    ///
+    /// Buttons, scrolling and leaving always happen at the latest pointer
+    /// position, so any pending motion is sent first.
+    fn end_pointer_frame(&mut self, sender: InputSender) {
+        let frame = std::mem::take(&mut self.pointer_state.frame);
+        let seat = self.id;
+
+        if let Some((x, y)) = frame.enter {
+            self.pointer_state.pending_motion = None;
+            self.send_pointer(sender, Event::PointerEnter { seat, x, y });
+        }
+
+        if let Some(motion) = frame.motion {
+            self.pointer_state.pending_motion = Some(motion);
+        }
+
+        let force = !frame.buttons.is_empty() || frame.axis.is_some() || frame.leave;
+        self.flush_pointer_motion(sender, force);
+
+        for (button, state) in frame.buttons {
+            self.send_pointer(
+                sender,
+                Event::PointerButton {
+                    seat,
+                    button,
+                    state,
+                },
+            );
+        }
+
+        if let Some(axis) = frame.axis {
+            self.send_pointer(
+                sender,
+                Event::PointerAxis {
+                    seat,
+                    horizontal: axis.horizontal,
+                    vertical: axis.vertical,
+                    horizontal_120: axis.horizontal_120,
+                    vertical_120: axis.vertical_120,
+                    source: axis.source.unwrap_or(AXIS_SOURCE_UNKNOWN),
+                    stop: axis.stop,
+                },
+            );
+        }
+
+        if frame.leave {
+            self.send_pointer(sender, Event::PointerLeave { seat });
+        }
+    }
+
+    /// Returns the scrolling received so far in the current pointer frame
+    fn axis_frame(&mut self) -> &mut AxisFrame {
+        self.pointer_state.frame.axis.get_or_insert_default()
+    }
+}
+
+impl WaylandState {
+    /// Send the pending pointer position of each seat, if it is due
+    ///
+    /// This is called from the event loop, so the last motion is delivered
+    /// even if the pointer stops moving.
+    pub fn update_pointer(&mut self) {
+        let sender = InputSender::new(&self.surfaces);
+        for seat in self.seats.iter_mut() {
+            seat.flush_pointer_motion(sender, false);
+        }
+    }
+}
+
+/// Convert a `WEnum` into its raw protocol value
+fn wenum_value<T: Into<u32>>(value: WEnum<T>) -> u32 {
+    match value {
+        WEnum::Value(val) => val.into(),
+        WEnum::Unknown(val) => val,
+    }
+}
+
+impl Dispatch<WlPointer, u32> for WaylandState {
     fn event(
         wayland_state: &mut Self,
-        _proxy: &WlPointer,
+        proxy: &WlPointer,
         event: <WlPointer as wayland_client::Proxy>::Event,
-        _data: &(),
+        data: &u32,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
     ) {
+        let Some((seat, sender)) = wayland_state.seat_mut(*data) else {
+            return;
+        };
+
         match event {
-            wl_pointer::Event::Motion {
-                time: _,
+            wl_pointer::Event::Enter {
+                serial: _,
+                surface,
                 surface_x,
                 surface_y,
             } => {
-                let mut send_event = false;
-                let event = Event::new(
-                    EventType::PointerMotion,
-                    EventParam::from(surface_x),
-                    EventParam::from(surface_y),
-                );
-                let now = Instant::now();
-
-                if wayland_state.pointer_timestamp.is_none() {
-                    wayland_state.pointer_timestamp = Some(now);
-                    send_event = true;
+                // Moving between outputs leaves one surface and enters another
+                // in the same frame, so the old surface is left first
+                if seat.pointer_state.frame.leave
+                    && seat.pointer_state.surface.as_ref() != Some(&surface)
+                {
+                    seat.end_pointer_frame(sender);
                 }
 
-                let timestamp = wayland_state.pointer_timestamp.unwrap();
-                let delta = now.duration_since(timestamp);
-
-                // Cap motion events at 60 events per second to avoid filling the event queue
-                if delta >= Duration::from_millis(1000 / 60) {
-                    send_event = true;
-                }
-
-                if send_event {
-                    wayland_state.pointer_timestamp = Some(now);
-
-                    let _ = event.write_to(
-                        wayland_state
-                            .renderer_write_pipe
-                            .as_ref()
-                            .unwrap()
-                            .write_fd(),
-                    );
-                } else {
-                    wayland_state.pending_pointer_event = Some(event);
-                }
+                seat.pointer_state.surface = Some(surface);
+                let frame = &mut seat.pointer_state.frame;
+                frame.enter = Some((surface_x, surface_y));
+                frame.motion = None;
+                frame.leave = false;
+            }
+            wl_pointer::Event::Leave {
+                serial: _,
+                surface: _,
+            } => {
+                seat.pointer_state.frame.leave = true;
+            }
+            wl_pointer::Event::Motion {
+                time: _,
+                surface_x,
+                surface_y,
+            } => {
+                seat.pointer_state.frame.motion = Some((surface_x, surface_y));
             }
             wl_pointer::Event::Button {
                 serial: _,
@@ -71,23 +228,63 @@ impl Dispatch<WlPointer, ()> for WaylandState {
                 button,
                 state,
             } => {
-                let event = Event::new(
-                    EventType::PointerButton,
-                    EventParam::from(button as u64),
-                    EventParam::from(match state {
-                        WEnum::Value(val) => val as u64,
-                        WEnum::Unknown(val) => val as u64,
-                    }),
-                );
-                let _ = event.write_to(
-                    wayland_state
-                        .renderer_write_pipe
-                        .as_ref()
-                        .unwrap()
-                        .write_fd(),
-                );
+                seat.pointer_state
+                    .frame
+                    .buttons
+                    .push((button, wenum_value(state)));
             }
-            _ => {}
+            wl_pointer::Event::Axis {
+                time: _,
+                axis,
+                value,
+            } => {
+                let axis_frame = seat.axis_frame();
+                match axis {
+                    WEnum::Value(Axis::VerticalScroll) => axis_frame.vertical += value,
+                    WEnum::Value(Axis::HorizontalScroll) => axis_frame.horizontal += value,
+                    _ => {}
+                }
+            }
+            wl_pointer::Event::AxisSource { axis_source } => {
+                seat.axis_frame().source = Some(wenum_value(axis_source));
+            }
+            wl_pointer::Event::AxisStop { time: _, axis } => {
+                let axis_frame = seat.axis_frame();
+                match axis {
+                    WEnum::Value(Axis::VerticalScroll) => axis_frame.stop |= AXIS_STOP_VERTICAL,
+                    WEnum::Value(Axis::HorizontalScroll) => axis_frame.stop |= AXIS_STOP_HORIZONTAL,
+                    _ => {}
+                }
+            }
+            wl_pointer::Event::AxisDiscrete { axis, discrete } => {
+                // Replaced by `AxisValue120` from version 8
+                let axis_frame = seat.axis_frame();
+                match axis {
+                    WEnum::Value(Axis::VerticalScroll) => axis_frame.vertical_120 += discrete * 120,
+                    WEnum::Value(Axis::HorizontalScroll) => {
+                        axis_frame.horizontal_120 += discrete * 120
+                    }
+                    _ => {}
+                }
+            }
+            wl_pointer::Event::AxisValue120 { axis, value120 } => {
+                let axis_frame = seat.axis_frame();
+                match axis {
+                    WEnum::Value(Axis::VerticalScroll) => axis_frame.vertical_120 += value120,
+                    WEnum::Value(Axis::HorizontalScroll) => axis_frame.horizontal_120 += value120,
+                    _ => {}
+                }
+            }
+            wl_pointer::Event::Frame => {
+                seat.end_pointer_frame(sender);
+                return;
+            }
+            _ => return,
+        }
+
+        // Before version 5, each event is a frame of its own
+        if proxy.version() < FRAME_SINCE_VERSION {
+            seat.end_pointer_frame(sender);
         }
     }
 }
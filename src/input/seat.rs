@@ -3,57 +3,236 @@
 
 /*
     seat.rs:
-        Handles seat capability events and acquires keyboard and pointer interfaces
+        Tracks seats, handles seat capability events and acquires keyboard,
+        pointer and touch interfaces for each seat
 */
 
+use crate::event::Event;
+use crate::graphics::ThemeSurface;
+use crate::input::{PointerState, TextInputState, TouchState};
 use crate::wayland::WaylandState;
 
 use tracing::debug;
 use wayland_client::{
-    Connection, Dispatch, QueueHandle, WEnum,
-    protocol::wl_seat::{self, Capability, WlSeat},
+    Connection, Dispatch, Proxy, QueueHandle, WEnum,
+    protocol::{
+        wl_keyboard::WlKeyboard,
+        wl_pointer::WlPointer,
+        wl_seat::{self, Capability, WlSeat},
+        wl_surface::WlSurface,
+        wl_touch::WlTouch,
+    },
 };
+use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3::ZwpTextInputV3;
 
-impl Dispatch<WlSeat, ()> for WaylandState {
+/// Sends input events to the renderers
+///
+/// Input events are dropped if they cannot be sent.
+#[derive(Clone, Copy)]
+pub struct InputSender<'a> {
+    surfaces: &'a [ThemeSurface],
+}
+
+impl<'a> InputSender<'a> {
+    /// Create a new `InputSender` for the renderers of `surfaces`
+    pub fn new(surfaces: &'a [ThemeSurface]) -> Self {
+        Self { surfaces }
+    }
+
+    /// Send `event` to the renderer of every output
+    pub fn send(&self, event: Event) {
+        for surface in self.surfaces {
+            let _ = surface.send(&event);
+        }
+    }
+
+    /// Send an event created by `event` to the renderer of every output
+    ///
+    /// This is used for events which cannot be shared, such as those holding
+    /// a file descriptor, which each renderer closes. Nothing is sent to a
+    /// renderer if `event` returns `None`.
+    pub fn send_each(&self, event: impl Fn() -> Option<Event>) {
+        for surface in self.surfaces {
+            if let Some(event) = event() {
+                let _ = surface.send(&event);
+            }
+        }
+    }
+
+    /// Send `event` to the renderer showing `surface`
+    ///
+    /// If the surface is not known, such as before the seat has focused a
+    /// surface, the event is sent to the renderer on the primary output.
+    pub fn send_to(&self, surface: Option<&WlSurface>, event: Event) {
+        let target = surface
+            .and_then(|surface| {
+                self.surfaces
+                    .iter()
+                    .find(|theme_surface| theme_surface.surface.as_ref() == Some(surface))
+            })
+            .or_else(|| self.surfaces.iter().find(|surface| surface.primary()));
+
+        if let Some(target) = target {
+            let _ = target.send(&event);
+        }
+    }
+}
+
+/// Holds the input devices and input state of a single seat
+///
+/// `id` is the registry name of the `wl_seat` global, which is used to
+/// identify the seat in events sent to the renderer. Key and text input
+/// events are sent to the renderer of `keyboard_focus`, the surface the
+/// seat's keyboard has entered.
+pub struct Seat {
+    pub id: u32,
+    pub seat: WlSeat,
+    pub name: Option<String>,
+
+    pub keyboard: Option<WlKeyboard>,
+    pub keyboard_focus: Option<WlSurface>,
+    pub pointer: Option<WlPointer>,
+    pub touch: Option<WlTouch>,
+    pub text_input: Option<ZwpTextInputV3>,
+
+    pub pointer_state: PointerState,
+    pub touch_state: TouchState,
+    pub text_input_state: TextInputState,
+}
+
+impl Seat {
+    /// Create a new `Seat` for a bound `wl_seat` global
+    pub fn new(id: u32, seat: WlSeat) -> Self {
+        Self {
+            id,
+            seat,
+            name: None,
+            keyboard: None,
+            keyboard_focus: None,
+            pointer: None,
+            touch: None,
+            text_input: None,
+            pointer_state: PointerState::default(),
+            touch_state: TouchState::default(),
+            text_input_state: TextInputState::default(),
+        }
+    }
+
+    /// Release all input devices and the text input held by this seat
+    pub fn release(&mut self) {
+        if let Some(keyboard) = self.keyboard.take() {
+            keyboard.release();
+        }
+
+        if let Some(pointer) = self.pointer.take() {
+            pointer.release();
+        }
+
+        if let Some(touch) = self.touch.take() {
+            touch.release();
+        }
+
+        if let Some(text_input) = self.text_input.take() {
+            text_input.destroy();
+        }
+    }
+}
+
+impl WaylandState {
+    /// Returns a sender for input events
+    pub fn input_sender(&self) -> InputSender<'_> {
+        InputSender::new(&self.surfaces)
+    }
+
+    /// Returns the seat with registry name `id`, and a sender for its events
+    pub fn seat_mut(&mut self, id: u32) -> Option<(&mut Seat, InputSender<'_>)> {
+        let sender = InputSender::new(&self.surfaces);
+
+        self.seats
+            .iter_mut()
+            .find(|seat| seat.id == id)
+            .map(|seat| (seat, sender))
+    }
+
+    /// Remove the seat with registry name `id`, after its global is removed
+    pub fn remove_seat(&mut self, id: u32) {
+        let Some(index) = self.seats.iter().position(|seat| seat.id == id) else {
+            return;
+        };
+
+        let mut seat = self.seats.remove(index);
+        seat.release();
+        if seat.seat.version() >= 5 {
+            seat.seat.release();
+        }
+
+        debug!("Removed seat {}", id);
+        self.input_sender().send(Event::SeatRemoved { seat: id });
+    }
+}
+
+impl Dispatch<WlSeat, u32> for WaylandState {
     fn event(
         state: &mut Self,
         proxy: &WlSeat,
         event: <WlSeat as wayland_client::Proxy>::Event,
-        _data: &(),
+        data: &u32,
         _conn: &Connection,
         qh: &QueueHandle<Self>,
     ) {
+        let Some((seat, sender)) = state.seat_mut(*data) else {
+            return;
+        };
+
         match event {
             wl_seat::Event::Capabilities { capabilities } => match capabilities {
                 WEnum::Value(bits) => {
-                    if bits.contains(Capability::Keyboard) && state.keyboard.is_none() {
-                        let keyboard = proxy.get_keyboard(qh, ());
-                        state.keyboard = Some(keyboard);
-                        debug!("Acquired keyboard input interface.");
+                    if bits.contains(Capability::Keyboard) && seat.keyboard.is_none() {
+                        let keyboard = proxy.get_keyboard(qh, seat.id);
+                        seat.keyboard = Some(keyboard);
+                        debug!("Acquired keyboard input interface for seat {}.", seat.id);
+                    }
+
+                    if bits.contains(Capability::Pointer) && seat.pointer.is_none() {
+                        let pointer = proxy.get_pointer(qh, seat.id);
+                        seat.pointer = Some(pointer);
+                        debug!("Acquired pointer input interface for seat {}.", seat.id);
+                    }
+
+                    if bits.contains(Capability::Touch) && seat.touch.is_none() {
+                        let touch = proxy.get_touch(qh, seat.id);
+                        seat.touch = Some(touch);
+                        debug!("Acquired touch input interface for seat {}.", seat.id);
                     }
 
-                    if bits.contains(Capability::Pointer) && state.pointer.is_none() {
-                        let pointer = proxy.get_pointer(qh, ());
-                        state.pointer = Some(pointer);
-                        debug!("Acquired pointer input interface.");
+                    if !bits.contains(Capability::Keyboard)
+                        && let Some(keyboard) = seat.keyboard.take()
+                    {
+                        keyboard.release();
                     }
 
-                    if !bits.contains(Capability::Keyboard) && state.keyboard.is_some() {
-                        if let Some(ref keyboard) = state.keyboard {
-                            keyboard.release();
-                            state.keyboard = None;
-                        }
+                    if !bits.contains(Capability::Pointer)
+                        && let Some(pointer) = seat.pointer.take()
+                    {
+                        pointer.release();
                     }
 
-                    if !bits.contains(Capability::Pointer) && state.pointer.is_some() {
-                        if let Some(ref pointer) = state.pointer {
-                            pointer.release();
-                            state.pointer = None;
-                        }
+                    if !bits.contains(Capability::Touch)
+                        && let Some(touch) = seat.touch.take()
+                    {
+                        touch.release();
                     }
                 }
                 _ => {}
             },
+            wl_seat::Event::Name { name } => {
+                debug!("Seat {} is named '{}'", seat.id, name);
+                seat.name = Some(name.clone());
+                sender.send(Event::SeatName {
+                    seat: seat.id,
+                    name,
+                });
+            }
             _ => {}
         }
     }
@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    text_input.rs:
+        Connects input methods to text fields in Qt, using `zwp_text_input_v3`
+*/
+
+use crate::event::Event;
+use crate::input::{InputSender, Seat};
+use crate::wayland::WaylandState;
+
+use tracing::debug;
+use wayland_client::{Connection, Dispatch, QueueHandle, protocol::wl_surface::WlSurface};
+use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3::{
+    self, ContentHint, ContentPurpose, ZwpTextInputV3,
+};
+
+/// A text field focused in the renderer, which accepts input method text
+///
+/// `hint` and `purpose` are `zwp_text_input_v3` content types.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextInputField {
+    pub hint: u32,
+    pub purpose: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Changes requested by the input method, applied on `zwp_text_input_v3.done`
+#[derive(Default)]
+struct TextInputFrame {
+    preedit: Option<(String, i32, i32)>,
+    commit: Option<String>,
+    delete_before: u32,
+    delete_after: u32,
+}
+
+/// Holds the text input state of a seat
+///
+/// Requests are only sent while the seat's text input focus is on the lock
+/// surface, and the text input is only enabled while a text field is focused.
+/// Text is sent to the renderer of `surface`, the surface last entered.
+#[derive(Default)]
+pub struct TextInputState {
+    surface: Option<WlSurface>,
+    focused: bool,
+    enabled: bool,
+    frame: TextInputFrame,
+}
+
+impl Seat {
+    /// Enable or disable the input method for the focused text field
+    fn update_text_input(&mut self, field: Option<TextInputField>) {
+        let Some(text_input) = &self.text_input else {
+            return;
+        };
+
+        let state = &mut self.text_input_state;
+        if !state.focused {
+            return;
+        }
+
+        match field {
+            Some(field) => {
+                if !state.enabled {
+                    text_input.enable();
+                    state.enabled = true;
+                }
+
+                text_input.set_content_type(
+                    ContentHint::from_bits_truncate(field.hint),
+                    ContentPurpose::try_from(field.purpose).unwrap_or(ContentPurpose::Normal),
+                );
+                text_input.set_cursor_rectangle(field.x, field.y, field.width, field.height);
+            }
+            None if state.enabled => {
+                text_input.disable();
+                state.enabled = false;
+            }
+            None => return,
+        }
+
+        text_input.commit();
+    }
+
+    /// Send the changes requested by the input method to the renderer
+    ///
+    /// Text is committed before the new preedit text is set, as required by
+    /// `zwp_text_input_v3.done`.
+    fn end_text_input_frame(&mut self, sender: InputSender) {
+        let frame = std::mem::take(&mut self.text_input_state.frame);
+        let surface = self.text_input_state.surface.as_ref();
+
+        if frame.commit.is_some() || frame.delete_before != 0 || frame.delete_after != 0 {
+            sender.send_to(
+                surface,
+                Event::TextInputCommit {
+                    seat: self.id,
+                    delete_before: frame.delete_before,
+                    delete_after: frame.delete_after,
+                    text: frame.commit.unwrap_or_default(),
+                },
+            );
+        }
+
+        let (text, cursor_begin, cursor_end) = frame.preedit.unwrap_or_default();
+        sender.send_to(
+            surface,
+            Event::TextInputPreedit {
+                seat: self.id,
+                cursor_begin,
+                cursor_end,
+                text,
+            },
+        );
+    }
+}
+
+impl WaylandState {
+    /// Create a text input for each seat without one
+    ///
+    /// Seats and the text input manager can be announced in any order, so
+    /// this is called when either is bound.
+    pub fn create_text_inputs(&mut self, qh: &QueueHandle<Self>) {
+        let Some(manager) = &self.text_input_manager else {
+            return;
+        };
+
+        for seat in self
+            .seats
+            .iter_mut()
+            .filter(|seat| seat.text_input.is_none())
+        {
+            seat.text_input = Some(manager.get_text_input(&seat.seat, qh, seat.id));
+            debug!("Acquired text input interface for seat {}.", seat.id);
+        }
+    }
+
+    /// Update the text field focused in the renderer, for every seat
+    ///
+    /// `field` is `None` when no text field is focused.
+    pub fn update_text_input_field(&mut self, field: Option<TextInputField>) {
+        if self.text_input_field == field {
+            return;
+        }
+
+        self.text_input_field = field;
+        for seat in self.seats.iter_mut() {
+            seat.update_text_input(field);
+        }
+    }
+}
+
+impl Dispatch<ZwpTextInputV3, u32> for WaylandState {
+    fn event(
+        wayland_state: &mut Self,
+        _proxy: &ZwpTextInputV3,
+        event: <ZwpTextInputV3 as wayland_client::Proxy>::Event,
+        data: &u32,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let field = wayland_state.text_input_field;
+        let Some((seat, sender)) = wayland_state.seat_mut(*data) else {
+            return;
+        };
+
+        match event {
+            zwp_text_input_v3::Event::Enter { surface } => {
+                seat.text_input_state.surface = Some(surface);
+                seat.text_input_state.focused = true;
+                seat.update_text_input(field);
+            }
+            zwp_text_input_v3::Event::Leave { surface: _ } => {
+                // The compositor ignores requests until the next enter, and
+                // any preedit text must be removed from the surface left
+                seat.text_input_state = TextInputState {
+                    surface: seat.text_input_state.surface.take(),
+                    ..Default::default()
+                };
+                seat.end_text_input_frame(sender);
+            }
+            zwp_text_input_v3::Event::PreeditString {
+                text,
+                cursor_begin,
+                cursor_end,
+            } => {
+                seat.text_input_state.frame.preedit =
+                    Some((text.unwrap_or_default(), cursor_begin, cursor_end));
+            }
+            zwp_text_input_v3::Event::CommitString { text } => {
+                seat.text_input_state.frame.commit = text;
+            }
+            zwp_text_input_v3::Event::DeleteSurroundingText {
+                before_length,
+                after_length,
+            } => {
+                let frame = &mut seat.text_input_state.frame;
+                frame.delete_before = before_length;
+                frame.delete_after = after_length;
+            }
+            zwp_text_input_v3::Event::Done { serial: _ } => {
+                seat.end_text_input_frame(sender);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    touch.rs:
+        Redirects touch events to Qt
+*/
+
+use crate::event::{Event, TouchPoint, TouchPointState};
+use crate::input::{InputSender, Seat};
+use crate::wayland::WaylandState;
+
+use wayland_client::{
+    Connection, Dispatch, QueueHandle,
+    protocol::{
+        wl_surface::WlSurface,
+        wl_touch::{self, WlTouch},
+    },
+};
+
+/// Holds the touch points changed since the last `wl_touch.frame`
+///
+/// Each point stays on the surface it went down on, which is held in
+/// `surfaces` until the point goes up.
+#[derive(Default)]
+pub struct TouchState {
+    frame: Vec<TouchPoint>,
+    surfaces: Vec<(i32, WlSurface)>,
+}
+
+impl TouchState {
+    /// Returns the surface touch point `id` went down on
+    fn surface(&self, id: i32) -> Option<&WlSurface> {
+        self.surfaces
+            .iter()
+            .find(|(point, _)| *point == id)
+            .map(|(_, surface)| surface)
+    }
+}
+
+impl Seat {
+    /// Send the touch points changed over a touch frame to the renderer
+    ///
+    /// The points are split by the surface they are on, and each renderer is
+    /// sent a frame with its own points.
+    fn end_touch_frame(&mut self, sender: InputSender) {
+        let mut points = std::mem::take(&mut self.touch_state.frame);
+        let up: Vec<i32> = points
+            .iter()
+            .filter(|point| point.state == TouchPointState::Up)
+            .map(|point| point.id)
+            .collect();
+
+        while !points.is_empty() {
+            let surface = self.touch_state.surface(points[0].id).cloned();
+            let (frame, rest) = points
+                .into_iter()
+                .partition(|point| self.touch_state.surface(point.id) == surface.as_ref());
+            points = rest;
+
+            sender.send_to(
+                surface.as_ref(),
+                Event::TouchFrame {
+                    seat: self.id,
+                    points: frame,
+                },
+            );
+        }
+
+        // Points which went up are finished, so forget their surfaces
+        self.touch_state.surfaces.retain(|(id, _)| !up.contains(id));
+    }
+
+    /// Add a touch point going down or up to the current touch frame
+    ///
+    /// A point can only change state once per frame, so if it has already
+    /// changed, the frame is sent first.
+    fn push_touch_point(&mut self, sender: InputSender, point: TouchPoint) {
+        if self.touch_state.frame.iter().any(|p| p.id == point.id) {
+            self.end_touch_frame(sender);
+        }
+
+        self.touch_state.frame.push(point);
+    }
+
+    /// Update the position of a touch point in the current touch frame
+    ///
+    /// Motion is coalesced, so only the last position of each point in a
+    /// frame is sent.
+    fn move_touch_point(&mut self, sender: InputSender, id: i32, x: f64, y: f64) {
+        match self.touch_state.frame.iter_mut().find(|p| p.id == id) {
+            Some(point) if point.state != TouchPointState::Up => {
+                point.x = x;
+                point.y = y;
+            }
+            _ => self.push_touch_point(
+                sender,
+                TouchPoint {
+                    id,
+                    state: TouchPointState::Motion,
+                    x,
+                    y,
+                },
+            ),
+        }
+    }
+}
+
+impl Dispatch<WlTouch, u32> for WaylandState {
+    fn event(
+        wayland_state: &mut Self,
+        _proxy: &WlTouch,
+        event: <WlTouch as wayland_client::Proxy>::Event,
+        data: &u32,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let Some((seat, sender)) = wayland_state.seat_mut(*data) else {
+            return;
+        };
+
+        match event {
+            wl_touch::Event::Down {
+                serial: _,
+                time: _,
+                surface,
+                id,
+                x,
+                y,
+            } => {
+                // Send any earlier use of this point id before it is moved
+                if seat.touch_state.frame.iter().any(|p| p.id == id) {
+                    seat.end_touch_frame(sender);
+                }
+
+                seat.touch_state.surfaces.retain(|(point, _)| *point != id);
+                seat.touch_state.surfaces.push((id, surface));
+                seat.push_touch_point(
+                    sender,
+                    TouchPoint {
+                        id,
+                        state: TouchPointState::Down,
+                        x,
+                        y,
+                    },
+                );
+            }
+            wl_touch::Event::Up {
+                serial: _,
+                time: _,
+                id,
+            } => {
+                seat.push_touch_point(
+                    sender,
+                    TouchPoint {
+                        id,
+                        state: TouchPointState::Up,
+                        x: 0.0,
+                        y: 0.0,
+                    },
+                );
+            }
+            wl_touch::Event::Motion { time: _, id, x, y } => {
+                seat.move_touch_point(sender, id, x, y);
+            }
+            wl_touch::Event::Frame => {
+                seat.end_touch_frame(sender);
+            }
+            wl_touch::Event::Cancel => {
+                // Every active point is cancelled, including any in this frame
+                seat.touch_state.frame.clear();
+                seat.touch_state.surfaces.clear();
+                sender.send(Event::TouchCancel { seat: seat.id });
+            }
+            _ => {}
+        }
+    }
+}
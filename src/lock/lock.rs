@@ -6,8 +6,7 @@
         Manages the state of the screen lock, and associated surfaces.
 */
 
-use crate::ffi::get_renderer;
-use crate::ffi::{set_state, start_renderer};
+use crate::ffi::set_state;
 use crate::shared::State;
 use crate::wayland::WaylandState;
 
@@ -21,9 +20,9 @@ use wayland_protocols::ext::session_lock::v1::client::{
 impl WaylandState {
     /// Try to lock the screen using the `ext_session_lock_v1` protocol
     ///
-    /// This function creates a surface to use as a lock surface.
+    /// This function creates a surface to use as a lock surface on each output.
     /// Using the lock manager obtained bound from the registry, lock the screen.
-    /// The surface is then set as a lock surface by various dispatch functions.
+    /// The surfaces are then set as lock surfaces by various dispatch functions.
     pub fn lock(
         &mut self,
         event_queue: &EventQueue<Self>,
@@ -31,10 +30,12 @@ impl WaylandState {
         let qh = event_queue.handle();
 
         if let (Some(compositor), Some(viewporter)) = (&self.compositor, &self.viewporter) {
-            let surface = compositor.create_surface(&qh, ());
-            let viewport = viewporter.get_viewport(&surface, &qh, ());
-            self.surface = Some(surface);
-            self.viewport = Some(viewport);
+            for theme_surface in self.surfaces.iter_mut() {
+                let surface = compositor.create_surface(&qh, ());
+                let viewport = viewporter.get_viewport(&surface, &qh, ());
+                theme_surface.surface = Some(surface);
+                theme_surface.viewport = Some(viewport);
+            }
         }
 
         if let Some(session_lock_manager) = &self.session_lock_manager {
@@ -55,12 +56,20 @@ impl WaylandState {
 
         session_lock.unlock_and_destroy();
 
-        if let Some(session_lock_surface) = &self.session_lock_surface {
-            session_lock_surface.destroy();
-        } else {
+        let mut lock_surfaces = self
+            .surfaces
+            .iter_mut()
+            .filter_map(|surface| surface.lock_surface.take())
+            .peekable();
+
+        if lock_surfaces.peek().is_none() {
             warn!("Session lock surface object not set");
         }
 
+        for lock_surface in lock_surfaces {
+            lock_surface.destroy();
+        }
+
         let display = self
             .display
             .as_ref()
@@ -75,7 +84,7 @@ impl WaylandState {
 
     /// Unlock the session
     ///
-    /// This function unlocks the session and destroys the lock surface.
+    /// This function unlocks the session and destroys the lock surfaces.
     /// It also sets the application state to unlocked, to exit the event
     /// loop.
     pub fn unlock(
@@ -101,10 +110,15 @@ impl Dispatch<ExtSessionLockV1, ()> for WaylandState {
             ext_session_lock_v1::Event::Locked => {
                 info!("Session is locked");
                 set_state(state.app_state, State::Locked);
-                if let Some(surface) = &state.surface {
-                    if let Some(output) = &state.output {
-                        let lock_surface = proxy.get_lock_surface(surface, output, &qh, ());
-                        state.session_lock_surface = Some(lock_surface);
+                for theme_surface in state.surfaces.iter_mut() {
+                    if let Some(surface) = &theme_surface.surface {
+                        let lock_surface = proxy.get_lock_surface(
+                            surface,
+                            &theme_surface.output,
+                            &qh,
+                            theme_surface.output_name,
+                        );
+                        theme_surface.lock_surface = Some(lock_surface);
                     }
                 }
             }
@@ -119,12 +133,12 @@ impl Dispatch<ExtSessionLockV1, ()> for WaylandState {
     }
 }
 
-impl Dispatch<ExtSessionLockSurfaceV1, ()> for WaylandState {
+impl Dispatch<ExtSessionLockSurfaceV1, u32> for WaylandState {
     fn event(
         state: &mut Self,
         proxy: &ExtSessionLockSurfaceV1,
         event: <ExtSessionLockSurfaceV1 as wayland_client::Proxy>::Event,
-        _data: &(),
+        output_name: &u32,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
     ) {
@@ -136,13 +150,13 @@ impl Dispatch<ExtSessionLockSurfaceV1, ()> for WaylandState {
             } => {
                 proxy.ack_configure(serial);
 
-                unsafe {
-                    if let Some(renderer) = get_renderer(state.app_state) {
-                        start_renderer(renderer);
-                    } else {
-                        error!("Renderer was None\n");
-                        return;
-                    }
+                // Each output's renderer is started once its lock surface is configured
+                if let Some(surface) = state
+                    .surfaces
+                    .iter_mut()
+                    .find(|surface| surface.output_name == *output_name)
+                {
+                    surface.start_renderer();
                 }
             }
             _ => {}
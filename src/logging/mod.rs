@@ -0,0 +1,3 @@
+mod subscriber;
+
+pub use subscriber::*;
@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    subscriber.rs:
+        Sets up the `tracing` subscriber used for all application logs.
+*/
+
+use crate::config::LogConfig;
+
+use std::{fs::OpenOptions, sync::Mutex};
+use tracing::{Level, warn};
+use tracing_subscriber::{
+    filter::LevelFilter, fmt, fmt::time::uptime, layer::SubscriberExt, util::SubscriberInitExt,
+};
+
+/// Identifier used for journald log entries
+pub const SYSLOG_IDENTIFIER: &str = "tlockr";
+
+/// Initialize the global `tracing` subscriber
+///
+/// Logs are always written to stderr, and optionally to a log file and to
+/// journald, as set in `config`. Messages logged through the `log` crate by
+/// dependencies are also forwarded to the subscriber.
+///
+/// Failing to open a log output is not fatal, a warning is logged instead.
+pub fn init_logging(level: Level, config: &LogConfig) {
+    let mut errors: Vec<String> = Vec::new();
+
+    let file_layer = config.file.as_ref().and_then(|path| {
+        match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => Some(
+                fmt::layer()
+                    .with_timer(uptime())
+                    .with_ansi(false)
+                    .with_writer(Mutex::new(file)),
+            ),
+            Err(e) => {
+                errors.push(format!(
+                    "Failed to open log file '{}': {}",
+                    path.display(),
+                    e
+                ));
+                None
+            }
+        }
+    });
+
+    let journald_layer = if config.journald.unwrap_or(false) {
+        match tracing_journald::layer() {
+            Ok(layer) => Some(layer.with_syslog_identifier(SYSLOG_IDENTIFIER.to_string())),
+            Err(e) => {
+                errors.push(format!("Failed to connect to journald: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    tracing_subscriber::registry()
+        .with(LevelFilter::from_level(level))
+        .with(fmt::layer().with_timer(uptime()))
+        .with(file_layer)
+        .with(journald_layer)
+        .init();
+
+    for e in errors {
+        warn!("{}", e);
+    }
+}
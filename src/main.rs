@@ -6,8 +6,11 @@
         Entry point for the application.
 */
 
+pub mod appearance;
 pub mod auth;
+pub mod background;
 pub mod buffer;
+pub mod capture;
 pub mod cli;
 pub mod config;
 pub mod event;
@@ -15,10 +18,18 @@ pub mod ffi;
 pub mod graphics;
 pub mod input;
 pub mod lock;
+pub mod logging;
+pub mod media;
+pub mod notification;
+pub mod preview;
+pub mod provider;
 pub mod registry;
 pub mod shared;
 pub mod wayland;
 
+#[cfg(test)]
+mod testing;
+
 use cli::start;
 
 fn main() {
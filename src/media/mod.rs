@@ -0,0 +1,7 @@
+mod mpris;
+mod state;
+mod watcher;
+
+pub use mpris::*;
+pub use state::*;
+pub use watcher::*;
@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    mpris.rs:
+        D-Bus interfaces of MPRIS media players, and reading their status
+*/
+
+use crate::event::{
+    MEDIA_CAN_GO_NEXT, MEDIA_CAN_GO_PREVIOUS, MEDIA_CAN_PAUSE, MEDIA_CAN_PLAY, MediaPlaybackStatus,
+};
+use crate::shared::truncate_text;
+
+use std::collections::HashMap;
+use zbus::blocking::{Connection, fdo::PropertiesProxy};
+use zbus::names::{BusName, InterfaceName};
+use zbus::proxy;
+use zbus::zvariant::OwnedValue;
+
+/// Prefix of the bus names of MPRIS media players
+pub const MPRIS_NAME_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+/// Object path of MPRIS media players
+pub const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+
+/// Interface with the name of the player
+const MPRIS_ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+
+/// Interface with the playback status and controls of the player
+const MPRIS_PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// Maximum length of each text field sent to the renderer, in bytes
+///
+/// This keeps `MediaStatus` events within the maximum message size.
+const MAX_TEXT_LENGTH: usize = 512;
+
+/// Playback controls of an MPRIS media player
+///
+/// Controls are sent without waiting for a reply, so an unresponsive player
+/// cannot stall the caller.
+#[proxy(
+    interface = "org.mpris.MediaPlayer2.Player",
+    default_path = "/org/mpris/MediaPlayer2",
+    gen_async = false,
+    blocking_name = "PlayerProxy"
+)]
+pub trait Player {
+    #[zbus(no_reply)]
+    fn play_pause(&self) -> zbus::Result<()>;
+
+    #[zbus(no_reply)]
+    fn play(&self) -> zbus::Result<()>;
+
+    #[zbus(no_reply)]
+    fn pause(&self) -> zbus::Result<()>;
+
+    #[zbus(no_reply)]
+    fn next(&self) -> zbus::Result<()>;
+
+    #[zbus(no_reply)]
+    fn previous(&self) -> zbus::Result<()>;
+}
+
+/// Status of a media player, as shown by themes
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MediaInfo {
+    pub player: String,
+    pub status: MediaPlaybackStatus,
+    pub capabilities: u32,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub art_url: String,
+}
+
+/// Returns a string property, or an empty string if it is missing
+fn string_value(properties: &HashMap<String, OwnedValue>, key: &str) -> String {
+    properties
+        .get(key)
+        .and_then(|value| <&str>::try_from(value).ok())
+        .map(|value| truncate_text(value.to_string(), MAX_TEXT_LENGTH))
+        .unwrap_or_default()
+}
+
+/// Returns a boolean property, or false if it is missing
+fn bool_value(properties: &HashMap<String, OwnedValue>, key: &str) -> bool {
+    properties
+        .get(key)
+        .and_then(|value| bool::try_from(value).ok())
+        .unwrap_or(false)
+}
+
+/// Returns a list of strings property joined with commas
+///
+/// Some players send a single string instead of a list, which is also
+/// accepted.
+fn string_list_value(properties: &HashMap<String, OwnedValue>, key: &str) -> String {
+    let Some(value) = properties.get(key) else {
+        return String::new();
+    };
+
+    if let Ok(value) = <&str>::try_from(value) {
+        return truncate_text(value.to_string(), MAX_TEXT_LENGTH);
+    }
+
+    value
+        .try_clone()
+        .ok()
+        .and_then(|value| Vec::<String>::try_from(value).ok())
+        .map(|values| truncate_text(values.join(", "), MAX_TEXT_LENGTH))
+        .unwrap_or_default()
+}
+
+/// Read the status of the media player owning `name`
+///
+/// The player is named by its `Identity`, or its bus name if it has none.
+/// Players that cannot be controlled report no capabilities.
+pub fn read_media_info(connection: &Connection, name: &BusName) -> zbus::Result<MediaInfo> {
+    let properties = PropertiesProxy::builder(connection)
+        .destination(name.to_owned())?
+        .path(MPRIS_PATH)?
+        .cache_properties(zbus::proxy::CacheProperties::No)
+        .build()?;
+
+    let root = properties.get_all(InterfaceName::from_static_str_unchecked(
+        MPRIS_ROOT_INTERFACE,
+    ))?;
+    let player = properties.get_all(InterfaceName::from_static_str_unchecked(
+        MPRIS_PLAYER_INTERFACE,
+    ))?;
+
+    let metadata = player
+        .get("Metadata")
+        .and_then(|value| value.try_clone().ok())
+        .and_then(|value| HashMap::<String, OwnedValue>::try_from(value).ok())
+        .unwrap_or_default();
+
+    let status = match string_value(&player, "PlaybackStatus").as_str() {
+        "Playing" => MediaPlaybackStatus::Playing,
+        "Paused" => MediaPlaybackStatus::Paused,
+        _ => MediaPlaybackStatus::Stopped,
+    };
+
+    let mut capabilities = 0;
+    if bool_value(&player, "CanControl") {
+        for (key, flag) in [
+            ("CanPlay", MEDIA_CAN_PLAY),
+            ("CanPause", MEDIA_CAN_PAUSE),
+            ("CanGoNext", MEDIA_CAN_GO_NEXT),
+            ("CanGoPrevious", MEDIA_CAN_GO_PREVIOUS),
+        ] {
+            if bool_value(&player, key) {
+                capabilities |= flag;
+            }
+        }
+    }
+
+    let mut identity = string_value(&root, "Identity");
+    if identity.is_empty() {
+        identity = name
+            .strip_prefix(MPRIS_NAME_PREFIX)
+            .unwrap_or(name)
+            .to_string();
+    }
+
+    Ok(MediaInfo {
+        player: identity,
+        status,
+        capabilities,
+        title: string_value(&metadata, "xesam:title"),
+        artist: string_list_value(&metadata, "xesam:artist"),
+        album: string_value(&metadata, "xesam:album"),
+        art_url: string_value(&metadata, "mpris:artUrl"),
+    })
+}
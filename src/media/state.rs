@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    state.rs:
+        Starts the media player watcher, and controls the active player
+*/
+
+use crate::config::MediaConfig;
+use crate::event::MediaAction;
+use crate::media::{ActivePlayer, MediaWatcher, PlayerProxy};
+use crate::wayland::WaylandState;
+
+use std::sync::mpsc::{Sender, channel};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+use zbus::blocking::{Connection, connection};
+use zbus::proxy::CacheProperties;
+
+/// How long to wait for a reply from the bus or a media player
+///
+/// A player that does not reply in time is skipped, so it cannot stall the
+/// watcher.
+const MEDIA_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Sends control actions from the renderer to the media controller thread
+pub struct MediaState {
+    actions: Sender<MediaAction>,
+    controls: bool,
+}
+
+/// Send `action` to the active player
+fn control(
+    connection: &Connection,
+    active: &ActivePlayer,
+    action: MediaAction,
+) -> zbus::Result<()> {
+    let Some(name) = active.lock().unwrap().clone() else {
+        debug!("No media player to send {:?} to", action);
+        return Ok(());
+    };
+
+    let player = PlayerProxy::builder(connection)
+        .destination(name)?
+        .cache_properties(CacheProperties::No)
+        .build()?;
+
+    match action {
+        MediaAction::PlayPause => player.play_pause(),
+        MediaAction::Play => player.play(),
+        MediaAction::Pause => player.pause(),
+        MediaAction::Next => player.next(),
+        MediaAction::Previous => player.previous(),
+    }
+}
+
+impl WaylandState {
+    /// Connect to the session bus, and start watching and controlling media
+    /// players on separate threads
+    ///
+    /// Without a session bus, no players are shown. Both threads run until
+    /// the process exits, so the Wayland event loop never waits for the bus.
+    pub fn start_media(&mut self, config: &MediaConfig) -> Result<(), Box<dyn std::error::Error>> {
+        let connection = match connection::Builder::session()
+            .and_then(|builder| builder.method_timeout(MEDIA_TIMEOUT).build())
+        {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!(
+                    "Media players unavailable, failed to connect to the session bus: {}",
+                    e
+                );
+                return Ok(());
+            }
+        };
+
+        let renderers = self.event_broadcast()?;
+
+        let active = ActivePlayer::default();
+        let controls = config.controls.unwrap_or(true);
+        let watcher = MediaWatcher::new(connection.clone(), renderers, active.clone(), controls);
+
+        std::thread::spawn(move || {
+            info!("Media watcher thread started");
+
+            if let Err(e) = watcher.run() {
+                error!("{:?}", e);
+            }
+
+            info!("Media watcher thread exited");
+        });
+
+        let (actions, receiver) = channel();
+        std::thread::spawn(move || {
+            for action in receiver {
+                if let Err(e) = control(&connection, &active, action) {
+                    warn!("Failed to send {:?} to media player: {}", action, e);
+                }
+            }
+        });
+
+        self.media = Some(MediaState { actions, controls });
+
+        Ok(())
+    }
+
+    /// Send a control action from the renderer to the active media player
+    pub fn handle_media_action(&self, action: MediaAction) {
+        let Some(media) = &self.media else {
+            return;
+        };
+
+        if !media.controls {
+            warn!("Ignoring {:?} media action, controls are disabled", action);
+            return;
+        }
+
+        if media.actions.send(action).is_err() {
+            warn!(
+                "Failed to send {:?} to media player, controls stopped",
+                action
+            );
+        }
+    }
+}
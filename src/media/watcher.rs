@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    watcher.rs:
+        Watches the session bus for MPRIS media players, and sends the status
+        of the active player to the renderers
+*/
+
+use crate::event::{Event, EventBroadcast};
+use crate::media::{MPRIS_NAME_PREFIX, MediaInfo, read_media_info};
+
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+use zbus::MatchRule;
+use zbus::blocking::{Connection, MessageIterator, fdo::DBusProxy};
+use zbus::message::Type;
+use zbus::names::OwnedBusName;
+
+/// The bus name of the active media player, shared with the main thread
+pub type ActivePlayer = Arc<Mutex<Option<OwnedBusName>>>;
+
+/// Watches media players, running on its own thread
+///
+/// The active player is the player with the highest playback status, so a
+/// playing player is preferred over a paused one. The current active player
+/// is kept if another player has the same status.
+pub struct MediaWatcher {
+    connection: Connection,
+    renderers: EventBroadcast,
+    active: ActivePlayer,
+    controls: bool,
+    last: Option<Option<MediaInfo>>,
+}
+
+impl MediaWatcher {
+    /// Create a new `MediaWatcher`
+    ///
+    /// If `controls` is false, players are reported without any capabilities.
+    pub fn new(
+        connection: Connection,
+        renderers: EventBroadcast,
+        active: ActivePlayer,
+        controls: bool,
+    ) -> Self {
+        Self {
+            connection,
+            renderers,
+            active,
+            controls,
+            last: None,
+        }
+    }
+
+    /// Returns a match rule for signals about media players
+    ///
+    /// The first argument of both `NameOwnerChanged` and `PropertiesChanged`
+    /// is in the MPRIS namespace, so one rule matches players appearing,
+    /// disappearing and changing.
+    fn match_rule() -> zbus::Result<MatchRule<'static>> {
+        Ok(MatchRule::builder()
+            .msg_type(Type::Signal)
+            .arg0ns(MPRIS_NAME_PREFIX.trim_end_matches('.'))?
+            .build())
+    }
+
+    /// Find the active player, and send its status to the renderer if it
+    /// changed
+    fn refresh(&mut self, dbus: &DBusProxy) -> zbus::Result<()> {
+        let mut names = dbus
+            .list_names()?
+            .into_iter()
+            .filter(|name| name.starts_with(MPRIS_NAME_PREFIX))
+            .collect::<Vec<_>>();
+        names.sort();
+
+        let current = self.active.lock().unwrap().clone();
+        let mut best: Option<(OwnedBusName, MediaInfo)> = None;
+
+        for name in names {
+            let info = match read_media_info(&self.connection, &name) {
+                Ok(info) => info,
+                Err(e) => {
+                    debug!("Failed to read media player {}: {}", name, e);
+                    continue;
+                }
+            };
+
+            let better = match &best {
+                None => true,
+                Some((_, best_info)) => {
+                    info.status > best_info.status
+                        || (info.status == best_info.status && current.as_ref() == Some(&name))
+                }
+            };
+
+            if better {
+                best = Some((name, info));
+            }
+        }
+
+        let (name, mut info) = best.unzip();
+        if !self.controls
+            && let Some(info) = info.as_mut()
+        {
+            info.capabilities = 0;
+        }
+
+        if current != name {
+            debug!("Active media player changed to {:?}", name);
+            *self.active.lock().unwrap() = name;
+        }
+
+        if self.last.as_ref() != Some(&info) {
+            self.send(info.as_ref());
+            self.last = Some(info);
+        }
+
+        Ok(())
+    }
+
+    /// Send the status of the active player to the renderers
+    fn send(&self, info: Option<&MediaInfo>) {
+        let default = MediaInfo::default();
+        let media = info.unwrap_or(&default);
+
+        let event = Event::MediaStatus {
+            available: info.is_some(),
+            status: media.status,
+            capabilities: media.capabilities,
+            player: media.player.clone(),
+            title: media.title.clone(),
+            artist: media.artist.clone(),
+            album: media.album.clone(),
+            art_url: media.art_url.clone(),
+        };
+
+        if let Err(e) = self.renderers.send(&event) {
+            warn!("Failed to send media status: {}", e);
+        }
+    }
+
+    /// Find the active player as with `refresh`, logging any error
+    ///
+    /// The bus can time out while busy, which is not fatal, so the watcher
+    /// tries again on the next signal.
+    fn try_refresh(&mut self, dbus: &DBusProxy) {
+        if let Err(e) = self.refresh(dbus) {
+            warn!("Failed to refresh media players: {}", e);
+        }
+    }
+
+    /// Watch players until the connection is closed
+    pub fn run(mut self) -> zbus::Result<()> {
+        let dbus = DBusProxy::new(&self.connection)?;
+        let messages =
+            MessageIterator::for_match_rule(Self::match_rule()?, &self.connection, None)?;
+
+        self.try_refresh(&dbus);
+
+        for message in messages {
+            message?;
+            self.try_refresh(&dbus);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,4 @@
+mod monitor;
+mod state;
+
+pub use monitor::*;
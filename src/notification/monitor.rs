@@ -0,0 +1,280 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    monitor.rs:
+        Monitors notifications sent on the session bus, and sends them to the
+        renderers
+*/
+
+use crate::config::NotificationPrivacy;
+use crate::event::{Event, EventBroadcast};
+use crate::shared::truncate_text;
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use tracing::{debug, warn};
+use zbus::MatchRule;
+use zbus::blocking::{Connection, MessageIterator, fdo::MonitoringProxy};
+use zbus::message::{Message, Type};
+use zbus::names::OwnedUniqueName;
+use zbus::zvariant::OwnedValue;
+
+/// Interface implemented by notification servers
+pub const NOTIFICATIONS_INTERFACE: &str = "org.freedesktop.Notifications";
+
+/// Maximum length of each text field sent to the renderer, in bytes
+///
+/// This keeps `Notification` events within the maximum message size.
+const MAX_TEXT_LENGTH: usize = 1024;
+
+/// Maximum number of notifications remembered, as in the renderer
+///
+/// Older notifications are forgotten, so a notification replacing one of them
+/// is shown as a new notification.
+const MAX_NOTIFICATIONS: usize = 100;
+
+/// Arguments of `org.freedesktop.Notifications.Notify`
+type NotifyArgs = (
+    String,
+    u32,
+    String,
+    String,
+    String,
+    Vec<String>,
+    HashMap<String, OwnedValue>,
+    i32,
+);
+
+/// Watches `Notify` calls to the notification server, running on its own
+/// thread
+///
+/// The monitor only listens, notifications are still handled by the
+/// notification server. Each notification is given a key, which is reused
+/// when a later notification replaces it.
+pub struct NotificationMonitor {
+    connection: Connection,
+    renderers: EventBroadcast,
+    privacy: NotificationPrivacy,
+    next_key: u32,
+    /// Keys of `Notify` calls waiting for a reply, by caller and serial
+    pending: HashMap<(OwnedUniqueName, u32), u32>,
+    /// Keys of notifications, by the ID given by the notification server
+    keys: HashMap<u32, u32>,
+}
+
+/// Returns the application name, summary and body of a notification, with
+/// the text hidden by `privacy` replaced by empty strings
+fn hide_text(
+    privacy: NotificationPrivacy,
+    app_name: String,
+    summary: String,
+    body: String,
+) -> (String, String, String) {
+    match privacy {
+        NotificationPrivacy::Count => Default::default(),
+        NotificationPrivacy::App => (app_name, String::new(), String::new()),
+        NotificationPrivacy::Full => (app_name, summary, body),
+    }
+}
+
+/// Insert `key` into `map`, then forget the oldest keys beyond
+/// `MAX_NOTIFICATIONS`
+///
+/// Keys are given out in increasing order, so the smallest is the oldest.
+fn insert_key<K: Eq + Hash + Clone>(map: &mut HashMap<K, u32>, id: K, key: u32) {
+    map.insert(id, key);
+
+    while map.len() > MAX_NOTIFICATIONS {
+        let Some(oldest) = map
+            .iter()
+            .min_by_key(|(_, key)| **key)
+            .map(|(id, _)| id.clone())
+        else {
+            break;
+        };
+        map.remove(&oldest);
+    }
+}
+
+impl NotificationMonitor {
+    /// Create a new `NotificationMonitor`
+    ///
+    /// `connection` is used only for monitoring, as a monitor connection
+    /// cannot send messages.
+    pub fn new(
+        connection: Connection,
+        renderers: EventBroadcast,
+        privacy: NotificationPrivacy,
+    ) -> Self {
+        Self {
+            connection,
+            renderers,
+            privacy,
+            next_key: 0,
+            pending: HashMap::new(),
+            keys: HashMap::new(),
+        }
+    }
+
+    /// Returns the match rules for `Notify` calls and the replies to them
+    ///
+    /// Replies cannot be matched by member, so every reply and error from
+    /// the notification server is matched.
+    fn match_rules() -> zbus::Result<[MatchRule<'static>; 3]> {
+        Ok([
+            MatchRule::builder()
+                .msg_type(Type::MethodCall)
+                .interface(NOTIFICATIONS_INTERFACE)?
+                .member("Notify")?
+                .build(),
+            MatchRule::builder()
+                .msg_type(Type::MethodReturn)
+                .sender(NOTIFICATIONS_INTERFACE)?
+                .build(),
+            MatchRule::builder()
+                .msg_type(Type::Error)
+                .sender(NOTIFICATIONS_INTERFACE)?
+                .build(),
+        ])
+    }
+
+    /// Handle a `Notify` call, sending the notification to the renderer
+    fn handle_notify(&mut self, message: &Message) -> zbus::Result<()> {
+        let header = message.header();
+        let (app_name, replaces_id, _, summary, body, _, _, _): NotifyArgs =
+            message.body().deserialize()?;
+
+        let key = match self.keys.get(&replaces_id) {
+            Some(key) if replaces_id != 0 => *key,
+            _ => {
+                self.next_key = self.next_key.wrapping_add(1);
+                self.next_key
+            }
+        };
+
+        if let Some(sender) = header.sender() {
+            let serial = header.primary().serial_num().get();
+            insert_key(&mut self.pending, (sender.to_owned().into(), serial), key);
+        }
+
+        debug!("Received notification {}", key);
+
+        let (app_name, summary, body) = hide_text(self.privacy, app_name, summary, body);
+
+        let event = Event::Notification {
+            key,
+            app_name: truncate_text(app_name, MAX_TEXT_LENGTH),
+            summary: truncate_text(summary, MAX_TEXT_LENGTH),
+            body: truncate_text(body, MAX_TEXT_LENGTH),
+        };
+
+        if let Err(e) = self.renderers.send(&event) {
+            warn!("Failed to send notification: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Handle a reply from the notification server, remembering the ID it
+    /// gave to a notification
+    ///
+    /// An error reply only forgets the `Notify` call it answers.
+    fn handle_reply(&mut self, message: &Message) -> zbus::Result<()> {
+        let header = message.header();
+        let (Some(destination), Some(serial)) = (header.destination(), header.reply_serial())
+        else {
+            return Ok(());
+        };
+
+        let Ok(caller) = OwnedUniqueName::try_from(destination.to_string()) else {
+            return Ok(());
+        };
+
+        let Some(key) = self.pending.remove(&(caller, serial.get())) else {
+            return Ok(());
+        };
+
+        if message.message_type() == Type::MethodReturn {
+            let (id,): (u32,) = message.body().deserialize()?;
+            insert_key(&mut self.keys, id, key);
+        }
+
+        Ok(())
+    }
+
+    /// Watch notifications until the connection is closed
+    pub fn run(mut self) -> zbus::Result<()> {
+        // Messages are only queued for iterators that already exist
+        let messages = MessageIterator::from(&self.connection);
+
+        MonitoringProxy::new(&self.connection)?.become_monitor(&Self::match_rules()?, 0)?;
+
+        for message in messages {
+            let message = message?;
+
+            let result = match message.message_type() {
+                Type::MethodCall => self.handle_notify(&message),
+                Type::MethodReturn | Type::Error => self.handle_reply(&message),
+                _ => Ok(()),
+            };
+
+            if let Err(e) = result {
+                debug!("Ignoring malformed notification message: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the text of a notification, as shown at `privacy`
+    fn shown(privacy: NotificationPrivacy) -> (String, String, String) {
+        hide_text(
+            privacy,
+            "App".to_string(),
+            "Summary".to_string(),
+            "Body".to_string(),
+        )
+    }
+
+    #[test]
+    fn count_privacy_hides_all_text() {
+        assert_eq!(
+            shown(NotificationPrivacy::Count),
+            (String::new(), String::new(), String::new())
+        );
+    }
+
+    #[test]
+    fn app_privacy_shows_only_app_name() {
+        assert_eq!(
+            shown(NotificationPrivacy::App),
+            ("App".to_string(), String::new(), String::new())
+        );
+    }
+
+    #[test]
+    fn full_privacy_shows_all_text() {
+        assert_eq!(
+            shown(NotificationPrivacy::Full),
+            ("App".to_string(), "Summary".to_string(), "Body".to_string())
+        );
+    }
+
+    #[test]
+    fn oldest_keys_are_forgotten() {
+        let mut keys = HashMap::new();
+        for key in 1..=MAX_NOTIFICATIONS as u32 + 10 {
+            insert_key(&mut keys, key * 2, key);
+        }
+
+        assert_eq!(keys.len(), MAX_NOTIFICATIONS);
+        assert!(!keys.contains_key(&20));
+        assert_eq!(keys.get(&22), Some(&11));
+    }
+}
@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    state.rs:
+        Starts the notification monitor
+*/
+
+use crate::config::NotificationConfig;
+use crate::notification::NotificationMonitor;
+use crate::wayland::WaylandState;
+
+use tracing::{error, info, warn};
+use zbus::blocking::Connection;
+
+impl WaylandState {
+    /// Connect to the session bus, and start monitoring notifications on a
+    /// separate thread
+    ///
+    /// Without a session bus, no notifications are shown. The monitor runs
+    /// until the process exits.
+    pub fn start_notifications(
+        &mut self,
+        config: &NotificationConfig,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let connection = match Connection::session() {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!(
+                    "Notifications unavailable, failed to connect to the session bus: {}",
+                    e
+                );
+                return Ok(());
+            }
+        };
+
+        let renderers = self.event_broadcast()?;
+
+        let privacy = config.privacy.unwrap_or_default();
+        let monitor = NotificationMonitor::new(connection, renderers, privacy);
+
+        std::thread::spawn(move || {
+            info!("Notification monitor thread started");
+
+            if let Err(e) = monitor.run() {
+                error!("{:?}", e);
+            }
+
+            info!("Notification monitor thread exited");
+        });
+
+        Ok(())
+    }
+}
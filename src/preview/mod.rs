@@ -0,0 +1,5 @@
+mod state;
+mod watch;
+mod window;
+
+pub use state::*;
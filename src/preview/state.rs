@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    state.rs:
+        Defines the `PreviewState` object, which holds the state of a theme
+        preview window.
+*/
+
+use crate::config::PreviewConfig;
+
+use nix::sys::inotify::Inotify;
+use std::time::Instant;
+use wayland_protocols::xdg::shell::client::{
+    xdg_surface::XdgSurface, xdg_toplevel::XdgToplevel, xdg_wm_base::XdgWmBase,
+};
+
+/// Holds the state of a theme preview
+///
+/// In preview mode, the theme is displayed in an `xdg_toplevel` window rather
+/// than on a session lock surface, and is reloaded when the theme's files
+/// change.
+pub struct PreviewState {
+    pub width: i32,
+    pub height: i32,
+
+    pub wm_base: Option<XdgWmBase>,
+    pub xdg_surface: Option<XdgSurface>,
+    pub toplevel: Option<XdgToplevel>,
+
+    pub watcher: Option<Inotify>,
+    pub reload_at: Option<Instant>,
+}
+
+impl PreviewState {
+    /// Create a new `PreviewState` from a `PreviewConfig`
+    pub fn new(config: &PreviewConfig) -> Self {
+        Self {
+            width: config.width,
+            height: config.height,
+            wm_base: None,
+            xdg_surface: None,
+            toplevel: None,
+            watcher: None,
+            reload_at: None,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    watch.rs:
+        Watches theme files with inotify, and asks the renderer to reload the
+        theme when they change.
+*/
+
+use crate::event::Event;
+use crate::wayland::WaylandState;
+
+use nix::errno::Errno;
+use nix::sys::inotify::{AddWatchFlags, InitFlags, Inotify};
+use std::{
+    os::fd::{AsFd, AsRawFd, RawFd},
+    path::Path,
+    time::{Duration, Instant},
+};
+use tracing::{debug, info, warn};
+
+/// Time to wait after the last file change before reloading
+///
+/// Editors often write several events when saving a file, this avoids
+/// reloading the theme for each of them.
+pub const RELOAD_DELAY: Duration = Duration::from_millis(100);
+
+/// Returns true if a changed file name should trigger a reload
+///
+/// Hidden files and backup files, which are commonly written by editors
+/// while saving, are ignored.
+fn is_relevant_change(name: &Path) -> bool {
+    let name = name.to_string_lossy();
+    !(name.starts_with('.') || name.ends_with('~'))
+}
+
+impl WaylandState {
+    /// Start watching a theme directory, and its subdirectories, for changes
+    pub fn watch_theme(&mut self, theme_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let preview = self
+            .preview
+            .as_mut()
+            .ok_or::<Box<dyn std::error::Error>>("Preview state not set".into())?;
+
+        let inotify = Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC)?;
+        let flags = AddWatchFlags::IN_CLOSE_WRITE
+            | AddWatchFlags::IN_MOVED_TO
+            | AddWatchFlags::IN_CREATE
+            | AddWatchFlags::IN_DELETE;
+
+        let mut dirs = vec![theme_dir.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            inotify.add_watch(&dir, flags)?;
+            debug!("Watching '{}' for changes", dir.display());
+
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                if path.is_dir() && is_relevant_change(Path::new(&entry.file_name())) {
+                    dirs.push(path);
+                }
+            }
+        }
+
+        preview.watcher = Some(inotify);
+
+        info!("Watching theme directory '{}'", theme_dir.display());
+
+        Ok(())
+    }
+
+    /// Returns the raw file descriptor of the theme watcher, if there is one
+    pub fn theme_watch_fd(&self) -> Option<RawFd> {
+        self.preview
+            .as_ref()?
+            .watcher
+            .as_ref()
+            .map(|w| w.as_fd().as_raw_fd())
+    }
+
+    /// Read pending events from the theme watcher
+    ///
+    /// If any relevant files changed, a reload is scheduled after `RELOAD_DELAY`.
+    pub fn handle_theme_watch_event(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(preview) = self.preview.as_mut() else {
+            return Ok(());
+        };
+        let Some(watcher) = preview.watcher.as_ref() else {
+            return Ok(());
+        };
+
+        loop {
+            let events = match watcher.read_events() {
+                Ok(events) => events,
+                Err(Errno::EAGAIN) => break,
+                Err(e) => return Err(Box::new(e)),
+            };
+
+            for event in events {
+                if let Some(name) = event.name {
+                    if is_relevant_change(Path::new(&name)) {
+                        debug!("Theme file changed: {:?}", name);
+                        preview.reload_at = Some(Instant::now() + RELOAD_DELAY);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send a reload event to the renderers, if a scheduled reload is due
+    pub fn update_theme_reload(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(preview) = self.preview.as_mut() else {
+            return Ok(());
+        };
+
+        match preview.reload_at {
+            Some(reload_at) if Instant::now() >= reload_at => {
+                preview.reload_at = None;
+            }
+            _ => return Ok(()),
+        }
+
+        info!("Reloading theme");
+
+        if let Err(e) = self.broadcast(&Event::ThemeReload) {
+            warn!("Failed to send reload event: {}", e);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    window.rs:
+        Manages the `xdg_toplevel` window used to preview themes.
+*/
+
+use crate::ffi::set_state;
+use crate::shared::State;
+use crate::wayland::WaylandState;
+
+use tracing::info;
+use wayland_client::{Connection, Dispatch, EventQueue, QueueHandle};
+use wayland_protocols::xdg::shell::client::{
+    xdg_surface::{self, XdgSurface},
+    xdg_toplevel::{self, XdgToplevel},
+    xdg_wm_base::{self, XdgWmBase},
+};
+
+pub const PREVIEW_WINDOW_TITLE: &str = "tlockr preview";
+pub const PREVIEW_APP_ID: &str = "tlockr-preview";
+
+impl WaylandState {
+    /// Use the preview window size in place of the output size
+    ///
+    /// This needs to be called before buffers are allocated, and before the
+    /// renderer is initialized.
+    pub fn apply_preview_size(&mut self) {
+        if let Some(preview) = &self.preview {
+            for surface in self.surfaces.iter_mut() {
+                surface.output_state.width = preview.width;
+                surface.output_state.height = preview.height;
+            }
+        }
+    }
+
+    /// Open a window to display the theme preview in
+    ///
+    /// The window is created with a fixed size, and shows the theme of the
+    /// primary output. The renderer is started when the window is first
+    /// configured.
+    ///
+    /// Preview windows behave like a locked session for the rest of the
+    /// application, so the state is set to `Locked` once the window is created.
+    pub fn open_preview_window(
+        &mut self,
+        event_queue: &EventQueue<Self>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let qh = event_queue.handle();
+
+        let (compositor, viewporter) = match (&self.compositor, &self.viewporter) {
+            (Some(compositor), Some(viewporter)) => (compositor, viewporter),
+            _ => return Err("Compositor or viewporter unavailable".into()),
+        };
+
+        let theme_surface = self
+            .surfaces
+            .iter_mut()
+            .find(|surface| surface.primary())
+            .ok_or::<Box<dyn std::error::Error>>("Primary output not selected".into())?;
+
+        let preview = self
+            .preview
+            .as_mut()
+            .ok_or::<Box<dyn std::error::Error>>("Preview state not set".into())?;
+        let wm_base = preview
+            .wm_base
+            .as_ref()
+            .ok_or::<Box<dyn std::error::Error>>("xdg_wm_base not available".into())?;
+
+        let surface = compositor.create_surface(&qh, ());
+        let viewport = viewporter.get_viewport(&surface, &qh, ());
+
+        let xdg_surface = wm_base.get_xdg_surface(&surface, &qh, ());
+        let toplevel = xdg_surface.get_toplevel(&qh, ());
+        toplevel.set_title(PREVIEW_WINDOW_TITLE.to_string());
+        toplevel.set_app_id(PREVIEW_APP_ID.to_string());
+        toplevel.set_min_size(preview.width, preview.height);
+        toplevel.set_max_size(preview.width, preview.height);
+
+        surface.commit();
+
+        preview.xdg_surface = Some(xdg_surface);
+        preview.toplevel = Some(toplevel);
+
+        theme_surface.surface = Some(surface);
+        theme_surface.viewport = Some(viewport);
+
+        info!("Opened preview window");
+        set_state(self.app_state, State::Locked);
+
+        Ok(())
+    }
+
+    /// Close the preview window and signal the event loop to exit
+    pub fn close_preview_window(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let preview = self
+            .preview
+            .as_mut()
+            .ok_or::<Box<dyn std::error::Error>>("Preview state not set".into())?;
+
+        if let Some(toplevel) = preview.toplevel.take() {
+            toplevel.destroy();
+        }
+
+        if let Some(xdg_surface) = preview.xdg_surface.take() {
+            xdg_surface.destroy();
+        }
+
+        info!("Closed preview window");
+        set_state(self.app_state, State::Unlocked);
+
+        Ok(())
+    }
+}
+
+impl Dispatch<XdgWmBase, ()> for WaylandState {
+    fn event(
+        _state: &mut Self,
+        proxy: &XdgWmBase,
+        event: <XdgWmBase as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            xdg_wm_base::Event::Ping { serial } => {
+                proxy.pong(serial);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<XdgSurface, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        proxy: &XdgSurface,
+        event: <XdgSurface as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            xdg_surface::Event::Configure { serial } => {
+                proxy.ack_configure(serial);
+
+                if let Some(surface) = state.primary_surface() {
+                    surface.start_renderer();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<XdgToplevel, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _proxy: &XdgToplevel,
+        event: <XdgToplevel as wayland_client::Proxy>::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            xdg_toplevel::Event::Close => {
+                set_state(state.app_state, State::Unlocking);
+            }
+            _ => {}
+        }
+    }
+}
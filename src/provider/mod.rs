@@ -0,0 +1,7 @@
+mod network;
+mod power;
+mod state;
+
+pub use network::*;
+pub use power::*;
+pub use state::*;
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    network.rs:
+        Reads network interface status from sysfs
+*/
+
+use crate::provider::read_attribute;
+
+use std::path::Path;
+
+/// Default directory holding network interfaces
+pub const DEFAULT_NET_PATH: &str = "/sys/class/net";
+
+/// Status of the network connection
+///
+/// `interface` is the name of the connected interface, or empty when not
+/// connected. Wired interfaces are preferred over wireless interfaces.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NetworkStatus {
+    pub connected: bool,
+    pub wireless: bool,
+    pub interface: String,
+}
+
+/// Returns whether the interface at `path` is up, with a link
+///
+/// Some drivers never report an operational state, their carrier is used
+/// instead.
+fn is_connected(path: &Path) -> bool {
+    match read_attribute(path, "operstate").as_deref() {
+        Some("up") => true,
+        Some("unknown") => read_attribute(path, "carrier").as_deref() == Some("1"),
+        _ => false,
+    }
+}
+
+/// Read the network status from the network interfaces in `root`
+///
+/// Only interfaces backed by a device are considered, so loopback, bridges,
+/// tunnels and other virtual interfaces are ignored.
+pub fn read_network_status(root: &Path) -> std::io::Result<NetworkStatus> {
+    let mut interfaces = std::fs::read_dir(root)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    interfaces.sort();
+
+    let connected = interfaces
+        .iter()
+        .filter(|path| path.join("device").exists() && is_connected(path))
+        .map(|path| {
+            let wireless = path.join("wireless").exists() || path.join("phy80211").exists();
+            (path, wireless)
+        })
+        .min_by_key(|(_, wireless)| *wireless);
+
+    Ok(match connected {
+        Some((path, wireless)) => NetworkStatus {
+            connected: true,
+            wireless,
+            interface: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        },
+        None => NetworkStatus::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    /// Add an interface to the network tree in `root`, backed by a device if
+    /// `physical`
+    fn interface(root: &TempDir, name: &str, operstate: &str, carrier: &str, physical: bool) {
+        root.write(&format!("{}/operstate", name), &format!("{}\n", operstate));
+        root.write(&format!("{}/carrier", name), &format!("{}\n", carrier));
+        if physical {
+            root.create_dir(&format!("{}/device", name));
+        }
+    }
+
+    #[test]
+    fn virtual_interfaces_are_ignored() {
+        let root = TempDir::new();
+        interface(&root, "lo", "unknown", "1", false);
+        interface(&root, "docker0", "up", "1", false);
+
+        assert_eq!(
+            read_network_status(root.path()).unwrap(),
+            NetworkStatus::default()
+        );
+    }
+
+    #[test]
+    fn wired_interface_is_preferred() {
+        let root = TempDir::new();
+        interface(&root, "wlan0", "up", "1", true);
+        root.create_dir("wlan0/phy80211");
+        interface(&root, "eth0", "up", "1", true);
+
+        assert_eq!(
+            read_network_status(root.path()).unwrap(),
+            NetworkStatus {
+                connected: true,
+                wireless: false,
+                interface: "eth0".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn wireless_interface_is_detected() {
+        let root = TempDir::new();
+        interface(&root, "eth0", "down", "0", true);
+        interface(&root, "wlan0", "up", "1", true);
+        root.create_dir("wlan0/wireless");
+
+        assert_eq!(
+            read_network_status(root.path()).unwrap(),
+            NetworkStatus {
+                connected: true,
+                wireless: true,
+                interface: "wlan0".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_operstate_uses_carrier() {
+        let root = TempDir::new();
+        interface(&root, "eth0", "unknown", "1", true);
+        assert!(read_network_status(root.path()).unwrap().connected);
+
+        let root = TempDir::new();
+        interface(&root, "eth0", "unknown", "0", true);
+        assert!(!read_network_status(root.path()).unwrap().connected);
+    }
+}
@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    power.rs:
+        Reads battery and power adapter status from sysfs
+*/
+
+use std::path::Path;
+
+/// Default directory holding power supply devices
+pub const DEFAULT_POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+
+/// Status of the system batteries and power adapters
+///
+/// `level` is the charge of all batteries together, as a percentage. The
+/// battery fields are meaningless when `present` is false.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PowerStatus {
+    pub present: bool,
+    pub level: u32,
+    pub charging: bool,
+    pub plugged_in: bool,
+}
+
+/// Read a sysfs attribute of a device, without its trailing newline
+pub(super) fn read_attribute(device: &Path, name: &str) -> Option<String> {
+    std::fs::read_to_string(device.join(name))
+        .ok()
+        .map(|value| value.trim_end().to_string())
+}
+
+/// Read a numeric sysfs attribute of a device
+pub(super) fn read_number(device: &Path, name: &str) -> Option<u64> {
+    read_attribute(device, name)?.parse().ok()
+}
+
+/// Read the power status from the power supply devices in `root`
+///
+/// Batteries of peripherals, such as wireless mice, are ignored. With
+/// several batteries, the level of each is weighted by its capacity.
+pub fn read_power_status(root: &Path) -> std::io::Result<PowerStatus> {
+    let mut status = PowerStatus::default();
+    let (mut charge, mut capacity) = (0u64, 0u64);
+
+    for entry in std::fs::read_dir(root)? {
+        let device = entry?.path();
+
+        match read_attribute(&device, "type").as_deref() {
+            Some("Battery") => {
+                if read_attribute(&device, "scope").as_deref() == Some("Device") {
+                    continue;
+                }
+
+                let Some(level) = read_number(&device, "capacity") else {
+                    continue;
+                };
+                let full = read_number(&device, "energy_full")
+                    .or_else(|| read_number(&device, "charge_full"))
+                    .filter(|&full| full > 0)
+                    .unwrap_or(1);
+
+                status.present = true;
+                status.charging |= read_attribute(&device, "status").as_deref() == Some("Charging");
+                charge += level.min(100) * full;
+                capacity += full;
+            }
+            Some("Mains" | "USB") => {
+                status.plugged_in |= read_number(&device, "online") == Some(1);
+            }
+            _ => {}
+        }
+    }
+
+    status.level = charge.checked_div(capacity).unwrap_or_default() as u32;
+
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    /// Add a device with `attributes` to the power supply tree in `root`
+    fn device(root: &TempDir, name: &str, attributes: &[(&str, &str)]) {
+        for (attribute, value) in attributes {
+            root.write(&format!("{}/{}", name, attribute), &format!("{}\n", value));
+        }
+    }
+
+    #[test]
+    fn no_devices_has_no_battery() {
+        let root = TempDir::new();
+        assert_eq!(
+            read_power_status(root.path()).unwrap(),
+            PowerStatus::default()
+        );
+    }
+
+    #[test]
+    fn battery_and_adapter_are_read() {
+        let root = TempDir::new();
+        device(
+            &root,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("capacity", "42"),
+                ("status", "Charging"),
+            ],
+        );
+        device(&root, "AC", &[("type", "Mains"), ("online", "1")]);
+
+        assert_eq!(
+            read_power_status(root.path()).unwrap(),
+            PowerStatus {
+                present: true,
+                level: 42,
+                charging: true,
+                plugged_in: true,
+            }
+        );
+    }
+
+    #[test]
+    fn batteries_are_weighted_by_capacity() {
+        let root = TempDir::new();
+        device(
+            &root,
+            "BAT0",
+            &[
+                ("type", "Battery"),
+                ("capacity", "100"),
+                ("energy_full", "30000"),
+                ("status", "Full"),
+            ],
+        );
+        device(
+            &root,
+            "BAT1",
+            &[
+                ("type", "Battery"),
+                ("capacity", "20"),
+                ("charge_full", "10000"),
+                ("status", "Discharging"),
+            ],
+        );
+
+        let status = read_power_status(root.path()).unwrap();
+        assert!(status.present);
+        assert!(!status.charging);
+        assert_eq!(status.level, 80);
+    }
+
+    #[test]
+    fn peripheral_batteries_are_ignored() {
+        let root = TempDir::new();
+        device(
+            &root,
+            "hidpp_battery_0",
+            &[("type", "Battery"), ("scope", "Device"), ("capacity", "5")],
+        );
+        device(&root, "USB", &[("type", "USB"), ("online", "0")]);
+
+        assert_eq!(
+            read_power_status(root.path()).unwrap(),
+            PowerStatus::default()
+        );
+    }
+
+    #[test]
+    fn missing_root_is_an_error() {
+        let root = TempDir::new();
+        assert!(read_power_status(&root.path().join("missing")).is_err());
+    }
+}
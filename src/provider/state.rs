@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    state.rs:
+        Polls the status providers, and sends changes to the renderer
+*/
+
+use crate::config::ProviderConfig;
+use crate::event::Event;
+use crate::provider::{
+    DEFAULT_NET_PATH, DEFAULT_POWER_SUPPLY_PATH, NetworkStatus, PowerStatus, read_network_status,
+    read_power_status,
+};
+use crate::wayland::WaylandState;
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Default time between reads of the status providers
+pub const DEFAULT_PROVIDER_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Holds the state of the status providers
+///
+/// The last status sent to the renderer is kept, so only changes are sent.
+pub struct ProviderState {
+    power_supply_path: PathBuf,
+    net_path: PathBuf,
+    interval: Duration,
+    update_at: Instant,
+
+    power: Option<PowerStatus>,
+    network: Option<NetworkStatus>,
+}
+
+impl ProviderState {
+    /// Create a new `ProviderState`, which reads the providers immediately
+    pub fn new(config: &ProviderConfig) -> Self {
+        Self {
+            power_supply_path: config
+                .power_supply_path
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_POWER_SUPPLY_PATH)),
+            net_path: config
+                .net_path
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_NET_PATH)),
+            interval: config
+                .interval_ms
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_PROVIDER_INTERVAL),
+            update_at: Instant::now(),
+            power: None,
+            network: None,
+        }
+    }
+
+    /// Read the providers, returning events for any status that changed
+    ///
+    /// A provider that cannot be read reports nothing present or connected.
+    fn poll(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        let power = read_power_status(&self.power_supply_path).unwrap_or_else(|e| {
+            debug!(
+                "Failed to read power supplies from '{}': {}",
+                self.power_supply_path.display(),
+                e
+            );
+            PowerStatus::default()
+        });
+
+        if self.power != Some(power) {
+            events.push(Event::PowerStatus {
+                present: power.present,
+                level: power.level,
+                charging: power.charging,
+                plugged_in: power.plugged_in,
+            });
+            self.power = Some(power);
+        }
+
+        let network = read_network_status(&self.net_path).unwrap_or_else(|e| {
+            debug!(
+                "Failed to read network interfaces from '{}': {}",
+                self.net_path.display(),
+                e
+            );
+            NetworkStatus::default()
+        });
+
+        if self.network.as_ref() != Some(&network) {
+            events.push(Event::NetworkStatus {
+                connected: network.connected,
+                wireless: network.wireless,
+                interface: network.interface.clone(),
+            });
+            self.network = Some(network);
+        }
+
+        events
+    }
+}
+
+impl WaylandState {
+    /// Read the status providers if they are due, and send any changes to the
+    /// renderers
+    pub fn update_providers(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(providers) = self.providers.as_mut() else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        if now < providers.update_at {
+            return Ok(());
+        }
+        providers.update_at = now + providers.interval;
+
+        for event in providers.poll() {
+            if let Err(e) = self.broadcast(&event) {
+                warn!("Failed to send {:?} event: {}", event, e);
+            }
+        }
+
+        Ok(())
+    }
+}
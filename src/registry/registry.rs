@@ -6,6 +6,8 @@
         Binds Wayland interfaces to objects in WaylandState.
 */
 
+use crate::graphics::OutputInfo;
+use crate::input::Seat;
 use crate::wayland::WaylandState;
 
 use wayland_client::{
@@ -19,8 +21,12 @@ use wayland_client::{
     },
 };
 use wayland_protocols::{
+    ext::image_capture_source::v1::client::ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1,
+    ext::image_copy_capture::v1::client::ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1,
     ext::session_lock::v1::client::ext_session_lock_manager_v1::ExtSessionLockManagerV1,
+    wp::text_input::zv3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3,
     wp::viewporter::client::wp_viewporter::WpViewporter,
+    xdg::shell::client::xdg_wm_base::XdgWmBase,
 };
 
 impl Dispatch<WlRegistry, ()> for WaylandState {
@@ -39,8 +45,9 @@ impl Dispatch<WlRegistry, ()> for WaylandState {
                 version,
             } => match interface.as_str() {
                 "wl_output" => {
-                    let output = registry.bind::<WlOutput, _, _>(name, version, qh, ());
-                    state.output = Some(output);
+                    // Outputs are identified by their registry name
+                    let output = registry.bind::<WlOutput, _, _>(name, version, qh, name);
+                    state.outputs.push(OutputInfo::new(name, output));
                 }
                 "wl_shm" => {
                     let shm = registry.bind::<WlShm, _, _>(name, version, qh, ());
@@ -51,20 +58,55 @@ impl Dispatch<WlRegistry, ()> for WaylandState {
                     state.compositor = Some(compositor);
                 }
                 "wl_seat" => {
-                    let seat = registry.bind::<WlSeat, _, _>(name, version, qh, ());
-                    state.seat = Some(seat);
+                    // Seats are identified by their registry name
+                    let seat = registry.bind::<WlSeat, _, _>(name, version, qh, name);
+                    state.seats.push(Seat::new(name, seat));
+                    state.create_text_inputs(qh);
                 }
                 "wp_viewporter" => {
                     let viewporter = registry.bind::<WpViewporter, _, _>(name, version, qh, ());
                     state.viewporter = Some(viewporter);
                 }
+                "zwp_text_input_manager_v3" => {
+                    let text_input_manager =
+                        registry.bind::<ZwpTextInputManagerV3, _, _>(name, version, qh, ());
+                    state.text_input_manager = Some(text_input_manager);
+                    state.create_text_inputs(qh);
+                }
                 "ext_session_lock_manager_v1" => {
                     let session_lock_manager =
                         registry.bind::<ExtSessionLockManagerV1, _, _>(name, version, qh, ());
                     state.session_lock_manager = Some(session_lock_manager);
                 }
+                "ext_output_image_capture_source_manager_v1" => {
+                    let manager = registry.bind::<ExtOutputImageCaptureSourceManagerV1, _, _>(
+                        name,
+                        version,
+                        qh,
+                        (),
+                    );
+                    state.image_capture_source_manager = Some(manager);
+                }
+                "ext_image_copy_capture_manager_v1" => {
+                    let manager =
+                        registry.bind::<ExtImageCopyCaptureManagerV1, _, _>(name, version, qh, ());
+                    state.image_copy_capture_manager = Some(manager);
+                }
+                "xdg_wm_base" => {
+                    // xdg_wm_base is only needed for preview windows
+                    if let Some(preview) = &mut state.preview {
+                        let wm_base = registry.bind::<XdgWmBase, _, _>(name, version, qh, ());
+                        preview.wm_base = Some(wm_base);
+                    }
+                }
                 _ => {}
             },
+            RegistryEvent::GlobalRemove { name } => {
+                // Seats can be added and removed at any time, e.g. by
+                // compositors with multiple seat support
+                state.remove_seat(name);
+                state.remove_output(name);
+            }
             _ => {}
         }
     }
@@ -0,0 +1,124 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    background.rs:
+        This file defines the Background object, which is C-compatible, and
+        holds the images themes can use as their background.
+*/
+
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// A screenshot of an output, taken before locking
+///
+/// `name` and `data` are owned by this object. `format` is a `wl_shm` format
+/// code, either `Argb8888` or `Xrgb8888`.
+#[repr(C)]
+pub struct Screenshot {
+    pub name: *mut c_char,
+    pub data: *mut u8,
+    pub size: usize,
+    pub width: i32,
+    pub height: i32,
+    pub stride: i32,
+    pub format: u32,
+}
+
+/// Screenshots of every output, as an array of `count` entries, and the path
+/// of the configured wallpaper
+///
+/// `wallpaper` is null if no wallpaper is configured.
+#[repr(C)]
+pub struct Background {
+    pub screenshots: *mut Screenshot,
+    pub count: usize,
+    pub wallpaper: *mut c_char,
+}
+
+impl Screenshot {
+    /// Create a `Screenshot` of the output `name`, from its pixel data
+    ///
+    /// Returns `None` if `name` contains a null byte.
+    pub fn new(
+        name: &str,
+        data: Vec<u8>,
+        width: i32,
+        height: i32,
+        stride: i32,
+        format: u32,
+    ) -> Option<Self> {
+        let name = CString::new(name).ok()?.into_raw();
+        let data = data.into_boxed_slice();
+        let size = data.len();
+
+        Some(Self {
+            name,
+            data: Box::into_raw(data) as *mut u8,
+            size,
+            width,
+            height,
+            stride,
+            format,
+        })
+    }
+}
+
+impl Background {
+    /// Create a `Background` with no screenshots or wallpaper
+    pub fn empty() -> Self {
+        Self {
+            screenshots: std::ptr::null_mut(),
+            count: 0,
+            wallpaper: std::ptr::null_mut(),
+        }
+    }
+
+    /// Create a `Background` from screenshots and the wallpaper path
+    pub fn new(screenshots: Vec<Screenshot>, wallpaper: Option<&Path>) -> Self {
+        let screenshots = screenshots.into_boxed_slice();
+        let count = screenshots.len();
+
+        Self {
+            screenshots: Box::into_raw(screenshots) as *mut Screenshot,
+            count,
+            wallpaper: wallpaper
+                .and_then(|path| CString::new(path.as_os_str().as_bytes()).ok())
+                .map(CString::into_raw)
+                .unwrap_or(std::ptr::null_mut()),
+        }
+    }
+}
+
+impl Drop for Screenshot {
+    fn drop(&mut self) {
+        if !self.name.is_null() {
+            drop(unsafe { CString::from_raw(self.name) });
+        }
+
+        if !self.data.is_null() {
+            drop(unsafe {
+                Box::from_raw(std::ptr::slice_from_raw_parts_mut(self.data, self.size))
+            });
+        }
+    }
+}
+
+impl Drop for Background {
+    fn drop(&mut self) {
+        if !self.screenshots.is_null() {
+            drop(unsafe {
+                Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                    self.screenshots,
+                    self.count,
+                ))
+            });
+        }
+
+        if !self.wallpaper.is_null() {
+            drop(unsafe { CString::from_raw(self.wallpaper) });
+        }
+    }
+}
@@ -1,5 +1,17 @@
+mod background;
+mod output;
 mod pipe;
+mod sandbox;
+mod session;
+mod settings;
 mod state;
+mod text;
 
+pub use background::*;
+pub use output::*;
 pub use pipe::*;
+pub use sandbox::*;
+pub use session::*;
+pub use settings::*;
 pub use state::*;
+pub use text::*;
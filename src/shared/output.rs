@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    output.rs:
+        This file defines the OutputDetails and OutputState objects, which are
+        C-compatible, and describe the output a theme is shown on.
+*/
+
+use crate::shared::{ThemeSandbox, ThemeSettings};
+
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int};
+
+/// Information about the output the theme is shown on
+///
+/// Strings are owned by this object, and are null if unknown. `role` is set
+/// by the user's output configuration.
+#[repr(C)]
+pub struct OutputDetails {
+    pub name: *mut c_char,
+    pub description: *mut c_char,
+    pub role: *mut c_char,
+    pub primary: bool,
+}
+
+/// Convert `value` into an owned C string, or null if it is `None` or not
+/// representable
+fn into_raw(value: Option<&str>) -> *mut c_char {
+    value
+        .and_then(|value| CString::new(value).ok())
+        .map(CString::into_raw)
+        .unwrap_or(std::ptr::null_mut())
+}
+
+impl OutputDetails {
+    /// Create an `OutputDetails` with nothing known
+    ///
+    /// Without an output, such as when rendering to images, the theme is
+    /// treated as being on the primary output.
+    pub fn empty() -> Self {
+        Self {
+            name: std::ptr::null_mut(),
+            description: std::ptr::null_mut(),
+            role: std::ptr::null_mut(),
+            primary: true,
+        }
+    }
+
+    /// Create an `OutputDetails`
+    pub fn new(
+        name: Option<&str>,
+        description: Option<&str>,
+        role: Option<&str>,
+        primary: bool,
+    ) -> Self {
+        Self {
+            name: into_raw(name),
+            description: into_raw(description),
+            role: into_raw(role),
+            primary,
+        }
+    }
+}
+
+impl Drop for OutputDetails {
+    fn drop(&mut self) {
+        for string in [self.name, self.description, self.role] {
+            if !string.is_null() {
+                drop(unsafe { CString::from_raw(string) });
+            }
+        }
+    }
+}
+
+/// State of the renderer for a single output
+///
+/// Each output can show a different theme, so the theme's QML file, settings
+/// and sandbox are kept here rather than in the `ApplicationState`. The QML
+/// path is owned by this object.
+#[repr(C)]
+pub struct OutputState {
+    pub qml_path: *mut c_char,
+    pub renderer_read_fd: c_int,
+    pub width: c_int,
+    pub height: c_int,
+    pub details: OutputDetails,
+    pub settings: ThemeSettings,
+    pub sandbox: ThemeSandbox,
+}
+
+impl OutputState {
+    /// Create an `OutputState` for the theme at `qml_path`
+    pub fn new(
+        qml_path: CString,
+        details: OutputDetails,
+        settings: ThemeSettings,
+        sandbox: ThemeSandbox,
+    ) -> Self {
+        Self {
+            qml_path: qml_path.into_raw(),
+            renderer_read_fd: -1,
+            width: -1,
+            height: -1,
+            details,
+            settings,
+            sandbox,
+        }
+    }
+}
+
+impl Drop for OutputState {
+    fn drop(&mut self) {
+        if !self.qml_path.is_null() {
+            drop(unsafe { CString::from_raw(self.qml_path) });
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    sandbox.rs:
+        This file defines the ThemeSandbox object, which is C-compatible, and
+        describes what the loaded theme is allowed to access.
+*/
+
+use crate::config::ThemePermissions;
+
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// The theme directories, and the permissions declared by the theme
+///
+/// `roots` is an array of `count` directories, one for each theme in the
+/// chain of `extends`, most derived first. The array and its strings are
+/// owned by this object. If it is empty, nothing is restricted.
+#[repr(C)]
+pub struct ThemeSandbox {
+    pub roots: *mut *mut c_char,
+    pub count: usize,
+    pub network: bool,
+    pub files: bool,
+}
+
+impl ThemeSandbox {
+    /// Create a `ThemeSandbox` that restricts nothing
+    pub fn empty() -> Self {
+        Self {
+            roots: std::ptr::null_mut(),
+            count: 0,
+            network: true,
+            files: true,
+        }
+    }
+
+    /// Create a `ThemeSandbox` for a theme, which can load files from any of
+    /// the directories in `roots`
+    pub fn new<'a>(
+        roots: impl IntoIterator<Item = &'a Path>,
+        permissions: ThemePermissions,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let roots = roots
+            .into_iter()
+            .map(|root| {
+                let root = root.canonicalize()?;
+                Ok(CString::new(root.as_os_str().as_bytes())?)
+            })
+            .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+        if roots.is_empty() {
+            return Err("A theme sandbox needs at least one directory".into());
+        }
+
+        let roots = roots
+            .into_iter()
+            .map(CString::into_raw)
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+
+        let count = roots.len();
+        Ok(Self {
+            roots: Box::into_raw(roots) as *mut *mut c_char,
+            count,
+            network: permissions.network.unwrap_or(false),
+            files: permissions.files.unwrap_or(false),
+        })
+    }
+}
+
+impl Drop for ThemeSandbox {
+    fn drop(&mut self) {
+        if self.roots.is_null() {
+            return;
+        }
+
+        let roots =
+            unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(self.roots, self.count)) };
+        for root in roots {
+            drop(unsafe { CString::from_raw(root) });
+        }
+    }
+}
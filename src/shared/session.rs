@@ -0,0 +1,123 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    session.rs:
+        This file defines the SessionInfo object, which is C-compatible, and
+        holds information about the user and host shown by themes.
+*/
+
+use std::ffi::{CString, OsStr};
+use std::os::raw::c_char;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+use uzers::os::unix::UserExt;
+use uzers::{get_current_uid, get_user_by_uid};
+
+/// Directory holding AccountsService user icons, named after each user
+const ACCOUNTS_SERVICE_ICONS: &str = "/var/lib/AccountsService/icons";
+
+/// Information about the user and session
+///
+/// Strings are owned by this object, and are null if unknown. `lock_time` is
+/// the time locking started, in seconds since the Unix epoch.
+#[repr(C)]
+pub struct SessionInfo {
+    pub user_name: *mut c_char,
+    pub real_name: *mut c_char,
+    pub avatar_path: *mut c_char,
+    pub host_name: *mut c_char,
+    pub lock_time: i64,
+}
+
+impl SessionInfo {
+    /// Create a `SessionInfo` with nothing known
+    pub fn empty() -> Self {
+        Self {
+            user_name: std::ptr::null_mut(),
+            real_name: std::ptr::null_mut(),
+            avatar_path: std::ptr::null_mut(),
+            host_name: std::ptr::null_mut(),
+            lock_time: 0,
+        }
+    }
+
+    /// Collect information about the user running this process, and the host
+    ///
+    /// The lock time is set to now.
+    pub fn current() -> Self {
+        let mut info = Self::empty();
+
+        info.lock_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs() as i64)
+            .unwrap_or_default();
+
+        match nix::unistd::gethostname() {
+            Ok(host_name) => info.host_name = into_raw(&host_name),
+            Err(e) => warn!("Failed to get hostname: {}", e),
+        }
+
+        let Some(user) = get_user_by_uid(get_current_uid()) else {
+            warn!("Failed to find the current user");
+            return info;
+        };
+
+        info.user_name = into_raw(user.name());
+
+        // The first GECOS field is the full name, the rest are contact details
+        let gecos = user.gecos().as_bytes();
+        let real_name = gecos.split(|&c| c == b',').next().unwrap_or_default();
+        info.real_name = into_raw(OsStr::from_bytes(real_name));
+
+        if let Some(avatar) = find_avatar(user.name(), user.home_dir()) {
+            debug!("Found user avatar at {}", avatar.display());
+            info.avatar_path = into_raw(avatar.as_os_str());
+        }
+
+        info
+    }
+}
+
+impl Drop for SessionInfo {
+    fn drop(&mut self) {
+        for string in [
+            self.user_name,
+            self.real_name,
+            self.avatar_path,
+            self.host_name,
+        ] {
+            if !string.is_null() {
+                drop(unsafe { CString::from_raw(string) });
+            }
+        }
+    }
+}
+
+/// Convert `value` into an owned C string, or null if it is empty or not
+/// representable
+fn into_raw(value: &OsStr) -> *mut c_char {
+    if value.is_empty() {
+        return std::ptr::null_mut();
+    }
+
+    CString::new(value.as_bytes())
+        .map(CString::into_raw)
+        .unwrap_or(std::ptr::null_mut())
+}
+
+/// Find the avatar image of a user
+///
+/// The user's own `~/.face` or `~/.face.icon` is preferred over the icon set
+/// through AccountsService.
+fn find_avatar(user_name: &OsStr, home_dir: &Path) -> Option<PathBuf> {
+    [
+        home_dir.join(".face"),
+        home_dir.join(".face.icon"),
+        Path::new(ACCOUNTS_SERVICE_ICONS).join(user_name),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
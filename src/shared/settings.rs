@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    settings.rs:
+        This file defines the ThemeSettings object, which is C-compatible, and
+        holds the resolved values of theme settings.
+*/
+
+use crate::config::{SettingKind, SettingValue};
+
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::os::unix::ffi::OsStrExt;
+
+/// A single theme setting
+///
+/// `text` holds `String`, `Color` and `Path` values, and is owned by this
+/// object. Only the field matching `kind` is meaningful.
+#[repr(C)]
+pub struct ThemeSettingEntry {
+    pub name: *mut c_char,
+    pub kind: SettingKind,
+    pub text: *mut c_char,
+    pub boolean: bool,
+    pub integer: i64,
+    pub number: f64,
+}
+
+/// The settings of the loaded theme, as an array of `count` entries
+#[repr(C)]
+pub struct ThemeSettings {
+    pub entries: *mut ThemeSettingEntry,
+    pub count: usize,
+}
+
+impl ThemeSettings {
+    /// Create a `ThemeSettings` with no settings
+    pub fn empty() -> Self {
+        Self {
+            entries: std::ptr::null_mut(),
+            count: 0,
+        }
+    }
+
+    /// Create a `ThemeSettings` from resolved setting values
+    ///
+    /// Settings with names or text containing null bytes are skipped.
+    pub fn new(settings: Vec<(String, SettingValue)>) -> Self {
+        let entries = settings
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let mut entry = ThemeSettingEntry {
+                    name: CString::new(name).ok()?.into_raw(),
+                    kind: SettingKind::String,
+                    text: std::ptr::null_mut(),
+                    boolean: false,
+                    integer: 0,
+                    number: 0.0,
+                };
+
+                let text = match value {
+                    SettingValue::String(s) => CString::new(s),
+                    SettingValue::Color(s) => {
+                        entry.kind = SettingKind::Color;
+                        CString::new(s)
+                    }
+                    SettingValue::Path(path) => {
+                        entry.kind = SettingKind::Path;
+                        CString::new(path.as_os_str().as_bytes())
+                    }
+                    SettingValue::Bool(b) => {
+                        entry.kind = SettingKind::Bool;
+                        entry.boolean = b;
+                        return Some(entry);
+                    }
+                    SettingValue::Int(i) => {
+                        entry.kind = SettingKind::Int;
+                        entry.integer = i;
+                        return Some(entry);
+                    }
+                    SettingValue::Float(f) => {
+                        entry.kind = SettingKind::Float;
+                        entry.number = f;
+                        return Some(entry);
+                    }
+                };
+
+                // The entry frees its name when dropped
+                entry.text = text.ok()?.into_raw();
+                Some(entry)
+            })
+            .collect::<Vec<_>>()
+            .into_boxed_slice();
+
+        let count = entries.len();
+        Self {
+            entries: Box::into_raw(entries) as *mut ThemeSettingEntry,
+            count,
+        }
+    }
+}
+
+impl Drop for ThemeSettingEntry {
+    fn drop(&mut self) {
+        for string in [self.name, self.text] {
+            if !string.is_null() {
+                drop(unsafe { CString::from_raw(string) });
+            }
+        }
+    }
+}
+
+impl Drop for ThemeSettings {
+    fn drop(&mut self) {
+        if !self.entries.is_null() {
+            drop(unsafe {
+                Box::from_raw(std::ptr::slice_from_raw_parts_mut(self.entries, self.count))
+            });
+        }
+    }
+}
@@ -7,9 +7,10 @@
         for the shared state.
 */
 
-use crate::ffi::QmlRenderer;
+use crate::config::ColorScheme;
+use crate::shared::{Background, SessionInfo};
 
-use std::os::raw::{c_char, c_int};
+use std::os::raw::{c_int, c_uint};
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -22,31 +23,32 @@ pub enum State {
     Unlocked = 5,
 }
 
+/// State shared by the renderers on every output
 #[repr(C)]
 pub struct ApplicationState {
-    pub qml_path: *mut c_char,
     pub state: State,
-    pub renderer: *mut QmlRenderer,
     pub renderer_write_fd: c_int,
-    pub renderer_read_fd: c_int,
     pub auth_write_fd: c_int,
     pub auth_read_fd: c_int,
-    pub output_width: c_int,
-    pub output_height: c_int,
+    pub buffer_format: c_uint,
+    pub preview: bool,
+    pub session: SessionInfo,
+    pub color_scheme: ColorScheme,
+    pub background: Background,
 }
 
 impl ApplicationState {
-    pub fn new(qml_path: *mut c_char) -> Self {
+    pub fn new() -> Self {
         Self {
-            qml_path: qml_path,
             state: State::None,
-            renderer: std::ptr::null_mut(),
             renderer_write_fd: -1,
-            renderer_read_fd: -1,
             auth_write_fd: -1,
             auth_read_fd: -1,
-            output_width: -1,
-            output_height: -1,
+            buffer_format: 0,
+            preview: false,
+            session: SessionInfo::empty(),
+            color_scheme: ColorScheme::Default,
+            background: Background::empty(),
         }
     }
 }
@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    text.rs:
+        Helpers for text sent to the renderer
+*/
+
+/// Shorten `text` to at most `max_length` bytes, on a character boundary
+pub fn truncate_text(mut text: String, max_length: usize) -> String {
+    if text.len() > max_length {
+        let end = (0..=max_length)
+            .rev()
+            .find(|&end| text.is_char_boundary(end))
+            .unwrap_or(0);
+        text.truncate(end);
+    }
+
+    text
+}
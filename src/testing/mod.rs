@@ -0,0 +1,3 @@
+mod temp_dir;
+
+pub use temp_dir::*;
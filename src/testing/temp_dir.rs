@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    temp_dir.rs:
+        Temporary directories for unit tests
+*/
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Number of directories created by this process
+static DIR_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A temporary directory, removed when dropped
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Create a new, empty directory
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "tlockr-unit-{}-{}",
+            std::process::id(),
+            DIR_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).expect("Failed to create temporary directory");
+
+        Self { path }
+    }
+
+    /// Returns the path of the directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write `contents` to the file `name`, creating its parent directories
+    pub fn write(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.path.join(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("Failed to create directory");
+        }
+        std::fs::write(&path, contents).expect("Failed to write file");
+
+        path
+    }
+
+    /// Create the directory `name`, and its parents
+    pub fn create_dir(&self, name: &str) -> PathBuf {
+        let path = self.path.join(name);
+        std::fs::create_dir_all(&path).expect("Failed to create directory");
+
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
@@ -11,12 +11,18 @@ use crate::wayland::WaylandState;
 use wayland_client::{
     Connection, Dispatch, QueueHandle,
     protocol::{
-        wl_callback::WlCallback, wl_compositor::WlCompositor, wl_display::WlDisplay, wl_shm::WlShm,
-        wl_shm_pool::WlShmPool, wl_surface::WlSurface,
+        wl_buffer::WlBuffer, wl_callback::WlCallback, wl_compositor::WlCompositor,
+        wl_display::WlDisplay, wl_shm_pool::WlShmPool, wl_surface::WlSurface,
     },
 };
 use wayland_protocols::{
+    ext::image_capture_source::v1::client::{
+        ext_image_capture_source_v1::ExtImageCaptureSourceV1,
+        ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1,
+    },
+    ext::image_copy_capture::v1::client::ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1,
     ext::session_lock::v1::client::ext_session_lock_manager_v1::ExtSessionLockManagerV1,
+    wp::text_input::zv3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3,
     wp::viewporter::client::{wp_viewport::WpViewport, wp_viewporter::WpViewporter},
 };
 
@@ -44,8 +50,14 @@ empty_dispatch! {
     WpViewporter,
     ExtSessionLockManagerV1,
     WlSurface,
-    WlShm,
     WlShmPool,
     WpViewport,
-    WlCallback
+    WlCallback,
+    ZwpTextInputManagerV3,
+    // Buffers of screenshots are only read once captured, so their release
+    // is ignored
+    WlBuffer,
+    ExtOutputImageCaptureSourceManagerV1,
+    ExtImageCaptureSourceV1,
+    ExtImageCopyCaptureManagerV1
 }
@@ -7,30 +7,32 @@
         backend, and links with the rest of the application.
 */
 
+use crate::background::ScreenshotCapture;
 use crate::buffer::BufferManager;
-use crate::event::Event;
-use crate::ffi::{get_state, set_renderer_read_fd, set_renderer_write_fd, set_state};
+use crate::config::OutputRules;
+use crate::ffi::{get_state, set_buffer_format, set_renderer_write_fd, set_state};
+use crate::graphics::{OutputInfo, ThemeSurface};
+use crate::input::{Seat, TextInputField};
+use crate::media::MediaState;
+use crate::preview::PreviewState;
+use crate::provider::ProviderState;
 use crate::shared::State;
 use crate::shared::{ApplicationState, Pipe};
 
 use std::os::fd::AsRawFd;
-use std::time::Instant;
 use wayland_client::EventQueue;
-use wayland_client::protocol::wl_pointer::WlPointer;
 use wayland_client::{
     Connection,
-    protocol::{
-        wl_compositor::WlCompositor, wl_display::WlDisplay, wl_keyboard::WlKeyboard,
-        wl_output::WlOutput, wl_registry::WlRegistry, wl_seat::WlSeat, wl_surface::WlSurface,
-    },
+    protocol::{wl_compositor::WlCompositor, wl_display::WlDisplay, wl_registry::WlRegistry},
 };
 use wayland_protocols::{
+    ext::image_capture_source::v1::client::ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1,
+    ext::image_copy_capture::v1::client::ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1,
     ext::session_lock::v1::client::{
-        ext_session_lock_manager_v1::ExtSessionLockManagerV1,
-        ext_session_lock_surface_v1::ExtSessionLockSurfaceV1,
-        ext_session_lock_v1::ExtSessionLockV1,
+        ext_session_lock_manager_v1::ExtSessionLockManagerV1, ext_session_lock_v1::ExtSessionLockV1,
     },
-    wp::viewporter::client::{wp_viewport::WpViewport, wp_viewporter::WpViewporter},
+    wp::text_input::zv3::client::zwp_text_input_manager_v3::ZwpTextInputManagerV3,
+    wp::viewporter::client::wp_viewporter::WpViewporter,
 };
 
 /// Main state structure
@@ -41,36 +43,38 @@ pub struct WaylandState {
     pub display: Option<WlDisplay>,
     pub registry: Option<WlRegistry>,
 
-    pub output: Option<WlOutput>,
+    pub outputs: Vec<OutputInfo>,
+    pub output_rules: OutputRules,
     pub compositor: Option<WlCompositor>,
-    pub seat: Option<WlSeat>,
+    pub seats: Vec<Seat>,
     pub viewporter: Option<WpViewporter>,
+    pub text_input_manager: Option<ZwpTextInputManagerV3>,
 
-    pub surface: Option<WlSurface>,
+    /// The surfaces the theme is shown on, one for each output, with the
+    /// primary output first
+    pub surfaces: Vec<ThemeSurface>,
 
+    /// Holds the shm object and buffer format, shared by the buffers of
+    /// every output
     pub buffer_manager: BufferManager,
 
+    pub image_capture_source_manager: Option<ExtOutputImageCaptureSourceManagerV1>,
+    pub image_copy_capture_manager: Option<ExtImageCopyCaptureManagerV1>,
+    pub screenshots: Vec<ScreenshotCapture>,
+
     pub session_lock_manager: Option<ExtSessionLockManagerV1>,
     pub session_lock: Option<ExtSessionLockV1>,
-    pub session_lock_surface: Option<ExtSessionLockSurfaceV1>,
-
-    pub keyboard: Option<WlKeyboard>,
-    pub pointer: Option<WlPointer>,
 
-    pub viewport: Option<WpViewport>,
-
-    pub width: i32,
-    pub height: i32,
-
-    pub output_configured: bool,
+    pub text_input_field: Option<TextInputField>,
 
     pub app_state: *mut ApplicationState,
 
+    /// Pipe for events sent by the renderers of every output
     pub renderer_read_pipe: Option<Pipe>,
-    pub renderer_write_pipe: Option<Pipe>,
 
-    pub pointer_timestamp: Option<Instant>,
-    pub pending_pointer_event: Option<Event>,
+    pub preview: Option<PreviewState>,
+    pub providers: Option<ProviderState>,
+    pub media: Option<MediaState>,
 }
 
 impl WaylandState {
@@ -82,26 +86,25 @@ impl WaylandState {
             connection: None,
             display: None,
             registry: None,
-            output: None,
+            outputs: Vec::new(),
+            output_rules: OutputRules::new(),
             compositor: None,
-            seat: None,
+            seats: Vec::new(),
             viewporter: None,
-            surface: None,
+            text_input_manager: None,
+            surfaces: Vec::new(),
             buffer_manager: BufferManager::new(),
+            image_capture_source_manager: None,
+            image_copy_capture_manager: None,
+            screenshots: Vec::new(),
             session_lock_manager: None,
             session_lock: None,
-            session_lock_surface: None,
-            keyboard: None,
-            pointer: None,
-            viewport: None,
-            width: -1,
-            height: -1,
-            output_configured: false,
+            text_input_field: None,
             app_state: app_state,
             renderer_read_pipe: None,
-            renderer_write_pipe: None,
-            pointer_timestamp: None,
-            pending_pointer_event: None,
+            preview: None,
+            providers: None,
+            media: None,
         }
     }
 
@@ -124,7 +127,7 @@ impl WaylandState {
 
     /// Returns a boolean value indicating whether this object instance is ready for use
     pub fn ready(&self) -> bool {
-        return self.output_configured;
+        !self.surfaces.is_empty()
     }
 
     /// Prepare the object for use
@@ -136,16 +139,14 @@ impl WaylandState {
         set_state(self.app_state, State::Initialized);
 
         let renderer_read_pipe = Pipe::new()?;
-        let renderer_write_pipe = Pipe::new()?;
 
         // The naming is weird here
-        // The renderer's read fd is the readable side of our write pipe
-        // The renderer's write fd is the writeable side of our read pipe
-        set_renderer_read_fd(self.app_state, renderer_write_pipe.read_fd().as_raw_fd());
+        // The renderer's write fd is the writeable side of our read pipe,
+        // which is shared by every renderer. Each renderer reads from its
+        // own pipe, held by its `ThemeSurface`
         set_renderer_write_fd(self.app_state, renderer_read_pipe.write_fd().as_raw_fd());
 
         self.renderer_read_pipe = Some(renderer_read_pipe);
-        self.renderer_write_pipe = Some(renderer_write_pipe);
 
         Ok(event_queue)
     }
@@ -167,20 +168,42 @@ impl WaylandState {
         match get_state(self.app_state)
             .ok_or::<Box<dyn std::error::Error>>("Failed to get application state".into())?
         {
-            State::Initialized => {
-                if self.ready() {
-                    set_state(self.app_state, State::Ready);
-                }
+            State::Initialized if self.ready() => {
+                set_state(self.app_state, State::Ready);
             }
             State::Ready => {
-                self.buffer_manager
-                    .set_output_dimensions(self.width, self.height);
-                self.buffer_manager.allocate_buffers(event_queue, 2)?;
-                self.initialize_renderer()?;
-                self.lock(event_queue)?;
+                self.apply_preview_size();
+                let format = self.buffer_manager.select_format();
+                set_buffer_format(self.app_state, format as u32);
+
+                for surface in self.surfaces.iter_mut() {
+                    let mut buffer_manager = self.buffer_manager.for_output();
+                    buffer_manager.set_output_dimensions(
+                        surface.output_state.width,
+                        surface.output_state.height,
+                    );
+                    buffer_manager.allocate_buffers(event_queue, 2)?;
+                    *surface.buffer_manager = buffer_manager;
+                }
+
+                self.initialize_renderers()?;
+
+                if self.preview.is_some() {
+                    self.open_preview_window(event_queue)?;
+                } else {
+                    self.lock(event_queue)?;
+                }
+            }
+            State::Locked => {
+                self.update_theme_reload()?;
+                self.update_providers()?;
             }
             State::Unlocking => {
-                self.unlock(event_queue)?;
+                if self.preview.is_some() {
+                    self.close_preview_window()?;
+                } else {
+                    self.unlock(event_queue)?;
+                }
             }
             _ => {}
         }
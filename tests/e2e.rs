@@ -0,0 +1,584 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    e2e.rs:
+        End-to-end tests, running tlockr against a headless compositor with
+        the mock authenticator.
+*/
+
+mod harness;
+
+use harness::{
+    DEFAULT_TIMEOUT, Frame, LockEvent, MockNotificationServer, MockPlayer, OUTPUT_HEIGHT,
+    OUTPUT_WIDTH, SCREENSHOT_COLOUR, SECOND_OUTPUT_NAME, SessionOptions, TEST_OUTPUT_NAME, TestBus,
+    TestHome, TestSession, fixture_path,
+};
+use std::path::Path;
+
+/// Background colour of the `unlock` fixture theme
+const BACKGROUND: (u8, u8, u8) = (0x33, 0x66, 0x99);
+
+/// Background colour of the `unlock` fixture theme after a failed attempt
+const FAILED_BACKGROUND: (u8, u8, u8) = (0x99, 0x33, 0x33);
+
+/// Background colour fixture themes turn once what they check has been seen
+const PASS_BACKGROUND: (u8, u8, u8) = (0x33, 0x99, 0x66);
+
+/// Background colour of the `media` fixture theme once the mock player pauses
+const MEDIA_PAUSED_BACKGROUND: (u8, u8, u8) = (0x99, 0x66, 0x33);
+
+/// Background colour of the `notifications` fixture theme for a notification
+/// with unexpected text
+const NOTIFICATION_BACKGROUND: (u8, u8, u8) = (0x99, 0x66, 0x33);
+
+/// Colour of the wallpaper written by `wallpaper_is_shown_by_theme`
+const WALLPAPER_COLOUR: (u8, u8, u8) = (0x66, 0x99, 0x33);
+
+/// Returns whether `(r, g, b)` is `colour`, allowing for rounding
+fn colour_is((r, g, b): (u8, u8, u8), colour: (u8, u8, u8)) -> bool {
+    r.abs_diff(colour.0) <= 2 && g.abs_diff(colour.1) <= 2 && b.abs_diff(colour.2) <= 2
+}
+
+/// Returns whether the centre of `frame` is `colour`, allowing for rounding
+fn centre_is(frame: &Frame, colour: (u8, u8, u8)) -> bool {
+    colour_is(frame.centre(), colour)
+}
+
+/// Write a PNG image of a single colour to `path`
+fn write_png(path: &Path, width: u32, height: u32, (r, g, b): (u8, u8, u8)) {
+    let file = std::fs::File::create(path).unwrap();
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data = [r, g, b].repeat((width * height) as usize);
+    encoder
+        .write_header()
+        .unwrap()
+        .write_image_data(&data)
+        .unwrap();
+}
+
+/// Returns the size of the PNG image at `path`, and the colour of its centre
+fn read_png(path: &Path) -> ((u32, u32), (u8, u8, u8)) {
+    let file = std::fs::File::open(path).unwrap();
+    let mut reader = png::Decoder::new(std::io::BufReader::new(file))
+        .read_info()
+        .unwrap();
+
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).unwrap();
+    assert_eq!(info.bit_depth, png::BitDepth::Eight);
+
+    let offset = (info.height / 2) as usize * info.line_size
+        + (info.width / 2) as usize * info.color_type.samples();
+    (
+        (info.width, info.height),
+        (data[offset], data[offset + 1], data[offset + 2]),
+    )
+}
+
+/// Start the `unlock` theme, and wait for its first frame to be displayed
+fn start_locked(args: &[&str]) -> TestSession {
+    let mut session = TestSession::start(&fixture_path("unlock"), args);
+
+    session.wait_for("the theme to be displayed", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .iter()
+            .any(|frame| centre_is(frame, BACKGROUND))
+    });
+
+    session
+}
+
+#[test]
+fn locks_session_and_displays_theme() {
+    let session = start_locked(&[]);
+    let state = &session.compositor.state;
+
+    assert_eq!(
+        state.events,
+        vec![LockEvent::Locked, LockEvent::LockSurfaceCreated]
+    );
+
+    let frame = state.frames.last().unwrap();
+    assert_eq!(frame.width, OUTPUT_WIDTH);
+    assert_eq!(frame.height, OUTPUT_HEIGHT);
+    assert!(centre_is(frame, BACKGROUND));
+}
+
+#[test]
+fn correct_password_unlocks_session() {
+    let mut session = start_locked(&["--mock-password", "hunter"]);
+
+    session.submit("hunter");
+    session.wait_for("the session to unlock", DEFAULT_TIMEOUT, |state| {
+        state.has_event(LockEvent::Unlocked)
+    });
+
+    let status = session.wait_exit(DEFAULT_TIMEOUT);
+    assert!(status.success(), "tlockr exited with {status}");
+}
+
+#[test]
+fn incorrect_password_keeps_session_locked() {
+    let mut session = start_locked(&["--mock-password", "hunter"]);
+
+    session.submit("wrong");
+    session.wait_for("the failure to be displayed", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, FAILED_BACKGROUND))
+    });
+    assert!(!session.compositor.state.has_event(LockEvent::Unlocked));
+
+    // The session must still be unlockable after a failed attempt
+    session.submit("hunter");
+    session.wait_for("the session to unlock", DEFAULT_TIMEOUT, |state| {
+        state.has_event(LockEvent::Unlocked)
+    });
+}
+
+#[test]
+fn failing_authenticator_never_unlocks() {
+    let mut session = start_locked(&["--mock-fail"]);
+
+    session.submit("anything");
+    session.wait_for("the failure to be displayed", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, FAILED_BACKGROUND))
+    });
+
+    session.run_for(std::time::Duration::from_millis(500));
+    assert!(!session.compositor.state.has_event(LockEvent::Unlocked));
+}
+
+#[test]
+fn providers_read_configured_sysfs_paths() {
+    let sysfs = fixture_path("sysfs");
+    let config = format!(
+        "[providers]\npower_supply_path = {:?}\nnet_path = {:?}\n",
+        sysfs.join("power_supply"),
+        sysfs.join("net"),
+    );
+
+    let options = SessionOptions {
+        config,
+        ..Default::default()
+    };
+    let mut session = TestSession::start_with(&fixture_path("providers"), &[], &options);
+
+    session.wait_for("the status to be displayed", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, PASS_BACKGROUND))
+    });
+}
+
+#[test]
+fn theme_settings_are_overridden_by_config() {
+    let config = "[themes.settings]\n\
+        background = \"#339966\"\n\
+        enabled = true\n\
+        scale = 1.5\n"
+        .to_string();
+
+    let options = SessionOptions {
+        config,
+        ..Default::default()
+    };
+    let mut session = TestSession::start_with(&fixture_path("settings"), &[], &options);
+
+    session.wait_for("the settings to be applied", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, PASS_BACKGROUND))
+    });
+}
+
+#[test]
+fn theme_variant_overrides_inherited_settings() {
+    let theme = format!("{}:matched", fixture_path("extended").display());
+    let mut session = TestSession::start(Path::new(&theme), &[]);
+
+    session.wait_for("the variant to be applied", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, PASS_BACKGROUND))
+    });
+}
+
+#[test]
+fn color_scheme_selects_theme_variant() {
+    let options = SessionOptions {
+        config: "[appearance]\ncolor_scheme = \"dark\"\n".to_string(),
+        ..Default::default()
+    };
+    let mut session = TestSession::start_with(&fixture_path("scheme"), &[], &options);
+
+    session.wait_for("the dark variant to be applied", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, PASS_BACKGROUND))
+    });
+}
+
+#[test]
+fn output_rule_replaces_configured_theme() {
+    // `other` does not match, so its missing theme is never loaded
+    let config = format!(
+        "[outputs.other]\n\
+        name = \"OTHER-1\"\n\
+        theme = \"missing\"\n\
+        primary = true\n\n\
+        [outputs.test]\n\
+        name = \"{}\"\n\
+        theme = \"{}:matched\"\n",
+        TEST_OUTPUT_NAME,
+        fixture_path("extended").display()
+    );
+
+    let options = SessionOptions {
+        config,
+        ..Default::default()
+    };
+    let mut session = TestSession::start_with(&fixture_path("unlock"), &[], &options);
+
+    session.wait_for("the output's theme to be shown", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, PASS_BACKGROUND))
+    });
+}
+
+#[test]
+fn every_output_is_locked() {
+    // The second output is made primary, so it is not just the first output
+    let config = format!(
+        "[outputs.second]\n\
+        name = \"{}\"\n\
+        primary = true\n",
+        SECOND_OUTPUT_NAME
+    );
+
+    let options = SessionOptions {
+        config,
+        second_output: true,
+        ..Default::default()
+    };
+    let mut session = TestSession::start_with(&fixture_path("outputs"), &[], &options);
+
+    session.wait_for("both outputs to be locked", DEFAULT_TIMEOUT, |state| {
+        state
+            .last_frame_on(SECOND_OUTPUT_NAME)
+            .is_some_and(|frame| centre_is(frame, PASS_BACKGROUND))
+            && state
+                .last_frame_on(TEST_OUTPUT_NAME)
+                .is_some_and(|frame| centre_is(frame, BACKGROUND))
+    });
+
+    let state = &session.compositor.state;
+    assert_eq!(
+        state
+            .events
+            .iter()
+            .filter(|event| **event == LockEvent::LockSurfaceCreated)
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn screenshot_is_shown_by_theme() {
+    let options = SessionOptions {
+        config: "[background]\nscreenshot = true\n".to_string(),
+        ..Default::default()
+    };
+    let mut session = TestSession::start_with(&fixture_path("background"), &[], &options);
+
+    session.wait_for("the screenshot to be shown", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, SCREENSHOT_COLOUR))
+    });
+}
+
+#[test]
+fn wallpaper_is_shown_by_theme() {
+    let home = TestHome::new();
+    let wallpaper = home.dir.join("wallpaper.png");
+    write_png(&wallpaper, 64, 32, WALLPAPER_COLOUR);
+
+    let options = SessionOptions {
+        config: format!("[background]\nwallpaper = \"{}\"\n", wallpaper.display()),
+        ..Default::default()
+    };
+    let mut session = TestSession::start_with(&fixture_path("background"), &[], &options);
+
+    session.wait_for("the wallpaper to be shown", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, WALLPAPER_COLOUR))
+    });
+}
+
+#[test]
+fn theme_images_are_served_by_image_provider() {
+    let mut session = TestSession::start(&fixture_path("images"), &[]);
+
+    session.wait_for(
+        "the theme's images to be loaded",
+        DEFAULT_TIMEOUT,
+        |state| {
+            state
+                .frames
+                .last()
+                .is_some_and(|frame| centre_is(frame, PASS_BACKGROUND))
+        },
+    );
+}
+
+#[test]
+fn extended_theme_assets_are_loaded() {
+    let mut session = TestSession::start(&fixture_path("inherited"), &[]);
+
+    session.wait_for(
+        "the extended theme's images to be loaded",
+        DEFAULT_TIMEOUT,
+        |state| {
+            state
+                .frames
+                .last()
+                .is_some_and(|frame| centre_is(frame, PASS_BACKGROUND))
+        },
+    );
+}
+
+#[test]
+fn themes_cannot_load_files_outside_their_directory() {
+    let mut session = TestSession::start(&fixture_path("sandbox"), &[]);
+
+    session.wait_for("the outside file to be blocked", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, PASS_BACKGROUND))
+    });
+}
+
+#[test]
+fn theme_is_rendered_to_png() {
+    let home = TestHome::new();
+    let out = home.dir.join("frame.png");
+
+    let output = home.run(&[
+        "render",
+        fixture_path("unlock").to_str().unwrap(),
+        "--size",
+        "64x48",
+        "--out",
+        out.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+
+    let (size, centre) = read_png(&out);
+    assert_eq!(size, (64, 48));
+    assert!(colour_is(centre, BACKGROUND));
+}
+
+#[test]
+fn main_qml_file_must_be_inside_theme() {
+    let home = TestHome::new();
+    let theme = home.dir.join("escape");
+    std::fs::create_dir_all(&theme).unwrap();
+    std::fs::write(home.dir.join("main.qml"), "import QtQuick 2.15\nItem {}\n").unwrap();
+    std::fs::write(
+        theme.join("theme.toml"),
+        "[theme]\nname = \"escape\"\n\n[qml]\nmain = \"../main.qml\"\n",
+    )
+    .unwrap();
+
+    let output = home.run(&["theme", "install", theme.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(!home.theme_dir().join("escape").exists());
+
+    let out = home.dir.join("frame.png");
+    let output = home.run(&[
+        "render",
+        theme.to_str().unwrap(),
+        "--out",
+        out.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+    assert!(!out.exists());
+}
+
+#[test]
+fn themes_are_installed_listed_and_removed() {
+    let home = TestHome::new();
+    let theme = fixture_path("settings");
+    let theme = theme.to_str().unwrap();
+
+    let output = home.run(&["theme", "install", theme]);
+    assert!(output.status.success());
+    assert!(
+        home.theme_dir()
+            .join("settings")
+            .join("theme.toml")
+            .is_file()
+    );
+
+    // Installing again needs --force
+    let output = home.run(&["theme", "install", theme]);
+    assert!(!output.status.success());
+
+    let output = home.run(&["theme", "list"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Version:   0.1.0"));
+    assert!(stdout.contains("License:   GPL-3.0-or-later"));
+
+    let output = home.run(&["theme", "info", "settings"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("background (color, default \"#996633\"): Background colour"));
+
+    let output = home.run(&["theme", "remove", "settings"]);
+    assert!(output.status.success());
+    assert!(!home.theme_dir().join("settings").exists());
+
+    let output = home.run(&["theme", "list"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No themes installed"));
+}
+
+#[test]
+fn user_themes_take_precedence_over_packaged_themes() {
+    let home = TestHome::new();
+    let theme = fixture_path("settings");
+
+    std::fs::create_dir_all(home.data_theme_dir()).unwrap();
+    let status = std::process::Command::new("cp")
+        .arg("-r")
+        .arg(&theme)
+        .arg(home.data_theme_dir().join("settings"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = home.run(&["theme", "info", "settings"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&home.data_theme_dir().display().to_string()));
+
+    let output = home.run(&["theme", "install", theme.to_str().unwrap()]);
+    assert!(output.status.success());
+
+    let output = home.run(&["theme", "info", "settings"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&home.theme_dir().display().to_string()));
+
+    let output = home.run(&["theme", "list"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("settings (hidden)"));
+}
+
+#[test]
+fn media_player_is_shown_and_controlled() {
+    let bus = TestBus::start();
+    let player = MockPlayer::start(&bus, "Test Title", "Test Artist");
+
+    let options = SessionOptions {
+        env: vec![("DBUS_SESSION_BUS_ADDRESS".to_string(), bus.address.clone())],
+        ..Default::default()
+    };
+    let mut session = TestSession::start_with(&fixture_path("media"), &[], &options);
+
+    session.wait_for("the player to be displayed", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, PASS_BACKGROUND))
+    });
+
+    session.submit("");
+    session.wait_for("the player to pause", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, MEDIA_PAUSED_BACKGROUND))
+    });
+
+    assert_eq!(player.state.lock().unwrap().actions, vec!["PlayPause"]);
+}
+
+/// Start the `notifications` theme with all notification text shown, and a
+/// mock notification server on `bus`
+fn start_notifications(bus: &TestBus) -> TestSession {
+    let options = SessionOptions {
+        config: "[notifications]\nenabled = true\nprivacy = \"full\"\n".to_string(),
+        env: vec![("DBUS_SESSION_BUS_ADDRESS".to_string(), bus.address.clone())],
+        ..Default::default()
+    };
+
+    TestSession::start_with(&fixture_path("notifications"), &[], &options)
+}
+
+/// Send a notification until the centre of the screen is `colour`
+///
+/// The notification monitor starts in the background, so earlier attempts
+/// can be missed. Each attempt replaces the first, so only one notification
+/// is shown. Returns the ID of the notification.
+fn notify_until(
+    session: &mut TestSession,
+    server: &MockNotificationServer,
+    summary: &str,
+    colour: (u8, u8, u8),
+) -> u32 {
+    let deadline = std::time::Instant::now() + DEFAULT_TIMEOUT;
+    let id = server.notify("Test App", 0, summary, "Test Body");
+
+    loop {
+        session.run_for(std::time::Duration::from_millis(200));
+
+        let shown = session
+            .compositor
+            .state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, colour));
+        if shown {
+            return id;
+        }
+
+        assert!(
+            std::time::Instant::now() < deadline,
+            "Timed out waiting for the notification to be displayed"
+        );
+        server.notify("Test App", id, summary, "Test Body");
+    }
+}
+
+#[test]
+fn notifications_are_shown_while_locked() {
+    let bus = TestBus::start();
+    let server = MockNotificationServer::start(&bus);
+    let mut session = start_notifications(&bus);
+
+    notify_until(&mut session, &server, "First", NOTIFICATION_BACKGROUND);
+
+    server.notify("Test App", 0, "Second", "Test Body");
+    session.wait_for("the second notification", DEFAULT_TIMEOUT, |state| {
+        state
+            .frames
+            .last()
+            .is_some_and(|frame| centre_is(frame, PASS_BACKGROUND))
+    });
+}
@@ -0,0 +1,14 @@
+import QtQuick 2.15
+
+// Theme used by the end-to-end tests of background images
+//
+// The screenshot of the output is shown if there is one, otherwise the
+// wallpaper.
+Rectangle {
+    color: "#336699"
+
+    Image {
+        anchors.fill: parent
+        source: tlockr.Screenshot.toString() !== "" ? tlockr.Screenshot : tlockr.Wallpaper
+    }
+}
@@ -0,0 +1,37 @@
+import QtQuick 2.15
+
+// Theme used by the end-to-end tests of the image provider
+//
+// The background is green once a theme asset has been decoded at the
+// requested size, a colour has been generated, and an asset outside the
+// theme has been blocked.
+Rectangle {
+    readonly property bool loaded: asset.status === Image.Ready
+        && asset.implicitWidth === 16 && asset.implicitHeight === 8
+        && generated.status === Image.Ready
+        && generated.implicitWidth === 4 && generated.implicitHeight === 4
+        && outside.status === Image.Error
+
+    color: loaded ? "#339966" : "#996633"
+
+    Image {
+        id: asset
+        visible: false
+        source: "image://tlockr/theme/asset.png"
+        sourceSize.width: 16
+    }
+
+    Image {
+        id: generated
+        visible: false
+        source: "image://tlockr/color/336699"
+        sourceSize.width: 4
+    }
+
+    // Encoded, so the URL is not normalised before reaching the provider
+    Image {
+        id: outside
+        visible: false
+        source: "image://tlockr/theme/..%2Foutside.png"
+    }
+}
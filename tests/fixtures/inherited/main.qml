@@ -0,0 +1,24 @@
+import QtQuick 2.15
+
+// Theme used by the end-to-end tests of theme inheritance
+//
+// The background is green once the asset of the extended theme has been
+// loaded both from the image provider and by its path.
+Rectangle {
+    readonly property bool loaded: served.status === Image.Ready
+        && file.status === Image.Ready
+
+    color: loaded ? "#339966" : "#996633"
+
+    Image {
+        id: served
+        visible: false
+        source: "image://tlockr/theme/asset.png"
+    }
+
+    Image {
+        id: file
+        visible: false
+        source: "../images/asset.png"
+    }
+}
@@ -0,0 +1,26 @@
+import QtQuick 2.15
+
+// Theme used by the end-to-end tests of media player support
+//
+// The background turns green while the mock player is playing, and orange
+// once it is paused. Return toggles playback.
+Rectangle {
+    readonly property bool playerMatches: tlockr.media.available
+        && tlockr.media.playerName === "Mock Player"
+        && tlockr.media.title === "Test Title"
+        && tlockr.media.artist === "Test Artist"
+        && tlockr.media.canPause
+
+    color: !playerMatches ? "#336699"
+        : tlockr.media.playing ? "#339966"
+        : tlockr.media.playbackStatus === "Paused" ? "#996633"
+        : "#336699"
+    focus: true
+
+    Keys.onPressed: (event) => {
+        if (event.key === Qt.Key_Return) {
+            tlockr.media.playPause()
+        }
+        event.accepted = true
+    }
+}
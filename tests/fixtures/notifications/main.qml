@@ -0,0 +1,27 @@
+import QtQuick 2.15
+
+// Theme used by the end-to-end tests of notification support
+//
+// Each notification covers the screen, with later notifications on top. A
+// notification is green if it is the second notification with the expected
+// text, and orange otherwise.
+Rectangle {
+    color: "#336699"
+
+    Repeater {
+        model: tlockr.notifications
+
+        delegate: Rectangle {
+            required property string appName
+            required property string summary
+            required property string body
+
+            readonly property bool matches: appName === "Test App"
+                && summary === "Second" && body === "Test Body"
+
+            anchors.fill: parent
+            color: matches && tlockr.notifications.count === 2 ? "#339966"
+                : "#996633"
+        }
+    }
+}
@@ -0,0 +1,9 @@
+import QtQuick 2.15
+
+// Theme used by the end-to-end tests of multiple outputs
+//
+// The background is the pass colour on the primary output, and the default
+// colour on every other output.
+Rectangle {
+    color: tlockr.isPrimary ? "#339966" : "#336699"
+}
@@ -0,0 +1,17 @@
+import QtQuick 2.15
+
+// Theme used by the end-to-end tests of the status providers
+//
+// The background turns green once the battery and network status read from
+// the fake sysfs directories in `tests/fixtures/sysfs` have been received.
+Rectangle {
+    readonly property bool batteryMatches: tlockr.battery.present
+        && tlockr.battery.level === 42
+        && tlockr.battery.charging
+        && tlockr.battery.pluggedIn
+    readonly property bool networkMatches: tlockr.network.connected
+        && tlockr.network.wireless
+        && tlockr.network.interfaceName === "wlan0"
+
+    color: batteryMatches && networkMatches ? "#339966" : "#336699"
+}
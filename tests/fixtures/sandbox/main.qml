@@ -0,0 +1,22 @@
+import QtQuick 2.15
+
+// Theme used by the end-to-end tests of the theme sandbox
+//
+// The background is green once a file inside the theme has loaded, and a
+// file outside it has been blocked.
+Rectangle {
+    readonly property bool sandboxed: inside.status === Loader.Ready
+        && outside.status !== Loader.Ready
+
+    color: sandboxed ? "#339966" : "#996633"
+
+    Loader {
+        id: inside
+        source: "inside.qml"
+    }
+
+    Loader {
+        id: outside
+        source: "../unlock/main.qml"
+    }
+}
@@ -0,0 +1,13 @@
+import QtQuick 2.15
+
+// Theme used by the end-to-end tests of theme settings
+//
+// The background is the `background` setting once `enabled` is overridden,
+// and the other settings have their expected values.
+Rectangle {
+    readonly property bool settingsMatch: tlockr.settings.enabled
+        && tlockr.settings.label === "Default"
+        && tlockr.settings.scale === 1.5
+
+    color: settingsMatch ? tlockr.settings.background : "#336699"
+}
@@ -0,0 +1,33 @@
+import QtQuick 2.15
+
+// Minimal theme used by the end-to-end tests
+//
+// Typed text is collected by the root item, and submitted on Return. The
+// background turns red when authentication fails, so tests can observe the
+// authentication state from the rendered frames.
+Rectangle {
+    id: root
+    color: "#336699"
+    focus: true
+
+    property string password: ""
+
+    Keys.onPressed: (event) => {
+        if (event.key === Qt.Key_Return) {
+            tlockr.sendAuthSubmit(password)
+            password = ""
+        } else {
+            password += event.text
+        }
+        event.accepted = true
+    }
+
+    Connections {
+        target: tlockr
+        function onAuthStateChange(state) {
+            if (state === 1) {
+                root.color = "#993333"
+            }
+        }
+    }
+}
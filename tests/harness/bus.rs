@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    bus.rs:
+        A private D-Bus session bus, so tests never talk to services on the
+        real session bus.
+*/
+
+use std::{
+    io::{BufRead, BufReader},
+    process::{Child, Command, Stdio},
+};
+
+/// A `dbus-daemon` process running a private session bus
+pub struct TestBus {
+    daemon: Child,
+    pub address: String,
+}
+
+impl TestBus {
+    /// Start a new session bus
+    pub fn start() -> Self {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to start dbus-daemon");
+
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take().unwrap())
+            .read_line(&mut address)
+            .expect("Failed to read the bus address");
+
+        Self {
+            daemon,
+            address: address.trim().to_string(),
+        }
+    }
+}
+
+impl Drop for TestBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    command.rs:
+        Runs tlockr commands that exit without locking, in an isolated home
+        directory.
+*/
+
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Number of homes created by this process
+static HOME_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// An empty home directory, removed when dropped
+pub struct TestHome {
+    pub dir: PathBuf,
+}
+
+impl TestHome {
+    /// Create a new home directory
+    pub fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "tlockr-home-{}-{}",
+            std::process::id(),
+            HOME_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("Failed to create home directory");
+
+        Self { dir }
+    }
+
+    /// Returns the directory themes are installed to for the user
+    pub fn theme_dir(&self) -> PathBuf {
+        self.dir.join(".config").join("tlockr").join("themes")
+    }
+
+    /// Returns the directory packaged themes are found in, through
+    /// `XDG_DATA_DIRS`
+    pub fn data_theme_dir(&self) -> PathBuf {
+        self.dir
+            .join("usr")
+            .join("share")
+            .join("tlockr")
+            .join("themes")
+    }
+
+    /// Run tlockr with `args`, and wait for it to exit
+    ///
+    /// All XDG base directories are inside the home directory, so themes and
+    /// configuration installed on the host are not used.
+    pub fn run(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_tlockr"))
+            .args(args)
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", &self.dir)
+            .env("XDG_CONFIG_HOME", self.dir.join(".config"))
+            .env("XDG_DATA_HOME", self.dir.join(".local").join("share"))
+            .env("XDG_CONFIG_DIRS", self.dir.join("etc").join("xdg"))
+            .env("XDG_DATA_DIRS", self.dir.join("usr").join("share"))
+            // `render` draws themes without a GPU, as in `TestSession`
+            .env("LIBGL_ALWAYS_SOFTWARE", "1")
+            .envs(
+                std::env::vars_os()
+                    .filter(|(key, _)| matches!(key.to_str(), Some("DISPLAY" | "XAUTHORITY"))),
+            )
+            .output()
+            .expect("Failed to run tlockr")
+    }
+}
+
+impl Drop for TestHome {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
@@ -0,0 +1,783 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    compositor.rs:
+        A minimal in-process Wayland compositor, implementing just enough of
+        `wl_compositor`, `wl_shm`, `wl_seat`, `wl_output`, `wp_viewporter`,
+        `ext_session_lock_v1` and `ext_image_copy_capture_v1` to drive tlockr,
+        and record what it displays.
+*/
+
+use crate::harness::{keycode_for, keymap_text};
+
+use nix::poll::{PollFd, PollFlags, PollTimeout, poll};
+use nix::sys::memfd::{MFdFlags, memfd_create};
+use std::{
+    fs::File,
+    io::Write,
+    os::{fd::AsFd, unix::fs::FileExt},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use wayland_protocols::{
+    ext::image_capture_source::v1::server::ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1,
+    ext::image_copy_capture::v1::server::ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1,
+    ext::session_lock::v1::server::{
+        ext_session_lock_manager_v1::{self, ExtSessionLockManagerV1},
+        ext_session_lock_surface_v1::{self, ExtSessionLockSurfaceV1},
+        ext_session_lock_v1::{self, ExtSessionLockV1},
+    },
+    wp::viewporter::server::{
+        wp_viewport::{self, WpViewport},
+        wp_viewporter::{self, WpViewporter},
+    },
+};
+use wayland_server::{
+    Client, DataInit, Dispatch, Display, DisplayHandle, GlobalDispatch, ListeningSocket, New,
+    Resource, WEnum,
+    protocol::{
+        wl_buffer::{self, WlBuffer},
+        wl_callback::WlCallback,
+        wl_compositor::{self, WlCompositor},
+        wl_keyboard::{self, KeyState, KeymapFormat, WlKeyboard},
+        wl_output::{self, WlOutput},
+        wl_pointer::WlPointer,
+        wl_region::WlRegion,
+        wl_seat::{self, Capability, WlSeat},
+        wl_shm::{self, Format, WlShm},
+        wl_shm_pool::{self, WlShmPool},
+        wl_surface::{self, WlSurface},
+        wl_touch::WlTouch,
+    },
+};
+
+/// A single frame committed by the client, copied out of its shm buffer
+///
+/// `output` is the name of the output the frame was shown on.
+#[derive(Clone)]
+pub struct Frame {
+    pub output: String,
+    pub width: i32,
+    pub height: i32,
+    pub stride: i32,
+    pub format: Format,
+    pub data: Vec<u8>,
+}
+
+impl Frame {
+    /// Returns the (red, green, blue) components of a pixel
+    ///
+    /// Only 8-bit per channel formats are supported, which are stored as
+    /// little-endian `[B, G, R, A]`.
+    pub fn rgb(&self, x: i32, y: i32) -> (u8, u8, u8) {
+        assert!(
+            matches!(self.format, Format::Argb8888 | Format::Xrgb8888),
+            "Unsupported frame format: {:?}",
+            self.format
+        );
+
+        let offset = (y * self.stride + x * 4) as usize;
+        let pixel = &self.data[offset..offset + 4];
+        (pixel[2], pixel[1], pixel[0])
+    }
+
+    /// Returns the (red, green, blue) components of the centre pixel
+    pub fn centre(&self) -> (u8, u8, u8) {
+        self.rgb(self.width / 2, self.height / 2)
+    }
+}
+
+/// Events observed from the client, in the order they happened
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LockEvent {
+    Locked,
+    LockSurfaceCreated,
+    Unlocked,
+}
+
+/// Data attached to each `wl_buffer`, describing where its pixels live
+pub struct ShmBuffer {
+    pool: Arc<File>,
+    offset: i32,
+    width: i32,
+    height: i32,
+    stride: i32,
+    format: Format,
+}
+
+impl ShmBuffer {
+    /// Copy the buffer's current contents into a `Frame` shown on `output`
+    fn read_frame(&self, output: &str) -> std::io::Result<Frame> {
+        let mut data = vec![0u8; (self.stride * self.height) as usize];
+        self.pool.read_exact_at(&mut data, self.offset as u64)?;
+
+        Ok(Frame {
+            output: output.to_string(),
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            format: self.format,
+            data,
+        })
+    }
+
+    /// Fill the buffer with a single colour
+    pub fn fill(&self, (r, g, b): (u8, u8, u8)) -> std::io::Result<()> {
+        let row = [b, g, r, 0xFF].repeat(self.width as usize);
+        for y in 0..self.height {
+            self.pool
+                .write_all_at(&row, (self.offset + y * self.stride) as u64)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Double-buffered surface state
+#[derive(Default)]
+struct SurfaceData {
+    pending_buffer: Option<WlBuffer>,
+    pending_callbacks: Vec<WlCallback>,
+}
+
+/// State of the test compositor, inspected by tests
+pub struct ServerState {
+    pub output_width: i32,
+    pub output_height: i32,
+
+    pub events: Vec<LockEvent>,
+    /// Frames shown on every output, in the order they were committed
+    pub frames: Vec<Frame>,
+
+    /// Lock surfaces, with the name of the output each is shown on
+    lock_surfaces: Vec<(WlSurface, String)>,
+    keyboards: Vec<WlKeyboard>,
+    serial: u32,
+    start: Instant,
+}
+
+impl ServerState {
+    fn new(output_width: i32, output_height: i32) -> Self {
+        Self {
+            output_width,
+            output_height,
+            events: Vec::new(),
+            frames: Vec::new(),
+            lock_surfaces: Vec::new(),
+            keyboards: Vec::new(),
+            serial: 0,
+            start: Instant::now(),
+        }
+    }
+
+    fn next_serial(&mut self) -> u32 {
+        self.serial += 1;
+        self.serial
+    }
+
+    fn time(&self) -> u32 {
+        self.start.elapsed().as_millis() as u32
+    }
+
+    /// Returns whether `event` has been observed
+    pub fn has_event(&self, event: LockEvent) -> bool {
+        self.events.contains(&event)
+    }
+
+    /// Returns the last frame shown on the output named `output`
+    pub fn last_frame_on(&self, output: &str) -> Option<&Frame> {
+        self.frames
+            .iter()
+            .rev()
+            .find(|frame| frame.output == output)
+    }
+
+    /// Send keyboard focus for the first lock surface to a keyboard
+    fn send_enter(&mut self, keyboard: &WlKeyboard) {
+        if let Some((surface, _)) = self.lock_surfaces.first().cloned() {
+            let serial = self.next_serial();
+            keyboard.enter(serial, &surface, Vec::new());
+            keyboard.modifiers(serial, 0, 0, 0, 0);
+        }
+    }
+
+    /// Send a key press and release to every keyboard
+    pub fn tap_key(&mut self, key: u32) {
+        for state in [KeyState::Pressed, KeyState::Released] {
+            let serial = self.next_serial();
+            let time = self.time();
+            for keyboard in &self.keyboards {
+                keyboard.key(serial, time, key, state);
+            }
+        }
+    }
+
+    /// Type a string of lowercase letters, one key at a time
+    pub fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            let key = keycode_for(c).unwrap_or_else(|| panic!("No key for '{c}' in test keymap"));
+            self.tap_key(key);
+        }
+    }
+}
+
+/// An in-process Wayland compositor listening on a socket in `runtime_dir`
+pub struct TestCompositor {
+    display: Display<ServerState>,
+    socket: ListeningSocket,
+    socket_path: PathBuf,
+    pub state: ServerState,
+}
+
+impl TestCompositor {
+    /// Create a new compositor with an output of the given size for each
+    /// name in `output_names`
+    pub fn new(
+        runtime_dir: &Path,
+        output_width: i32,
+        output_height: i32,
+        output_names: &[&str],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let display = Display::<ServerState>::new()?;
+        let socket_path = runtime_dir.join("wayland-tlockr-test");
+        let socket = ListeningSocket::bind_absolute(socket_path.clone())?;
+
+        let dh = display.handle();
+        dh.create_global::<ServerState, WlCompositor, ()>(4, ());
+        dh.create_global::<ServerState, WlShm, ()>(1, ());
+        dh.create_global::<ServerState, WlSeat, ()>(5, ());
+        for name in output_names {
+            dh.create_global::<ServerState, WlOutput, String>(4, name.to_string());
+        }
+        dh.create_global::<ServerState, WpViewporter, ()>(1, ());
+        dh.create_global::<ServerState, ExtSessionLockManagerV1, ()>(1, ());
+        dh.create_global::<ServerState, ExtOutputImageCaptureSourceManagerV1, ()>(1, ());
+        dh.create_global::<ServerState, ExtImageCopyCaptureManagerV1, ()>(1, ());
+
+        Ok(Self {
+            display,
+            socket,
+            socket_path,
+            state: ServerState::new(output_width, output_height),
+        })
+    }
+
+    /// Path of the listening socket, for use as `WAYLAND_DISPLAY`
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Wait up to `timeout` for activity, then accept clients and process requests
+    pub fn dispatch(&mut self, timeout: Duration) -> Result<(), Box<dyn std::error::Error>> {
+        {
+            let mut fds = [
+                PollFd::new(self.socket.as_fd(), PollFlags::POLLIN),
+                PollFd::new(self.display.backend().poll_fd(), PollFlags::POLLIN),
+            ];
+            let timeout = PollTimeout::try_from(timeout.as_millis().min(i32::MAX as u128) as i32)?;
+            poll(&mut fds, timeout)?;
+        }
+
+        while let Some(stream) = self.socket.accept()? {
+            self.display.handle().insert_client(stream, Arc::new(()))?;
+        }
+
+        self.display.dispatch_clients(&mut self.state)?;
+        self.flush()
+    }
+
+    /// Send any queued events to clients
+    pub fn flush(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.display.flush_clients()?;
+        Ok(())
+    }
+}
+
+impl GlobalDispatch<WlCompositor, ()> for ServerState {
+    fn bind(
+        _state: &mut Self,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<WlCompositor>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl Dispatch<WlCompositor, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &WlCompositor,
+        request: wl_compositor::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        match request {
+            wl_compositor::Request::CreateSurface { id } => {
+                data_init.init(id, Mutex::new(SurfaceData::default()));
+            }
+            wl_compositor::Request::CreateRegion { id } => {
+                data_init.init(id, ());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlRegion, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &WlRegion,
+        _request: <WlRegion as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlSurface, Mutex<SurfaceData>> for ServerState {
+    fn request(
+        state: &mut Self,
+        _client: &Client,
+        resource: &WlSurface,
+        request: wl_surface::Request,
+        data: &Mutex<SurfaceData>,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        let mut surface = data.lock().unwrap();
+
+        match request {
+            wl_surface::Request::Attach { buffer, .. } => {
+                surface.pending_buffer = buffer;
+            }
+            wl_surface::Request::Frame { callback } => {
+                let callback = data_init.init(callback, ());
+                surface.pending_callbacks.push(callback);
+            }
+            wl_surface::Request::Commit => {
+                // Only frames displayed on lock surfaces are recorded
+                let output = state
+                    .lock_surfaces
+                    .iter()
+                    .find(|(lock_surface, _)| lock_surface == resource)
+                    .map(|(_, output)| output.clone());
+
+                if let Some(buffer) = surface.pending_buffer.take() {
+                    if let Some(output) = output {
+                        if let Some(shm) = buffer.data::<ShmBuffer>() {
+                            match shm.read_frame(&output) {
+                                Ok(frame) => state.frames.push(frame),
+                                Err(e) => panic!("Failed to read committed buffer: {e}"),
+                            }
+                        }
+                    }
+
+                    // The contents have been copied, so the client can reuse the buffer
+                    buffer.release();
+                }
+
+                let time = state.time();
+                for callback in surface.pending_callbacks.drain(..) {
+                    callback.done(time);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlCallback, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &WlCallback,
+        _request: <WlCallback as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+    }
+}
+
+impl GlobalDispatch<WlShm, ()> for ServerState {
+    fn bind(
+        _state: &mut Self,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<WlShm>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        let shm = data_init.init(resource, ());
+        shm.format(Format::Argb8888);
+        shm.format(Format::Xrgb8888);
+    }
+}
+
+impl Dispatch<WlShm, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &WlShm,
+        request: wl_shm::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        match request {
+            wl_shm::Request::CreatePool { id, fd, .. } => {
+                data_init.init(id, Arc::new(File::from(fd)));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlShmPool, Arc<File>> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &WlShmPool,
+        request: wl_shm_pool::Request,
+        data: &Arc<File>,
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        match request {
+            wl_shm_pool::Request::CreateBuffer {
+                id,
+                offset,
+                width,
+                height,
+                stride,
+                format,
+            } => {
+                let format = match format {
+                    WEnum::Value(format) => format,
+                    WEnum::Unknown(value) => panic!("Unknown buffer format: 0x{value:08x}"),
+                };
+
+                data_init.init(
+                    id,
+                    ShmBuffer {
+                        pool: data.clone(),
+                        offset,
+                        width,
+                        height,
+                        stride,
+                        format,
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlBuffer, ShmBuffer> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &WlBuffer,
+        _request: wl_buffer::Request,
+        _data: &ShmBuffer,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+    }
+}
+
+impl GlobalDispatch<WlSeat, ()> for ServerState {
+    fn bind(
+        _state: &mut Self,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<WlSeat>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        let seat = data_init.init(resource, ());
+        seat.capabilities(Capability::Keyboard);
+        seat.name("seat0".to_string());
+    }
+}
+
+impl Dispatch<WlSeat, ()> for ServerState {
+    fn request(
+        state: &mut Self,
+        _client: &Client,
+        _resource: &WlSeat,
+        request: wl_seat::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        match request {
+            wl_seat::Request::GetKeyboard { id } => {
+                let keyboard = data_init.init(id, ());
+
+                let keymap = keymap_text();
+                let fd = memfd_create("tlockr-test-keymap", MFdFlags::empty())
+                    .expect("Failed to create keymap file");
+                let mut file = File::from(fd);
+                file.write_all(keymap.as_bytes())
+                    .and_then(|_| file.write_all(&[0]))
+                    .expect("Failed to write keymap");
+
+                keyboard.keymap(KeymapFormat::XkbV1, file.as_fd(), keymap.len() as u32 + 1);
+
+                // Disable key repeat, so held keys never produce extra input
+                keyboard.repeat_info(0, 0);
+
+                state.send_enter(&keyboard);
+                state.keyboards.push(keyboard);
+            }
+            wl_seat::Request::GetPointer { id } => {
+                data_init.init(id, ());
+            }
+            wl_seat::Request::GetTouch { id } => {
+                data_init.init(id, ());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlKeyboard, ()> for ServerState {
+    fn request(
+        state: &mut Self,
+        _client: &Client,
+        resource: &WlKeyboard,
+        request: wl_keyboard::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+        match request {
+            wl_keyboard::Request::Release => {
+                state.keyboards.retain(|k| k != resource);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlPointer, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &WlPointer,
+        _request: <WlPointer as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlTouch, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &WlTouch,
+        _request: <WlTouch as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+    }
+}
+
+impl GlobalDispatch<WlOutput, String> for ServerState {
+    fn bind(
+        state: &mut Self,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<WlOutput>,
+        name: &String,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        let output = data_init.init(resource, name.clone());
+        output.geometry(
+            0,
+            0,
+            0,
+            0,
+            wl_output::Subpixel::Unknown,
+            "tlockr".to_string(),
+            "Test Output".to_string(),
+            wl_output::Transform::Normal,
+        );
+        output.mode(
+            wl_output::Mode::Current | wl_output::Mode::Preferred,
+            state.output_width,
+            state.output_height,
+            60000,
+        );
+        output.scale(1);
+        if output.version() >= 4 {
+            output.name(name.clone());
+            output.description(format!("tlockr Test Output {name}"));
+        }
+        output.done();
+    }
+}
+
+impl Dispatch<WlOutput, String> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &WlOutput,
+        _request: wl_output::Request,
+        _data: &String,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+    }
+}
+
+impl GlobalDispatch<WpViewporter, ()> for ServerState {
+    fn bind(
+        _state: &mut Self,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<WpViewporter>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl Dispatch<WpViewporter, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &WpViewporter,
+        request: wp_viewporter::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        match request {
+            wp_viewporter::Request::GetViewport { id, .. } => {
+                data_init.init(id, ());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WpViewport, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &WpViewport,
+        _request: wp_viewport::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+    }
+}
+
+impl GlobalDispatch<ExtSessionLockManagerV1, ()> for ServerState {
+    fn bind(
+        _state: &mut Self,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ExtSessionLockManagerV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl Dispatch<ExtSessionLockManagerV1, ()> for ServerState {
+    fn request(
+        state: &mut Self,
+        _client: &Client,
+        _resource: &ExtSessionLockManagerV1,
+        request: ext_session_lock_manager_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        match request {
+            ext_session_lock_manager_v1::Request::Lock { id } => {
+                // There is nothing else to hide, so the session is locked immediately
+                let lock = data_init.init(id, ());
+                lock.locked();
+                state.events.push(LockEvent::Locked);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ExtSessionLockV1, ()> for ServerState {
+    fn request(
+        state: &mut Self,
+        _client: &Client,
+        _resource: &ExtSessionLockV1,
+        request: ext_session_lock_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        match request {
+            ext_session_lock_v1::Request::GetLockSurface {
+                id,
+                surface,
+                output,
+            } => {
+                let lock_surface = data_init.init(id, ());
+                let serial = state.next_serial();
+                lock_surface.configure(
+                    serial,
+                    state.output_width as u32,
+                    state.output_height as u32,
+                );
+
+                let output = output.data::<String>().cloned().unwrap_or_default();
+                state.lock_surfaces.push((surface, output));
+                state.events.push(LockEvent::LockSurfaceCreated);
+
+                // Keyboards only enter the first lock surface
+                if state.lock_surfaces.len() == 1 {
+                    for keyboard in state.keyboards.clone() {
+                        state.send_enter(&keyboard);
+                    }
+                }
+            }
+            ext_session_lock_v1::Request::UnlockAndDestroy => {
+                state.lock_surfaces.clear();
+                state.events.push(LockEvent::Unlocked);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ExtSessionLockSurfaceV1, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &ExtSessionLockSurfaceV1,
+        _request: ext_session_lock_surface_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+    }
+}
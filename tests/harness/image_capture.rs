@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    image_capture.rs:
+        `ext_image_copy_capture_v1` for the test compositor, capturing every
+        output as a single colour.
+*/
+
+use crate::harness::{ServerState, ShmBuffer};
+
+use std::sync::Mutex;
+use wayland_protocols::ext::{
+    image_capture_source::v1::server::{
+        ext_image_capture_source_v1::ExtImageCaptureSourceV1,
+        ext_output_image_capture_source_manager_v1::{self, ExtOutputImageCaptureSourceManagerV1},
+    },
+    image_copy_capture::v1::server::{
+        ext_image_copy_capture_frame_v1::{self, ExtImageCopyCaptureFrameV1},
+        ext_image_copy_capture_manager_v1::{self, ExtImageCopyCaptureManagerV1},
+        ext_image_copy_capture_session_v1::{self, ExtImageCopyCaptureSessionV1},
+    },
+};
+use wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+    protocol::{wl_buffer::WlBuffer, wl_shm::Format},
+};
+
+/// Colour of every captured output
+pub const SCREENSHOT_COLOUR: (u8, u8, u8) = (0x99, 0x66, 0x33);
+
+impl GlobalDispatch<ExtOutputImageCaptureSourceManagerV1, ()> for ServerState {
+    fn bind(
+        _state: &mut Self,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ExtOutputImageCaptureSourceManagerV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl Dispatch<ExtOutputImageCaptureSourceManagerV1, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &ExtOutputImageCaptureSourceManagerV1,
+        request: ext_output_image_capture_source_manager_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        if let ext_output_image_capture_source_manager_v1::Request::CreateSource {
+            source, ..
+        } = request
+        {
+            data_init.init(source, ());
+        }
+    }
+}
+
+impl Dispatch<ExtImageCaptureSourceV1, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &ExtImageCaptureSourceV1,
+        _request: <ExtImageCaptureSourceV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+    }
+}
+
+impl GlobalDispatch<ExtImageCopyCaptureManagerV1, ()> for ServerState {
+    fn bind(
+        _state: &mut Self,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ExtImageCopyCaptureManagerV1>,
+        _global_data: &(),
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        data_init.init(resource, ());
+    }
+}
+
+impl Dispatch<ExtImageCopyCaptureManagerV1, ()> for ServerState {
+    fn request(
+        state: &mut Self,
+        _client: &Client,
+        _resource: &ExtImageCopyCaptureManagerV1,
+        request: ext_image_copy_capture_manager_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        if let ext_image_copy_capture_manager_v1::Request::CreateSession { session, .. } = request {
+            let session = data_init.init(session, ());
+            session.buffer_size(state.output_width as u32, state.output_height as u32);
+            session.shm_format(Format::Xrgb8888);
+            session.done();
+        }
+    }
+}
+
+impl Dispatch<ExtImageCopyCaptureSessionV1, ()> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        _resource: &ExtImageCopyCaptureSessionV1,
+        request: ext_image_copy_capture_session_v1::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, Self>,
+    ) {
+        if let ext_image_copy_capture_session_v1::Request::CreateFrame { frame } = request {
+            data_init.init(frame, Mutex::new(None));
+        }
+    }
+}
+
+impl Dispatch<ExtImageCopyCaptureFrameV1, Mutex<Option<WlBuffer>>> for ServerState {
+    fn request(
+        _state: &mut Self,
+        _client: &Client,
+        resource: &ExtImageCopyCaptureFrameV1,
+        request: ext_image_copy_capture_frame_v1::Request,
+        data: &Mutex<Option<WlBuffer>>,
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, Self>,
+    ) {
+        match request {
+            ext_image_copy_capture_frame_v1::Request::AttachBuffer { buffer } => {
+                *data.lock().unwrap() = Some(buffer);
+            }
+            ext_image_copy_capture_frame_v1::Request::Capture => {
+                let filled = data
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .and_then(|buffer| buffer.data::<ShmBuffer>())
+                    .is_some_and(|buffer| buffer.fill(SCREENSHOT_COLOUR).is_ok());
+
+                if filled {
+                    resource.ready();
+                } else {
+                    resource.failed(ext_image_copy_capture_frame_v1::FailureReason::Unknown);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    keymap.rs:
+        A small, self-contained XKB keymap for injecting keyboard input,
+        so tests do not depend on the xkeyboard-config data installed.
+*/
+
+/// Linux evdev keycode for the Return key
+pub const KEY_ENTER: u32 = 28;
+
+/// Keys available in the test keymap: (XKB key name, evdev keycode, character)
+const LETTER_KEYS: &[(&str, u32, char)] = &[
+    ("AD01", 16, 'q'),
+    ("AD02", 17, 'w'),
+    ("AD03", 18, 'e'),
+    ("AD04", 19, 'r'),
+    ("AD05", 20, 't'),
+    ("AD06", 21, 'y'),
+    ("AD07", 22, 'u'),
+    ("AD08", 23, 'i'),
+    ("AD09", 24, 'o'),
+    ("AD10", 25, 'p'),
+    ("AC01", 30, 'a'),
+    ("AC02", 31, 's'),
+    ("AC03", 32, 'd'),
+    ("AC04", 33, 'f'),
+    ("AC05", 34, 'g'),
+    ("AC06", 35, 'h'),
+    ("AC07", 36, 'j'),
+    ("AC08", 37, 'k'),
+    ("AC09", 38, 'l'),
+    ("AB01", 44, 'z'),
+    ("AB02", 45, 'x'),
+    ("AB03", 46, 'c'),
+    ("AB04", 47, 'v'),
+    ("AB05", 48, 'b'),
+    ("AB06", 49, 'n'),
+    ("AB07", 50, 'm'),
+];
+
+/// Returns the evdev keycode that produces `c`, if it is in the test keymap
+pub fn keycode_for(c: char) -> Option<u32> {
+    LETTER_KEYS
+        .iter()
+        .find(|(_, _, key)| *key == c)
+        .map(|(_, code, _)| *code)
+}
+
+/// Generate the text of the test keymap, in `XKB_KEYMAP_FORMAT_TEXT_V1`
+///
+/// XKB keycodes are offset from evdev keycodes by 8.
+pub fn keymap_text() -> String {
+    let mut keycodes = String::new();
+    let mut symbols = String::new();
+
+    keycodes.push_str(&format!("        <RTRN> = {};\n", KEY_ENTER + 8));
+    symbols.push_str("        key <RTRN> { [ Return ] };\n");
+
+    for (name, code, c) in LETTER_KEYS {
+        keycodes.push_str(&format!("        <{}> = {};\n", name, code + 8));
+        symbols.push_str(&format!(
+            "        key <{}> {{ type = \"ALPHABETIC\", [ {}, {} ] }};\n",
+            name,
+            c,
+            c.to_ascii_uppercase()
+        ));
+    }
+
+    format!(
+        r#"xkb_keymap {{
+    xkb_keycodes "tlockr-test" {{
+        minimum = 8;
+        maximum = 255;
+{keycodes}    }};
+    xkb_types "tlockr-test" {{
+        type "ONE_LEVEL" {{
+            modifiers = none;
+            level_name[Level1] = "Any";
+        }};
+        type "ALPHABETIC" {{
+            modifiers = Shift + Lock;
+            map[Shift] = Level2;
+            map[Lock] = Level2;
+            level_name[Level1] = "Base";
+            level_name[Level2] = "Caps";
+        }};
+    }};
+    xkb_compatibility "tlockr-test" {{
+    }};
+    xkb_symbols "tlockr-test" {{
+{symbols}    }};
+}};
+"#
+    )
+}
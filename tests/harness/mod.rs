@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    mod.rs:
+        Harness for running tlockr end-to-end against a headless compositor
+*/
+
+mod bus;
+mod command;
+mod compositor;
+mod image_capture;
+mod keymap;
+mod mpris;
+mod notifications;
+mod session;
+
+pub use bus::*;
+pub use command::*;
+pub use compositor::*;
+pub use image_capture::*;
+pub use keymap::*;
+pub use mpris::*;
+pub use notifications::*;
+pub use session::*;
@@ -0,0 +1,139 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    mpris.rs:
+        A mock MPRIS media player, for testing media player support.
+*/
+
+use crate::harness::TestBus;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use zbus::{
+    blocking::{Connection, connection::Builder},
+    interface,
+    object_server::SignalEmitter,
+    zvariant::{OwnedValue, Value},
+};
+
+/// Bus name of the mock player
+const MOCK_PLAYER_NAME: &str = "org.mpris.MediaPlayer2.tlockr_test";
+
+/// Name of the mock player, shown to themes
+pub const MOCK_PLAYER_IDENTITY: &str = "Mock Player";
+
+/// State of the mock player, shared with the test
+#[derive(Default)]
+pub struct MockPlayerState {
+    pub playing: bool,
+    pub title: String,
+    pub artist: String,
+    pub actions: Vec<String>,
+}
+
+/// The `org.mpris.MediaPlayer2` interface of the mock player
+struct MockRoot;
+
+#[interface(name = "org.mpris.MediaPlayer2")]
+impl MockRoot {
+    #[zbus(property)]
+    fn identity(&self) -> String {
+        MOCK_PLAYER_IDENTITY.to_string()
+    }
+}
+
+/// The `org.mpris.MediaPlayer2.Player` interface of the mock player
+struct MockPlayerInterface {
+    state: Arc<Mutex<MockPlayerState>>,
+}
+
+#[interface(name = "org.mpris.MediaPlayer2.Player")]
+impl MockPlayerInterface {
+    async fn play_pause(&self, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.playing = !state.playing;
+            state.actions.push("PlayPause".to_string());
+        }
+
+        let _ = self.playback_status_changed(&emitter).await;
+    }
+
+    #[zbus(property)]
+    fn playback_status(&self) -> String {
+        match self.state.lock().unwrap().playing {
+            true => "Playing".to_string(),
+            false => "Paused".to_string(),
+        }
+    }
+
+    #[zbus(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        let state = self.state.lock().unwrap();
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "xesam:title".to_string(),
+            Value::from(state.title.as_str()).try_into().unwrap(),
+        );
+        metadata.insert(
+            "xesam:artist".to_string(),
+            Value::from(vec![state.artist.as_str()]).try_into().unwrap(),
+        );
+        metadata
+    }
+
+    #[zbus(property)]
+    fn can_control(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+}
+
+/// A mock media player, registered on a `TestBus` until dropped
+pub struct MockPlayer {
+    _connection: Connection,
+    pub state: Arc<Mutex<MockPlayerState>>,
+}
+
+impl MockPlayer {
+    /// Register a playing mock player on `bus`
+    pub fn start(bus: &TestBus, title: &str, artist: &str) -> Self {
+        let state = Arc::new(Mutex::new(MockPlayerState {
+            playing: true,
+            title: title.to_string(),
+            artist: artist.to_string(),
+            actions: Vec::new(),
+        }));
+
+        let connection = Builder::address(bus.address.as_str())
+            .and_then(|builder| builder.name(MOCK_PLAYER_NAME))
+            .and_then(|builder| builder.serve_at("/org/mpris/MediaPlayer2", MockRoot))
+            .and_then(|builder| {
+                builder.serve_at(
+                    "/org/mpris/MediaPlayer2",
+                    MockPlayerInterface {
+                        state: state.clone(),
+                    },
+                )
+            })
+            .and_then(|builder| builder.build())
+            .expect("Failed to register mock media player");
+
+        Self {
+            _connection: connection,
+            state,
+        }
+    }
+}
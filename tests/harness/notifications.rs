@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    notifications.rs:
+        A mock notification server, for testing notification support.
+*/
+
+use crate::harness::TestBus;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use zbus::{
+    blocking::{Connection, connection::Builder},
+    interface,
+    zvariant::{OwnedValue, Value},
+};
+
+/// Bus name of notification servers
+const NOTIFICATIONS_NAME: &str = "org.freedesktop.Notifications";
+
+/// Object path of notification servers
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+
+/// The `org.freedesktop.Notifications` interface of the mock server
+struct MockNotificationInterface {
+    last_id: Arc<Mutex<u32>>,
+}
+
+#[interface(name = "org.freedesktop.Notifications")]
+impl MockNotificationInterface {
+    /// Returns `replaces_id` if it is set, otherwise a new ID
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        _app_name: String,
+        replaces_id: u32,
+        _app_icon: String,
+        _summary: String,
+        _body: String,
+        _actions: Vec<String>,
+        _hints: HashMap<String, OwnedValue>,
+        _expire_timeout: i32,
+    ) -> u32 {
+        if replaces_id != 0 {
+            return replaces_id;
+        }
+
+        let mut last_id = self.last_id.lock().unwrap();
+        *last_id += 1;
+        *last_id
+    }
+}
+
+/// A mock notification server registered on a `TestBus`, with a client
+/// connection to send notifications through it
+pub struct MockNotificationServer {
+    _connection: Connection,
+    client: Connection,
+}
+
+impl MockNotificationServer {
+    /// Register the mock notification server on `bus`
+    pub fn start(bus: &TestBus) -> Self {
+        let connection = Builder::address(bus.address.as_str())
+            .and_then(|builder| builder.name(NOTIFICATIONS_NAME))
+            .and_then(|builder| {
+                builder.serve_at(
+                    NOTIFICATIONS_PATH,
+                    MockNotificationInterface {
+                        last_id: Arc::default(),
+                    },
+                )
+            })
+            .and_then(|builder| builder.build())
+            .expect("Failed to register mock notification server");
+
+        let client = Builder::address(bus.address.as_str())
+            .and_then(|builder| builder.build())
+            .expect("Failed to connect notification client");
+
+        Self {
+            _connection: connection,
+            client,
+        }
+    }
+
+    /// Send a notification, returning the ID given by the server
+    pub fn notify(&self, app_name: &str, replaces_id: u32, summary: &str, body: &str) -> u32 {
+        let reply = self
+            .client
+            .call_method(
+                Some(NOTIFICATIONS_NAME),
+                NOTIFICATIONS_PATH,
+                Some(NOTIFICATIONS_NAME),
+                "Notify",
+                &(
+                    app_name,
+                    replaces_id,
+                    "",
+                    summary,
+                    body,
+                    Vec::<&str>::new(),
+                    HashMap::<&str, Value>::new(),
+                    -1i32,
+                ),
+            )
+            .expect("Failed to send notification");
+
+        reply.body().deserialize().expect("Invalid notification ID")
+    }
+}
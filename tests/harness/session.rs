@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// Copyright (C) 2025, Nathan Gill
+
+/*
+    session.rs:
+        Runs the tlockr binary against a `TestCompositor`, in an isolated
+        environment using Qt's offscreen platform and software rendering.
+*/
+
+use crate::harness::{KEY_ENTER, ServerState, TestCompositor};
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Default time to wait for a condition before failing a test
+///
+/// Software rendering on a loaded CI machine can be slow, so this is generous.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Interval between compositor dispatches while waiting
+const DISPATCH_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Output size used by tests, kept small to make software rendering fast
+pub const OUTPUT_WIDTH: i32 = 320;
+pub const OUTPUT_HEIGHT: i32 = 240;
+
+/// Name of the test compositor's output
+pub const TEST_OUTPUT_NAME: &str = "TEST-1";
+
+/// Name of the test compositor's second output, see `SessionOptions`
+pub const SECOND_OUTPUT_NAME: &str = "TEST-2";
+
+/// Number of sessions started by this process
+static SESSION_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the path of a file or directory in the `tests/fixtures` directory
+pub fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+/// Extra configuration for a `TestSession`
+#[derive(Default)]
+pub struct SessionOptions {
+    /// Contents of the tlockr configuration file
+    pub config: String,
+    /// Environment variables set for tlockr, in addition to the defaults
+    pub env: Vec<(String, String)>,
+    /// Whether the compositor has a second output, named `SECOND_OUTPUT_NAME`
+    pub second_output: bool,
+}
+
+/// A running tlockr process, connected to its own compositor
+pub struct TestSession {
+    pub compositor: TestCompositor,
+    child: Option<Child>,
+    runtime_dir: PathBuf,
+}
+
+impl TestSession {
+    /// Start a compositor, then run `tlockr run` in develop mode with the
+    /// mock authenticator, using the theme at `theme_dir`
+    ///
+    /// `args` are appended to the `run` subcommand, and can be used to
+    /// configure the mock authenticator.
+    pub fn start(theme_dir: &Path, args: &[&str]) -> Self {
+        Self::start_with(theme_dir, args, &SessionOptions::default())
+    }
+
+    /// Start a session as with `start`, with extra configuration
+    pub fn start_with(theme_dir: &Path, args: &[&str], options: &SessionOptions) -> Self {
+        // Tests run in parallel, so each session needs its own directory
+        let runtime_dir = std::env::temp_dir().join(format!(
+            "tlockr-test-{}-{}",
+            std::process::id(),
+            SESSION_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_dir_all(&runtime_dir);
+        std::fs::create_dir_all(&runtime_dir).expect("Failed to create runtime directory");
+
+        let config_dir = runtime_dir.join("config").join("tlockr");
+        std::fs::create_dir_all(&config_dir).expect("Failed to create config directory");
+        std::fs::write(config_dir.join("tlockr.toml"), &options.config)
+            .expect("Failed to write config file");
+
+        let config_dirs = runtime_dir.join("etc").join("xdg");
+        let data_dirs = runtime_dir.join("usr").join("share");
+        for dir in [&config_dirs, &data_dirs] {
+            std::fs::create_dir_all(dir).expect("Failed to create XDG directory");
+        }
+
+        let output_names: &[&str] = if options.second_output {
+            &[TEST_OUTPUT_NAME, SECOND_OUTPUT_NAME]
+        } else {
+            &[TEST_OUTPUT_NAME]
+        };
+        let compositor =
+            TestCompositor::new(&runtime_dir, OUTPUT_WIDTH, OUTPUT_HEIGHT, output_names)
+                .expect("Failed to start test compositor");
+
+        let child = Command::new(env!("CARGO_BIN_EXE_tlockr"))
+            .arg("--log-level")
+            .arg("debug")
+            .arg("run")
+            .arg("--develop")
+            .arg("--mock-auth")
+            .args(args)
+            .arg(theme_dir)
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", &runtime_dir)
+            .env("XDG_RUNTIME_DIR", &runtime_dir)
+            .env("XDG_CONFIG_HOME", runtime_dir.join("config"))
+            // Configuration and themes installed on the host are not used
+            .env("XDG_CONFIG_DIRS", &config_dirs)
+            .env("XDG_DATA_DIRS", &data_dirs)
+            .env("WAYLAND_DISPLAY", compositor.socket_path())
+            // Render without a GPU or display server
+            .env("QT_QPA_PLATFORM", "offscreen")
+            .env("LIBGL_ALWAYS_SOFTWARE", "1")
+            // An X server, such as Xvfb, may be needed to create a GL context
+            .envs(
+                std::env::vars_os()
+                    .filter(|(key, _)| matches!(key.to_str(), Some("DISPLAY" | "XAUTHORITY"))),
+            )
+            .envs(options.env.iter().cloned())
+            .stdin(Stdio::null())
+            .spawn()
+            .expect("Failed to start tlockr");
+
+        Self {
+            compositor,
+            child: Some(child),
+            runtime_dir,
+        }
+    }
+
+    /// Dispatch the compositor until `condition` holds, or panic after `timeout`
+    ///
+    /// `what` describes the condition, for failure messages. This also fails
+    /// early if tlockr exits.
+    pub fn wait_for(
+        &mut self,
+        what: &str,
+        timeout: Duration,
+        condition: impl Fn(&ServerState) -> bool,
+    ) {
+        let deadline = Instant::now() + timeout;
+
+        while !condition(&self.compositor.state) {
+            if Instant::now() >= deadline {
+                panic!("Timed out waiting for {what}");
+            }
+
+            if let Some(status) = self.try_wait() {
+                panic!("tlockr exited with {status} while waiting for {what}");
+            }
+
+            self.compositor
+                .dispatch(DISPATCH_INTERVAL)
+                .expect("Failed to dispatch compositor");
+        }
+    }
+
+    /// Dispatch the compositor for `duration`, regardless of what happens
+    pub fn run_for(&mut self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+
+        while Instant::now() < deadline {
+            self.compositor
+                .dispatch(DISPATCH_INTERVAL)
+                .expect("Failed to dispatch compositor");
+        }
+    }
+
+    /// Type `text` followed by Return, and send the input to tlockr
+    pub fn submit(&mut self, text: &str) {
+        self.compositor.state.type_text(text);
+        self.compositor.state.tap_key(KEY_ENTER);
+        self.compositor.flush().expect("Failed to flush compositor");
+    }
+
+    /// Returns the exit status of tlockr, if it has exited
+    fn try_wait(&mut self) -> Option<ExitStatus> {
+        self.child
+            .as_mut()
+            .and_then(|child| child.try_wait().expect("Failed to poll tlockr"))
+    }
+
+    /// Wait for tlockr to exit, while continuing to dispatch the compositor
+    pub fn wait_exit(&mut self, timeout: Duration) -> ExitStatus {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if let Some(status) = self.try_wait() {
+                self.child = None;
+                return status;
+            }
+
+            if Instant::now() >= deadline {
+                panic!("Timed out waiting for tlockr to exit");
+            }
+
+            self.compositor
+                .dispatch(DISPATCH_INTERVAL)
+                .expect("Failed to dispatch compositor");
+        }
+    }
+}
+
+impl Drop for TestSession {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+
+        let _ = std::fs::remove_dir_all(&self.runtime_dir);
+    }
+}
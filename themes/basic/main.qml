@@ -34,7 +34,7 @@ Rectangle {
             id: background
             visible: true
             anchors.fill: parent
-            color: "#FFFFFF"
+            color: tlockr.settings.background
 
             Rectangle {
                 id: backgroundBorder
@@ -69,9 +69,9 @@ Rectangle {
                 horizontalCenter: parent.horizontalCenter
             }
             echoMode: TextInput.Password
-            color: "#000000"
-            selectionColor: "#000000"
-            selectedTextColor: "#FFFFFF"
+            color: tlockr.settings.foreground
+            selectionColor: tlockr.settings.foreground
+            selectedTextColor: tlockr.settings.background
             clip: true
             horizontalAlignment: TextInput.AlignHCenter
             verticalAlignment: TextInput.AlignVCenter